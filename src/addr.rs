@@ -10,7 +10,21 @@ use crate::structures::paging::page_table::PageTableLevel;
 use crate::structures::paging::{PageOffset, PageTableIndex};
 use bit_field::BitField;
 
-const ADDRESS_SPACE_SIZE: u64 = 0x1_0000_0000_0000;
+/// The number of bits of a virtual address that are translated by the page tables.
+#[cfg(not(feature = "la57"))]
+const VIRT_ADDR_BITS: u32 = 48;
+/// The number of bits of a virtual address that are translated by the page tables.
+#[cfg(feature = "la57")]
+const VIRT_ADDR_BITS: u32 = 57;
+
+const ADDRESS_SPACE_SIZE: u64 = 1 << VIRT_ADDR_BITS;
+
+/// The index of the most significant bit of a virtual address, which is
+/// sign-extended to the upper bits.
+const SIGN_BIT: usize = VIRT_ADDR_BITS as usize - 1;
+/// The value of the bits starting at `SIGN_BIT` for addresses in the higher
+/// half.
+const SIGN_EXTENSION: u64 = (1 << (64 - SIGN_BIT)) - 1;
 
 /// A canonical 64-bit virtual memory address.
 ///
@@ -22,6 +36,10 @@ const ADDRESS_SPACE_SIZE: u64 = 0x1_0000_0000_0000;
 /// On `x86_64`, only the 48 lower bits of a virtual address can be used. The top 16 bits need
 /// to be copies of bit 47, i.e. the most significant bit. Addresses that fulfil this criterion
 /// are called “canonical”. This type guarantees that it always represents a canonical address.
+///
+/// With 5-level paging (`CR4.LA57`), the 57 lower bits of a virtual address can be used and the
+/// top 7 bits need to be copies of bit 56. This crate uses this definition of canonical addresses
+/// instead when the `la57` feature is enabled.
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(transparent)]
 pub struct VirtAddr(u64);
@@ -41,12 +59,13 @@ pub struct PhysAddr(u64);
 
 /// A passed `u64` was not a valid virtual address.
 ///
-/// This means that bits 48 to 64 are not
-/// a valid sign extension and are not null either. So automatic sign extension would have
+/// This means that the bits above the most significant address bit (bit 47, or bit 56 with the
+/// `la57` feature) are not a valid sign extension and are not null either. So automatic sign extension would have
 /// overwritten possibly meaningful bits. This likely indicates a bug, for example an invalid
 /// address calculation.
 ///
 /// Contains the invalid address.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct VirtAddrNotValid(pub u64);
 
 impl core::fmt::Debug for VirtAddrNotValid {
@@ -66,13 +85,18 @@ impl VirtAddr {
     /// ## Panics
     ///
     /// This function panics if the bits in the range 48 to 64 are invalid
-    /// (i.e. are not a proper sign extension of bit 47).
+    /// (i.e. are not a proper sign extension of bit 47). With the `la57`
+    /// feature, the bits in the range 57 to 64 must be a sign extension of
+    /// bit 56 instead.
     #[inline]
     pub const fn new(addr: u64) -> VirtAddr {
         // TODO: Replace with .ok().expect(msg) when that works on stable.
         match Self::try_new(addr) {
             Ok(v) => v,
+            #[cfg(not(feature = "la57"))]
             Err(_) => panic!("virtual address must be sign extended in bits 48 to 64"),
+            #[cfg(feature = "la57")]
+            Err(_) => panic!("virtual address must be sign extended in bits 57 to 64"),
         }
     }
 
@@ -81,7 +105,21 @@ impl VirtAddr {
     /// This function checks wether the given address is canonical
     /// and returns an error otherwise. An address is canonical
     /// if bits 48 to 64 are a correct sign
-    /// extension (i.e. copies of bit 47).
+    /// extension (i.e. copies of bit 47), or bits 57 to 64 with the `la57`
+    /// feature. Unlike [`new_truncate`](Self::new_truncate), the address is never
+    /// modified, and the error contains the invalid address.
+    ///
+    /// This function can be used in const contexts:
+    ///
+    /// ```
+    /// use x86_64::VirtAddr;
+    ///
+    /// const ADDR: Option<VirtAddr> = match VirtAddr::try_new(0xffff_8000_0000_0000) {
+    ///     Ok(addr) => Some(addr),
+    ///     Err(_) => None,
+    /// };
+    /// assert!(ADDR.is_some());
+    /// ```
     #[inline]
     pub const fn try_new(addr: u64) -> Result<VirtAddr, VirtAddrNotValid> {
         let v = Self::new_truncate(addr);
@@ -95,20 +133,23 @@ impl VirtAddr {
     /// Creates a new canonical virtual address, throwing out bits 48..64.
     ///
     /// This function performs sign extension of bit 47 to make the address
-    /// canonical, overwriting bits 48 to 64. If you want to check whether an
+    /// canonical, overwriting bits 48 to 64. With the `la57` feature, bit 56
+    /// is sign extended instead. If you want to check whether an
     /// address is canonical, use [`new`](Self::new) or [`try_new`](Self::try_new).
     #[inline]
     pub const fn new_truncate(addr: u64) -> VirtAddr {
         // By doing the right shift as a signed operation (on a i64), it will
         // sign extend the value, repeating the leftmost bit.
-        VirtAddr(((addr << 16) as i64 >> 16) as u64)
+        const SHIFT: u32 = 64 - VIRT_ADDR_BITS;
+        VirtAddr(((addr << SHIFT) as i64 >> SHIFT) as u64)
     }
 
     /// Creates a new virtual address, without any checks.
     ///
     /// ## Safety
     ///
-    /// You must make sure bits 48..64 are equal to bit 47. This is not checked.
+    /// You must make sure bits 48..64 are equal to bit 47 (or bits 57..64 are
+    /// equal to bit 56 with the `la57` feature). This is not checked.
     #[inline]
     pub const unsafe fn new_unsafe(addr: u64) -> VirtAddr {
         VirtAddr(addr)
@@ -134,13 +175,29 @@ impl VirtAddr {
     }
 
     /// Converts the address to a raw pointer.
+    ///
+    /// ## Panics
+    ///
+    /// With debug assertions enabled, this panics if the address is not aligned for `T`. To
+    /// create an unaligned pointer, e.g. for `read_unaligned`, use `as_ptr::<u8>()` and cast the
+    /// result.
     #[cfg(target_pointer_width = "64")]
     #[inline]
     pub const fn as_ptr<T>(self) -> *const T {
+        debug_assert!(
+            self.0 % core::mem::align_of::<T>() as u64 == 0,
+            "virtual address is not aligned for the pointee type"
+        );
         self.as_u64() as *const T
     }
 
     /// Converts the address to a mutable raw pointer.
+    ///
+    /// ## Panics
+    ///
+    /// With debug assertions enabled, this panics if the address is not aligned for `T`. To
+    /// create an unaligned pointer, e.g. for `read_unaligned`, use `as_mut_ptr::<u8>()` and cast the
+    /// result.
     #[cfg(target_pointer_width = "64")]
     #[inline]
     pub const fn as_mut_ptr<T>(self) -> *mut T {
@@ -233,6 +290,13 @@ impl VirtAddr {
         PageTableIndex::new_truncate((self.0 >> 12 >> 9 >> 9 >> 9) as u16)
     }
 
+    /// Returns the 9-bit level 5 page table index.
+    #[cfg(feature = "la57")]
+    #[inline]
+    pub const fn p5_index(self) -> PageTableIndex {
+        PageTableIndex::new_truncate((self.0 >> 12 >> 9 >> 9 >> 9 >> 9) as u16)
+    }
+
     /// Returns the 9-bit level page table index.
     #[inline]
     pub const fn page_table_index(self, level: PageTableLevel) -> PageTableIndex {
@@ -258,7 +322,7 @@ impl VirtAddr {
         let mut steps = end.0.checked_sub(start.0)?;
 
         // Mask away extra bits that appear while jumping the gap.
-        steps &= 0xffff_ffff_ffff;
+        steps &= ADDRESS_SPACE_SIZE - 1;
 
         Some(steps)
     }
@@ -278,10 +342,10 @@ impl VirtAddr {
 
         let mut addr = start.0.checked_add(count)?;
 
-        match addr.get_bits(47..) {
+        match addr.get_bits(SIGN_BIT..) {
             0x1 => {
-                // Jump the gap by sign extending the 47th bit.
-                addr.set_bits(47.., 0x1ffff);
+                // Jump the gap by sign extending the most significant bit.
+                addr.set_bits(SIGN_BIT.., SIGN_EXTENSION);
             }
             0x2 => {
                 // Address overflow
@@ -303,12 +367,14 @@ impl VirtAddr {
 
         let mut addr = start.0.checked_sub(count)?;
 
-        match addr.get_bits(47..) {
-            0x1fffe => {
-                // Jump the gap by sign extending the 47th bit.
-                addr.set_bits(47.., 0);
+        const GAP: u64 = SIGN_EXTENSION - 1;
+        const UNDERFLOW: u64 = SIGN_EXTENSION - 2;
+        match addr.get_bits(SIGN_BIT..) {
+            GAP => {
+                // Jump the gap by sign extending the most significant bit.
+                addr.set_bits(SIGN_BIT.., 0);
             }
-            0x1fffd => {
+            UNDERFLOW => {
                 // Address underflow
                 return None;
             }
@@ -319,6 +385,23 @@ impl VirtAddr {
     }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for VirtAddr {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for VirtAddr {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let addr = <u64 as serde::Deserialize>::deserialize(deserializer)?;
+        VirtAddr::try_new(addr).map_err(|_| {
+            serde::de::Error::custom(format_args!("virtual address {:#x} is not canonical", addr))
+        })
+    }
+}
+
 impl fmt::Debug for VirtAddr {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_tuple("VirtAddr")
@@ -423,6 +506,7 @@ impl Step for VirtAddr {
 /// This means that bits 52 to 64 were not all null.
 ///
 /// Contains the invalid address.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct PhysAddrNotValid(pub u64);
 
 impl core::fmt::Debug for PhysAddrNotValid {
@@ -544,6 +628,69 @@ impl PhysAddr {
     pub(crate) const fn is_aligned_u64(self, align: u64) -> bool {
         self.align_down_u64(align).as_u64() == self.as_u64()
     }
+
+    /// Checked addition. Returns `None` if the result has a bit in the range 52 to 64 set.
+    #[inline]
+    pub const fn checked_add(self, rhs: u64) -> Option<Self> {
+        match self.0.checked_add(rhs) {
+            Some(addr) => match Self::try_new(addr) {
+                Ok(addr) => Some(addr),
+                Err(_) => None,
+            },
+            None => None,
+        }
+    }
+
+    /// Checked subtraction. Returns `None` if the result would be negative.
+    #[inline]
+    pub const fn checked_sub(self, rhs: u64) -> Option<Self> {
+        match self.0.checked_sub(rhs) {
+            Some(addr) => Some(PhysAddr(addr)),
+            None => None,
+        }
+    }
+
+    /// Wrapping addition, wrapping around at the 52-bit boundary.
+    #[inline]
+    pub const fn wrapping_add(self, rhs: u64) -> Self {
+        Self::new_truncate(self.0.wrapping_add(rhs))
+    }
+
+    /// Wrapping subtraction, wrapping around at the 52-bit boundary.
+    #[inline]
+    pub const fn wrapping_sub(self, rhs: u64) -> Self {
+        Self::new_truncate(self.0.wrapping_sub(rhs))
+    }
+
+    /// Saturating addition. Returns the highest physical address `0xf_ffff_ffff_ffff` if the
+    /// result has a bit in the range 52 to 64 set.
+    #[inline]
+    pub const fn saturating_add(self, rhs: u64) -> Self {
+        match self.checked_add(rhs) {
+            Some(addr) => addr,
+            None => PhysAddr((1 << 52) - 1),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for PhysAddr {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for PhysAddr {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let addr = <u64 as serde::Deserialize>::deserialize(deserializer)?;
+        PhysAddr::try_new(addr).map_err(|_| {
+            serde::de::Error::custom(format_args!(
+                "physical address {:#x} has non-zero bits in the range 52 to 64",
+                addr
+            ))
+        })
+    }
 }
 
 impl fmt::Debug for PhysAddr {
@@ -663,6 +810,40 @@ pub const fn align_up(addr: u64, align: u64) -> u64 {
 mod tests {
     use super::*;
 
+    #[test]
+    pub fn physaddr_try_new() {
+        const MAX: Result<PhysAddr, PhysAddrNotValid> = PhysAddr::try_new(0x000f_ffff_ffff_ffff);
+        assert_eq!(MAX, Ok(PhysAddr::new(0x000f_ffff_ffff_ffff)));
+        assert_eq!(
+            PhysAddr::try_new(0x0010_0000_0000_1000),
+            Err(PhysAddrNotValid(0x0010_0000_0000_1000))
+        );
+        // Bits 52 to 64 of a page table entry contain flags.
+        assert_eq!(
+            PhysAddr::new_truncate(0x8010_0000_0000_1000),
+            PhysAddr::new(0x1000)
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "la57"))]
+    pub fn virtaddr_try_new() {
+        assert_eq!(
+            VirtAddr::try_new(0x0000_7fff_ffff_ffff),
+            Ok(VirtAddr::new(0x0000_7fff_ffff_ffff))
+        );
+        assert_eq!(
+            VirtAddr::try_new(0xffff_8000_0000_0000),
+            Ok(VirtAddr::new(0xffff_8000_0000_0000))
+        );
+        let err = VirtAddr::try_new(0x0000_8000_0000_0000).unwrap_err();
+        assert_eq!(err.0, 0x0000_8000_0000_0000);
+        assert_eq!(
+            VirtAddr::try_new(0xffff_7fff_ffff_ffff),
+            Err(VirtAddrNotValid(0xffff_7fff_ffff_ffff))
+        );
+    }
+
     #[test]
     #[should_panic]
     pub fn add_overflow_virtaddr() {
@@ -687,6 +868,33 @@ mod tests {
         let _ = PhysAddr::new(0) - 1;
     }
 
+    #[test]
+    pub fn physaddr_checked_wrapping_saturating() {
+        let max = PhysAddr::new(0xf_ffff_ffff_ffff);
+        assert_eq!(
+            PhysAddr::new(0x1000).checked_add(0x1000),
+            Some(PhysAddr::new(0x2000))
+        );
+        assert_eq!(max.checked_add(0), Some(max));
+        assert_eq!(max.checked_add(1), None);
+        assert_eq!(max.checked_add(u64::MAX), None);
+        assert_eq!(
+            PhysAddr::new(0x1000).checked_sub(0x1000),
+            Some(PhysAddr::zero())
+        );
+        assert_eq!(PhysAddr::zero().checked_sub(1), None);
+
+        assert_eq!(max.wrapping_add(1), PhysAddr::zero());
+        assert_eq!(PhysAddr::zero().wrapping_sub(1), max);
+
+        assert_eq!(max.saturating_add(1), max);
+        assert_eq!(
+            PhysAddr::new(0x1000).saturating_add(0x1000),
+            PhysAddr::new(0x2000)
+        );
+    }
+
+    #[cfg(not(feature = "la57"))]
     #[test]
     pub fn virtaddr_new_truncate() {
         assert_eq!(VirtAddr::new_truncate(0), VirtAddr(0));
@@ -695,8 +903,41 @@ mod tests {
         assert_eq!(VirtAddr::new_truncate(123 << 47), VirtAddr(0xfffff << 47));
     }
 
+    #[cfg(feature = "la57")]
     #[test]
-    #[cfg(feature = "step_trait")]
+    pub fn virtaddr_new_truncate_la57() {
+        assert_eq!(VirtAddr::new_truncate(1 << 47), VirtAddr(1 << 47));
+        assert_eq!(VirtAddr::new_truncate(1 << 56), VirtAddr(0xff << 56));
+        assert_eq!(VirtAddr::new_truncate(123 << 56), VirtAddr(0xff << 56));
+        assert!(VirtAddr::try_new(1 << 56).is_err());
+        assert_eq!(
+            VirtAddr::new(0x0001_2345_6789_a000).p5_index(),
+            PageTableIndex::new(1)
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "step_trait", feature = "la57"))]
+    fn virtaddr_step_la57() {
+        assert_eq!(
+            Step::forward_checked(VirtAddr(0x00ff_ffff_ffff_ffff), 1),
+            Some(VirtAddr(0xff00_0000_0000_0000))
+        );
+        assert_eq!(
+            Step::backward_checked(VirtAddr(0xff00_0000_0000_0000), 1),
+            Some(VirtAddr(0x00ff_ffff_ffff_ffff))
+        );
+        assert_eq!(
+            Step::steps_between(
+                &VirtAddr(0x00ff_ffff_ffff_ffff),
+                &VirtAddr(0xff00_0000_0000_0000)
+            ),
+            (1, Some(1))
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "step_trait", not(feature = "la57")))]
     fn virtaddr_step_forward() {
         assert_eq!(Step::forward(VirtAddr(0), 0), VirtAddr(0));
         assert_eq!(Step::forward(VirtAddr(0), 1), VirtAddr(1));
@@ -740,7 +981,7 @@ mod tests {
     }
 
     #[test]
-    #[cfg(feature = "step_trait")]
+    #[cfg(all(feature = "step_trait", not(feature = "la57")))]
     fn virtaddr_step_backward() {
         assert_eq!(Step::backward(VirtAddr(0), 0), VirtAddr(0));
         assert_eq!(Step::backward_checked(VirtAddr(0), 1), None);
@@ -776,7 +1017,7 @@ mod tests {
     }
 
     #[test]
-    #[cfg(feature = "step_trait")]
+    #[cfg(all(feature = "step_trait", not(feature = "la57")))]
     fn virtaddr_steps_between() {
         assert_eq!(
             Step::steps_between(&VirtAddr(0), &VirtAddr(0)),
@@ -854,8 +1095,48 @@ mod tests {
         assert_eq!(align_up(0, 1), 0);
         assert_eq!(align_up(0, 2), 0);
         assert_eq!(align_up(0, 0x8000_0000_0000_0000), 0);
+        // align 4096
+        assert_eq!(align_up(1, 4096), 4096);
+        assert_eq!(align_up(4096, 4096), 4096);
+        assert_eq!(align_up(4097, 4096), 8192);
+    }
+
+    #[test]
+    pub fn test_align_down() {
+        // align 1
+        assert_eq!(align_down(0, 1), 0);
+        assert_eq!(align_down(1234, 1), 1234);
+        assert_eq!(align_down(0xffff_ffff_ffff_ffff, 1), 0xffff_ffff_ffff_ffff);
+        // align 4096
+        assert_eq!(align_down(4095, 4096), 0);
+        assert_eq!(align_down(4096, 4096), 4096);
+        assert_eq!(
+            align_down(0xffff_ffff_ffff_ffff, 4096),
+            0xffff_ffff_ffff_f000
+        );
+    }
+
+    #[test]
+    pub fn test_align_const() {
+        const STACK_TOP: u64 = align_down(0x1_0000_1234, 16);
+        const STACK_BOTTOM: u64 = align_up(0x1234, 4096);
+        assert_eq!(STACK_TOP, 0x1_0000_1230);
+        assert_eq!(STACK_BOTTOM, 0x2000);
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn test_align_up_not_power_of_two() {
+        align_up(0x1000, 3);
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn test_align_down_not_power_of_two() {
+        align_down(0x1000, 3);
     }
 
+    #[cfg(not(feature = "la57"))]
     #[test]
     fn test_virt_addr_align_up() {
         // Make sure the 47th bit is extended.
@@ -865,6 +1146,7 @@ mod tests {
         );
     }
 
+    #[cfg(not(feature = "la57"))]
     #[test]
     fn test_virt_addr_align_down() {
         // Make sure the 47th bit is extended.
@@ -886,6 +1168,23 @@ mod tests {
         PhysAddr::new(0x000f_ffff_ffff_ffff).align_up(2u64);
     }
 
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn test_as_ptr() {
+        let value = 42u64;
+        let addr = VirtAddr::from_ptr(&value);
+        assert_eq!(addr.as_ptr::<u64>(), &value as *const u64);
+        assert_eq!(unsafe { *addr.as_ptr::<u64>() }, 42);
+        assert_eq!((addr + 1u64).as_ptr::<u8>() as u64, addr.as_u64() + 1);
+    }
+
+    #[test]
+    #[cfg(all(target_pointer_width = "64", debug_assertions))]
+    #[should_panic]
+    fn test_as_ptr_unaligned() {
+        let _ = VirtAddr::new(0x1001).as_ptr::<u64>();
+    }
+
     #[test]
     #[cfg(target_pointer_width = "64")]
     fn test_from_ptr_array() {
@@ -896,6 +1195,26 @@ mod tests {
             VirtAddr::from_ptr(&slice[0])
         );
     }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn deserialize_addr() {
+        use serde::de::{value::Error, IntoDeserializer};
+        use serde::Deserialize;
+
+        let virt =
+            |addr: u64| VirtAddr::deserialize(IntoDeserializer::<Error>::into_deserializer(addr));
+        assert_eq!(
+            virt(0xffff_8000_0000_0000).unwrap(),
+            VirtAddr::new(0xffff_8000_0000_0000)
+        );
+        assert!(virt(0x1234_0000_0000_0000).is_err());
+
+        let phys =
+            |addr: u64| PhysAddr::deserialize(IntoDeserializer::<Error>::into_deserializer(addr));
+        assert_eq!(phys(0x1000).unwrap(), PhysAddr::new(0x1000));
+        assert!(phys(1 << 52).is_err());
+    }
 }
 
 #[cfg(kani)]
@@ -0,0 +1,49 @@
+//! Memory ordering barriers.
+//!
+//! Normal memory accesses on x86_64 are strongly ordered, so these instructions are rarely
+//! needed for ordinary memory; [`core::sync::atomic::fence`] is usually sufficient there.
+//! They are needed for weakly ordered accesses, e.g. non-temporal stores, write-combining
+//! memory (like a framebuffer), or [`clflushopt`](super::cache::ClFlushOpt::flush).
+//!
+//! All functions also act as compiler barriers, i.e. the compiler doesn't move memory
+//! accesses across them.
+
+use core::arch::asm;
+
+/// Serializes all loads and stores issued before this instruction using the `mfence`
+/// instruction.
+///
+/// All previous loads and stores become globally visible before any later load or store.
+/// This also orders [`clflush`](super::cache::clflush),
+/// [`clflushopt`](super::cache::ClFlushOpt::flush), and [`clwb`](super::cache::Clwb::write_back).
+#[inline]
+pub fn mfence() {
+    unsafe {
+        asm!("mfence", options(nostack, preserves_flags));
+    }
+}
+
+/// Serializes all stores issued before this instruction using the `sfence` instruction.
+///
+/// This is needed after non-temporal stores (e.g. `movnti`) or stores to write-combining
+/// memory, before signaling a device or another processor that the data was written (e.g. by
+/// writing to a doorbell register). It also orders
+/// [`clflushopt`](super::cache::ClFlushOpt::flush) and [`clwb`](super::cache::Clwb::write_back).
+#[inline]
+pub fn sfence() {
+    unsafe {
+        asm!("sfence", options(nostack, preserves_flags));
+    }
+}
+
+/// Serializes all loads issued before this instruction using the `lfence` instruction.
+///
+/// `lfence` also doesn't allow later instructions to execute before all previous instructions
+/// have completed locally, so it is commonly used as a speculation barrier (e.g. after a
+/// bounds check) and to prevent `rdtsc` from executing early.
+#[inline]
+pub fn lfence() {
+    unsafe {
+        asm!("lfence", options(nostack, preserves_flags));
+    }
+}
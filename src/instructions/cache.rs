@@ -0,0 +1,142 @@
+//! Cache maintenance instructions.
+//!
+//! [`clflush`], [`ClFlushOpt::flush`] and [`Clwb::write_back`] operate on the cache line that
+//! contains the given address, and are useful e.g. for making writes visible to devices that
+//! don't snoop the caches. Their availability is reported by CPUID:
+//!
+//! - `clflush`: CPUID.01H:EDX bit 19 (`CLFSH`)
+//! - `clflushopt`: CPUID.(EAX=07H, ECX=0H):EBX bit 23 (`CLFLUSHOPT`)
+//! - `clwb`: CPUID.(EAX=07H, ECX=0H):EBX bit 24 (`CLWB`)
+//!
+//! `clflush` is supported by all x86_64 processors in practice. `clflushopt` and `clwb` are
+//! only available through the [`ClFlushOpt`] and [`Clwb`] types, which check the CPUID bit
+//! when they are created, since the instructions throw a `#UD` if they aren't supported.
+//!
+//! `clflush` is ordered with respect to writes, but `clflushopt` and `clwb` are only ordered
+//! with respect to older writes to the same cache line. Before signaling a device that the
+//! data was written back (e.g. by writing to a doorbell register), the flushes must be
+//! completed with [`sfence`](super::barrier::sfence) or [`mfence`](super::barrier::mfence).
+
+use crate::VirtAddr;
+use core::arch::asm;
+
+/// Writes back and invalidates the cache line that contains `addr` using the `clflush`
+/// instruction.
+///
+/// Like a read, this causes a page fault if `addr` is not mapped.
+#[inline]
+pub fn clflush(addr: VirtAddr) {
+    unsafe {
+        asm!("clflush [{}]", in(reg) addr.as_u64(), options(nostack, preserves_flags));
+    }
+}
+
+/// The `clflushopt` instruction, which is only available if CPUID reports support for it.
+#[derive(Debug, Clone, Copy)]
+pub struct ClFlushOpt(());
+
+impl ClFlushOpt {
+    /// Creates Some(ClFlushOpt) if `clflushopt` is supported, None otherwise.
+    #[inline]
+    pub fn new() -> Option<Self> {
+        if super::cpuid::has_clflushopt() {
+            Some(ClFlushOpt(()))
+        } else {
+            None
+        }
+    }
+
+    /// Writes back and invalidates the cache line that contains `addr` using the `clflushopt`
+    /// instruction.
+    ///
+    /// This is like [`clflush`], but with weaker ordering guarantees, which allows multiple
+    /// flushes to execute in parallel. Like a read, this causes a page fault if `addr` is not
+    /// mapped.
+    #[inline]
+    pub fn flush(self, addr: VirtAddr) {
+        unsafe {
+            asm!("clflushopt [{}]", in(reg) addr.as_u64(), options(nostack, preserves_flags));
+        }
+    }
+}
+
+/// The `clwb` instruction, which is only available if CPUID reports support for it.
+#[derive(Debug, Clone, Copy)]
+pub struct Clwb(());
+
+impl Clwb {
+    /// Creates Some(Clwb) if `clwb` is supported, None otherwise.
+    #[inline]
+    pub fn new() -> Option<Self> {
+        if super::cpuid::has_clwb() {
+            Some(Clwb(()))
+        } else {
+            None
+        }
+    }
+
+    /// Writes back the cache line that contains `addr` using the `clwb` instruction.
+    ///
+    /// Unlike [`ClFlushOpt::flush`], the cache line may stay in the cache. Like a read, this
+    /// causes a page fault if `addr` is not mapped.
+    #[inline]
+    pub fn write_back(self, addr: VirtAddr) {
+        unsafe {
+            asm!("clwb [{}]", in(reg) addr.as_u64(), options(nostack, preserves_flags));
+        }
+    }
+}
+
+/// Writes back all modified cache lines and invalidates the caches using the `wbinvd`
+/// instruction.
+///
+/// This is very slow, especially on processors with large caches, and must be executed in
+/// ring 0.
+#[inline]
+pub fn wbinvd() {
+    unsafe {
+        asm!("wbinvd", options(nostack, preserves_flags));
+    }
+}
+
+/// Invalidates the caches without writing back modified cache lines using the `invd`
+/// instruction.
+///
+/// All data that was only written to the caches is lost, so this is almost never the right
+/// choice: use [`wbinvd`] instead. It must be executed in ring 0.
+///
+/// ## Safety
+///
+/// The caller must ensure that no cache line contains modified data that is still needed,
+/// e.g. because the caches are known to be clean or are used as RAM before memory is
+/// initialized.
+#[inline]
+pub unsafe fn invd() {
+    unsafe {
+        asm!("invd", options(nostack, preserves_flags));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flush_cache_line() {
+        let value = 42u64;
+        clflush(VirtAddr::from_ptr(&value));
+        assert_eq!(value, 42);
+    }
+
+    #[test]
+    fn optional_flushes() {
+        let value = 42u64;
+        if let Some(clflushopt) = ClFlushOpt::new() {
+            clflushopt.flush(VirtAddr::from_ptr(&value));
+        }
+        if let Some(clwb) = Clwb::new() {
+            clwb.write_back(VirtAddr::from_ptr(&value));
+        }
+        assert_eq!(value, 42);
+    }
+}
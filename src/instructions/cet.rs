@@ -0,0 +1,86 @@
+//! Access the shadow stack of the control-flow enforcement technology (CET).
+//!
+//! Shadow stacks must be enabled in [`CR4.CET`][Cr4Flags::CONTROL_FLOW_ENFORCEMENT] and in
+//! [`SCet`]/[`UCet`] (see [`CetFlags::SS_ENABLE`]). Support can be detected using
+//! CPUID.(EAX=07H, ECX=0H):ECX bit 7 (`CET_SS`).
+//!
+//! [Cr4Flags::CONTROL_FLOW_ENFORCEMENT]: crate::registers::control::Cr4Flags::CONTROL_FLOW_ENFORCEMENT
+//! [`SCet`]: crate::registers::model_specific::SCet
+//! [`UCet`]: crate::registers::model_specific::UCet
+//! [`CetFlags::SS_ENABLE`]: crate::registers::model_specific::CetFlags::SS_ENABLE
+
+use crate::VirtAddr;
+use core::arch::asm;
+
+/// Read the current shadow stack pointer using the `rdsspq` instruction.
+///
+/// If shadow stacks are not enabled (or not supported), the instruction does nothing and
+/// this function returns a null address.
+#[inline]
+pub fn rdssp() -> VirtAddr {
+    let ssp: u64;
+    unsafe {
+        asm!(
+            "mov {0:e}, 0",
+            "rdsspq {0}",
+            out(reg) ssp,
+            options(nomem, nostack, preserves_flags),
+        );
+    }
+    VirtAddr::new(ssp)
+}
+
+/// Pop `count` entries from the shadow stack using the `incsspq` instruction.
+///
+/// This is used to unwind the shadow stack, e.g. when performing a `longjmp`.
+///
+/// ## Safety
+///
+/// The regular stack must be unwound by the same number of return addresses, otherwise the
+/// next `ret` causes a control protection exception (`#CP`).
+#[inline]
+pub unsafe fn incssp(count: u8) {
+    unsafe {
+        asm!("incsspq {}", in(reg) u64::from(count), options(nostack, preserves_flags));
+    }
+}
+
+/// Save the previous shadow stack pointer using the `saveprevssp` instruction.
+///
+/// This pushes a restore token for the previous shadow stack to that stack after switching to
+/// a new shadow stack with [`rstorssp`].
+///
+/// ## Safety
+///
+/// The previous shadow stack must not be in use anymore, see the Intel SDM for details.
+#[inline]
+pub unsafe fn saveprevssp() {
+    unsafe {
+        asm!("saveprevssp", options(nostack, preserves_flags));
+    }
+}
+
+/// Switch to the shadow stack whose restore token is at `token` using the `rstorssp`
+/// instruction.
+///
+/// ## Safety
+///
+/// `token` must point to a valid restore token on a shadow stack that is not in use. The
+/// caller must also switch the regular stack to a matching one.
+#[inline]
+pub unsafe fn rstorssp(token: VirtAddr) {
+    unsafe {
+        asm!("rstorssp [{}]", in(reg) token.as_u64(), options(nostack));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rdssp_is_aligned() {
+        // Tests don't run on a shadow stack, but `rdssp` must not fault either way.
+        assert!(rdssp().is_aligned(8u64));
+    }
+}
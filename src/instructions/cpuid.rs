@@ -0,0 +1,92 @@
+//! Detect processor features using the `cpuid` instruction.
+//!
+//! This only covers the feature bits that are needed by the wrappers in this crate. Use a
+//! crate like `raw-cpuid` for everything else. Each function executes `cpuid` for the leaf
+//! that reports the feature (after checking that the leaf is supported), so the result should
+//! be cached if it is needed often.
+
+use crate::registers::model_specific::{CpuidFeature, CpuidRegister};
+
+/// CPUID.01H:ECX bit 30 (`RDRAND`).
+pub const RDRAND: CpuidFeature = CpuidFeature::new(0x1, 0, CpuidRegister::Ecx, 30);
+/// CPUID.(EAX=07H, ECX=0H):EBX bit 18 (`RDSEED`).
+pub const RDSEED: CpuidFeature = CpuidFeature::new(0x7, 0, CpuidRegister::Ebx, 18);
+/// CPUID.(EAX=07H, ECX=0H):EBX bit 0 (`FSGSBASE`).
+pub const FSGSBASE: CpuidFeature = CpuidFeature::new(0x7, 0, CpuidRegister::Ebx, 0);
+/// CPUID.(EAX=07H, ECX=0H):ECX bit 22 (`RDPID`).
+pub const RDPID: CpuidFeature = CpuidFeature::new(0x7, 0, CpuidRegister::Ecx, 22);
+/// CPUID.(EAX=07H, ECX=0H):EBX bit 23 (`CLFLUSHOPT`).
+pub const CLFLUSHOPT: CpuidFeature = CpuidFeature::new(0x7, 0, CpuidRegister::Ebx, 23);
+/// CPUID.(EAX=07H, ECX=0H):EBX bit 24 (`CLWB`).
+pub const CLWB: CpuidFeature = CpuidFeature::new(0x7, 0, CpuidRegister::Ebx, 24);
+/// CPUID.(EAX=07H, ECX=0H):ECX bit 7 (`CET_SS`).
+pub const CET_SS: CpuidFeature = CpuidFeature::new(0x7, 0, CpuidRegister::Ecx, 7);
+/// CPUID.80000001H:EDX bit 27 (`RDTSCP`).
+pub const RDTSCP: CpuidFeature = CpuidFeature::new(0x8000_0001, 0, CpuidRegister::Edx, 27);
+
+/// Returns whether the `rdrand` instruction is supported.
+#[inline]
+pub fn has_rdrand() -> bool {
+    RDRAND.is_supported()
+}
+
+/// Returns whether the `rdseed` instruction is supported.
+#[inline]
+pub fn has_rdseed() -> bool {
+    RDSEED.is_supported()
+}
+
+/// Returns whether the `rdfsbase`, `wrfsbase`, `rdgsbase` and `wrgsbase` instructions are
+/// supported.
+///
+/// The instructions must also be enabled in
+/// [`CR4.FSGSBASE`](crate::registers::control::Cr4Flags::FSGSBASE) before they can be used.
+#[inline]
+pub fn has_fsgsbase() -> bool {
+    FSGSBASE.is_supported()
+}
+
+/// Returns whether the `rdpid` instruction is supported, see [`read_pid`](super::read_pid).
+#[inline]
+pub fn has_rdpid() -> bool {
+    RDPID.is_supported()
+}
+
+/// Returns whether the `clflushopt` instruction is supported, see
+/// [`ClFlushOpt`](super::cache::ClFlushOpt).
+#[inline]
+pub fn has_clflushopt() -> bool {
+    CLFLUSHOPT.is_supported()
+}
+
+/// Returns whether the `clwb` instruction is supported, see [`Clwb`](super::cache::Clwb).
+#[inline]
+pub fn has_clwb() -> bool {
+    CLWB.is_supported()
+}
+
+/// Returns whether CET shadow stacks are supported, see the [`cet`](super::cet) module.
+#[inline]
+pub fn has_cet_ss() -> bool {
+    CET_SS.is_supported()
+}
+
+/// Returns whether the `rdtscp` instruction and the
+/// [`TscAux`](crate::registers::model_specific::TscAux) MSR are supported.
+#[inline]
+pub fn has_rdtscp() -> bool {
+    RDTSCP.is_supported()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rdrand_matches_cpuid() {
+        // `__cpuid` is safe on newer compilers but unsafe on our MSRV.
+        #[allow(unused_unsafe)]
+        let cpuid = unsafe { core::arch::x86_64::__cpuid(0x1) };
+        assert_eq!(has_rdrand(), cpuid.ecx & (1 << 30) != 0);
+    }
+}
@@ -0,0 +1,142 @@
+//! Save and restore the legacy x87 FPU, MMX and SSE state using `FXSAVE`/`FXRSTOR`.
+//!
+//! The XMM registers and MXCSR are only saved and restored if
+//! [`CR4.OSFXSR`][Cr4Flags::OSFXSR] is set.
+//!
+//! [Cr4Flags::OSFXSR]: crate::registers::control::Cr4Flags::OSFXSR
+
+use core::arch::asm;
+use core::fmt;
+
+/// The 512-byte area written by `fxsave64` and read by `fxrstor64`.
+#[repr(C, align(16))]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct FxsaveArea {
+    /// The x87 FPU control word.
+    pub fcw: u16,
+    /// The x87 FPU status word.
+    pub fsw: u16,
+    /// The abridged x87 FPU tag word, one bit per register.
+    pub ftw: u8,
+    reserved_1: u8,
+    /// The opcode of the last non-control x87 instruction.
+    pub fop: u16,
+    /// The instruction pointer of the last non-control x87 instruction.
+    pub fip: u64,
+    /// The data pointer of the last non-control x87 instruction.
+    pub fdp: u64,
+    /// The MXCSR register.
+    pub mxcsr: u32,
+    /// The bits of MXCSR that are supported by the processor.
+    pub mxcsr_mask: u32,
+    /// The ST0-ST7 (or MM0-MM7) registers. Only the lower 80 bits are used.
+    pub st: [u128; 8],
+    /// The XMM0-XMM15 registers.
+    pub xmm: [u128; 16],
+    reserved_2: [u8; 48],
+    /// Bytes that are ignored by the processor and can be used by software.
+    pub available: [u8; 48],
+}
+
+impl FxsaveArea {
+    /// Creates a new area with all fields set to zero.
+    #[inline]
+    pub const fn new() -> Self {
+        FxsaveArea {
+            fcw: 0,
+            fsw: 0,
+            ftw: 0,
+            reserved_1: 0,
+            fop: 0,
+            fip: 0,
+            fdp: 0,
+            mxcsr: 0,
+            mxcsr_mask: 0,
+            st: [0; 8],
+            xmm: [0; 16],
+            reserved_2: [0; 48],
+            available: [0; 48],
+        }
+    }
+}
+
+impl Default for FxsaveArea {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for FxsaveArea {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FxsaveArea")
+            .field("fcw", &format_args!("{:#x}", self.fcw))
+            .field("fsw", &format_args!("{:#x}", self.fsw))
+            .field("ftw", &format_args!("{:#x}", self.ftw))
+            .field("fop", &format_args!("{:#x}", self.fop))
+            .field("fip", &format_args!("{:#x}", self.fip))
+            .field("fdp", &format_args!("{:#x}", self.fdp))
+            .field("mxcsr", &format_args!("{:#x}", self.mxcsr))
+            .field("mxcsr_mask", &format_args!("{:#x}", self.mxcsr_mask))
+            .field("st", &self.st)
+            .field("xmm", &self.xmm)
+            .finish()
+    }
+}
+
+/// Save the x87 FPU, MMX and SSE state into the given area using the `fxsave64` instruction.
+#[inline]
+pub fn fxsave(area: &mut FxsaveArea) {
+    unsafe {
+        asm!("fxsave64 [{}]", in(reg) area, options(nostack, preserves_flags));
+    }
+}
+
+/// Restore the x87 FPU, MMX and SSE state from the given area using the `fxrstor64` instruction.
+///
+/// ## Safety
+///
+/// The area must contain a valid state, e.g. one that was written by [`fxsave`]. In particular,
+/// setting reserved bits in `mxcsr` causes a `#GP`.
+///
+/// The x87, MMX and SSE registers are declared as clobbered, so the compiler doesn't keep
+/// values in them across this call. The caller must ensure that the restored `mxcsr` and x87
+/// control word are what the surrounding Rust code expects.
+#[inline]
+pub unsafe fn fxrstor(area: &FxsaveArea) {
+    unsafe {
+        asm!(
+            "fxrstor64 [{}]",
+            in(reg) area,
+            out("xmm0") _, out("xmm1") _, out("xmm2") _, out("xmm3") _,
+            out("xmm4") _, out("xmm5") _, out("xmm6") _, out("xmm7") _,
+            out("xmm8") _, out("xmm9") _, out("xmm10") _, out("xmm11") _,
+            out("xmm12") _, out("xmm13") _, out("xmm14") _, out("xmm15") _,
+            out("mm0") _, out("mm1") _, out("mm2") _, out("mm3") _,
+            out("mm4") _, out("mm5") _, out("mm6") _, out("mm7") _,
+            out("st(0)") _, out("st(1)") _, out("st(2)") _, out("st(3)") _,
+            out("st(4)") _, out("st(5)") _, out("st(6)") _, out("st(7)") _,
+            options(nostack, preserves_flags, readonly),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::mem::{align_of, size_of};
+
+    #[test]
+    fn fxsave_area_layout() {
+        assert_eq!(size_of::<FxsaveArea>(), 512);
+        assert_eq!(align_of::<FxsaveArea>(), 16);
+    }
+
+    #[test]
+    fn fxsave_fxrstor() {
+        let mut area = FxsaveArea::new();
+        fxsave(&mut area);
+        assert_eq!(area.mxcsr, crate::registers::mxcsr::read().bits());
+        unsafe { fxrstor(&area) };
+    }
+}
@@ -10,11 +10,63 @@ pub fn are_enabled() -> bool {
     rflags::read().contains(RFlags::INTERRUPT_FLAG)
 }
 
+#[cfg(feature = "interrupt_tracking")]
+static DISABLE_COUNT: core::sync::atomic::AtomicUsize = core::sync::atomic::AtomicUsize::new(0);
+
+/// Returns the number of [`disable`] calls that weren't followed by an [`enable`] call yet.
+///
+/// This is only available with the `interrupt_tracking` feature, which makes [`disable`]
+/// increment and [`enable`] (and [`enable_and_hlt`]) decrement a counter. Without the feature,
+/// these functions compile to a bare `cli`/`sti`. The counter can be used to assert that
+/// `disable` and `enable` calls are balanced, e.g. in tests:
+///
+/// ```no_run
+/// use x86_64::instructions::interrupts;
+///
+/// interrupts::disable();
+/// interrupts::without_interrupts(|| assert_eq!(interrupts::disable_count(), 1));
+/// interrupts::enable();
+/// assert_eq!(interrupts::disable_count(), 0);
+/// ```
+///
+/// Note that this crate has no per-CPU storage, so there is a single counter that is shared
+/// by all processors. It is therefore only meaningful while a single processor calls these
+/// functions, e.g. before the other processors are started or in single-processor tests.
+/// [`without_interrupts`] doesn't change the counter when interrupts are already disabled.
+#[cfg(feature = "interrupt_tracking")]
+#[inline]
+pub fn disable_count() -> usize {
+    DISABLE_COUNT.load(core::sync::atomic::Ordering::Relaxed)
+}
+
+#[inline]
+fn track_enable() {
+    #[cfg(feature = "interrupt_tracking")]
+    {
+        use core::sync::atomic::Ordering;
+        // Saturate at zero, so that enabling interrupts that were disabled by other code (e.g.
+        // by the CPU on interrupt entry) doesn't wrap the counter.
+        let _ = DISABLE_COUNT.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |count| {
+            count.checked_sub(1)
+        });
+    }
+}
+
+#[inline]
+fn track_disable() {
+    #[cfg(feature = "interrupt_tracking")]
+    DISABLE_COUNT.fetch_add(1, core::sync::atomic::Ordering::Relaxed);
+}
+
 /// Enable interrupts.
 ///
 /// This is a wrapper around the `sti` instruction.
+///
+/// To enable interrupts and wait for the next one, use [`enable_and_hlt`] instead of calling
+/// this function followed by [`hlt`](crate::instructions::hlt), which would not be atomic.
 #[inline]
 pub fn enable() {
+    track_enable();
     // Omit `nomem` to imitate a lock release. Otherwise, the compiler
     // is free to move reads and writes through this asm block.
     unsafe {
@@ -25,6 +77,11 @@ pub fn enable() {
 /// Disable interrupts.
 ///
 /// This is a wrapper around the `cli` instruction.
+///
+/// Calls don't nest: a single [`enable`] re-enables interrupts after any number of `disable`
+/// calls, so code with nested critical sections should use [`without_interrupts`], which
+/// restores the previous state instead. With the `interrupt_tracking` feature, the number of
+/// unbalanced calls can be queried with `disable_count` to catch missing `sti`s.
 #[inline]
 pub fn disable() {
     // Omit `nomem` to imitate a lock acquire. Otherwise, the compiler
@@ -32,6 +89,7 @@ pub fn disable() {
     unsafe {
         asm!("cli", options(preserves_flags, nostack));
     }
+    track_disable();
 }
 
 /// Run a closure with disabled interrupts.
@@ -41,18 +99,28 @@ pub fn disable() {
 ///
 /// If you have other `enable` and `disable` calls _within_ the closure, things may not work as expected.
 ///
+/// The closure's return value is passed through. Nested calls keep interrupts disabled until
+/// the outermost call returns:
+///
 /// # Examples
 ///
-/// ```ignore
-/// // interrupts are enabled
-/// without_interrupts(|| {
-///     // interrupts are disabled
-///     without_interrupts(|| {
-///         // interrupts are disabled
+/// ```no_run
+/// use x86_64::instructions::interrupts::{are_enabled, without_interrupts};
+///
+/// assert!(are_enabled());
+/// let value = without_interrupts(|| {
+///     assert!(!are_enabled());
+///     let inner = without_interrupts(|| {
+///         assert!(!are_enabled());
+///         42
 ///     });
-///     // interrupts are still disabled
+///     // the inner call didn't re-enable interrupts
+///     assert!(!are_enabled());
+///     inner
 /// });
-/// // interrupts are enabled again
+/// // only the outermost call enables interrupts again
+/// assert!(are_enabled());
+/// assert_eq!(value, 42);
 /// ```
 #[inline]
 pub fn without_interrupts<F, R>(f: F) -> R
@@ -124,12 +192,19 @@ where
 /// information.
 #[inline]
 pub fn enable_and_hlt() {
+    track_enable();
     unsafe {
         asm!("sti; hlt", options(nomem, nostack));
     }
 }
 
 /// Cause a breakpoint exception by invoking the `int3` instruction.
+///
+/// This raises vector 3, which is handled by the
+/// [`breakpoint`](crate::structures::idt::InterruptDescriptorTable::breakpoint) handler. `int3`
+/// is encoded as the single byte `0xcc`, which is why debuggers patch it over the first byte of
+/// an instruction to set a software breakpoint. This makes it useful for testing that the
+/// breakpoint handler runs and returns correctly.
 #[inline]
 pub fn int3() {
     unsafe {
@@ -139,6 +214,11 @@ pub fn int3() {
 
 /// Generate a software interrupt by invoking the `int` instruction.
 ///
+/// The vector is a const generic parameter because `int` only takes an immediate operand,
+/// so it must be known at compile time, e.g. `software_interrupt::<0x80>()`. Note that
+/// `software_interrupt::<3>()` emits the two-byte `int 3` encoding, which differs from
+/// [`int3`] in some details (e.g. in virtual-8086 mode); use [`int3`] for breakpoints.
+///
 /// ## Safety
 ///
 /// Invoking an arbitrary interrupt is unsafe. It can cause your system to
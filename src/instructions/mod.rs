@@ -2,16 +2,29 @@
 
 //! Special x86_64 instructions.
 
+pub mod barrier;
+pub mod cache;
+pub mod cet;
+pub mod cpuid;
+pub mod fxsave;
 pub mod interrupts;
+pub mod mwait;
+pub mod nt;
 pub mod port;
 pub mod random;
 pub mod segmentation;
+pub mod smap;
 pub mod tables;
 pub mod tlb;
+pub mod tsc;
+pub mod xsave;
 
 use core::arch::asm;
 
 /// Halts the CPU until the next interrupt arrives.
+///
+/// To atomically enable interrupts before halting, use
+/// [`interrupts::enable_and_hlt`] instead.
 #[inline]
 pub fn hlt() {
     unsafe {
@@ -32,11 +45,24 @@ pub fn nop() {
     }
 }
 
+/// Executes the `pause` instruction, which hints to the processor that the code is in a
+/// spin-wait loop.
+///
+/// This improves the performance of spin-wait loops and reduces their power consumption.
+/// Unlike [`core::hint::spin_loop`], this always emits `pause`.
+#[inline]
+pub fn pause() {
+    unsafe {
+        asm!("pause", options(nomem, nostack, preserves_flags));
+    }
+}
+
 /// Emits a '[magic breakpoint](https://wiki.osdev.org/Bochs#Magic_Breakpoint)'
 /// instruction for the [Bochs](http://bochs.sourceforge.net/) CPU
 /// emulator.
 ///
-/// Make sure to set `magic_break: enabled=1` in your `.bochsrc` file.
+/// Make sure to set `magic_break: enabled=1` in your `.bochsrc` file. On real hardware and other
+/// emulators, the instruction does nothing.
 #[inline]
 pub fn bochs_breakpoint() {
     unsafe {
@@ -44,6 +70,33 @@ pub fn bochs_breakpoint() {
     }
 }
 
+/// Causes an invalid opcode exception (`#UD`) by executing the `ud2` instruction.
+///
+/// `ud2` is guaranteed to be an invalid opcode, so this is a well-defined way to trap, e.g.
+/// to abort in a way that debuggers and emulators recognize (unlike halting in a loop). Since
+/// `#UD` is a fault, the saved instruction pointer points to the `ud2` instruction, so
+/// returning from the
+/// [`invalid_opcode`](crate::structures::idt::InterruptDescriptorTable::invalid_opcode)
+/// handler executes it again. This function never returns.
+#[inline]
+pub fn ud2() -> ! {
+    unsafe {
+        asm!("ud2", options(noreturn, nomem, nostack));
+    }
+}
+
+/// Writes a byte to the debug console port `0xE9` of Bochs and QEMU.
+///
+/// The output only shows up if the emulator is configured for it, e.g. via
+/// `-debugcon stdio` in QEMU or `port_e9_hack: enabled=1` in Bochs. On real hardware the port
+/// is normally unused, so the write has no effect.
+#[inline]
+pub fn port_e9_write(byte: u8) {
+    unsafe {
+        asm!("out 0xe9, al", in("al") byte, options(nomem, nostack, preserves_flags));
+    }
+}
+
 /// Gets the current instruction pointer. Note that this is only approximate as it requires a few
 /// instructions to execute.
 #[inline(always)]
@@ -54,3 +107,19 @@ pub fn read_rip() -> crate::VirtAddr {
     }
     crate::VirtAddr::new(rip)
 }
+
+/// Reads the value of the `IA32_TSC_AUX` MSR, which usually contains an ID of the current
+/// processor, using the `rdpid` instruction.
+///
+/// Unlike [`tsc::read_tscp`], this doesn't read the time stamp counter.
+///
+/// Requires CPUID.(EAX=07H, ECX=0H):ECX bit 22 (`RDPID`), otherwise the instruction
+/// throws a `#UD`. Support can be checked with [`cpuid::has_rdpid`].
+#[inline]
+pub fn read_pid() -> u32 {
+    let pid: u64;
+    unsafe {
+        asm!("rdpid {}", out(reg) pid, options(nomem, nostack, preserves_flags));
+    }
+    pid as u32
+}
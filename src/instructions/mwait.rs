@@ -0,0 +1,74 @@
+//! Wait for writes to a memory range using `MONITOR`/`MWAIT`.
+//!
+//! All functions in this module require CPUID.01H:ECX bit 3 (`MONITOR`), otherwise the
+//! instructions throw a `#UD`.
+//!
+//! `mwait` can return spuriously (e.g. because of an interrupt or a write to an unrelated part
+//! of the monitored cache line), so callers must always re-check the condition they are
+//! waiting for:
+//!
+//! ```no_run
+//! use core::sync::atomic::{AtomicBool, Ordering};
+//! use x86_64::instructions::mwait::{monitor, mwait};
+//! use x86_64::VirtAddr;
+//!
+//! static WAKE_UP: AtomicBool = AtomicBool::new(false);
+//!
+//! while !WAKE_UP.load(Ordering::Acquire) {
+//!     unsafe {
+//!         monitor(VirtAddr::from_ptr(&WAKE_UP), 0, 0);
+//!         // Check again in case the write happened before the monitor was armed.
+//!         if WAKE_UP.load(Ordering::Acquire) {
+//!             break;
+//!         }
+//!         mwait(0, 0);
+//!     }
+//! }
+//! ```
+
+use crate::VirtAddr;
+use core::arch::asm;
+
+/// Arm the address monitoring hardware for the given address using the `monitor` instruction.
+///
+/// `extensions` and `hints` are passed in `ECX` and `EDX`. No extensions or hints are
+/// currently defined, so both should be zero.
+///
+/// ## Safety
+///
+/// `addr` must point to writable memory that uses the write back memory type. Not all
+/// processors allow `monitor` outside of ring 0.
+#[inline]
+pub unsafe fn monitor(addr: VirtAddr, extensions: u32, hints: u32) {
+    unsafe {
+        asm!(
+            "monitor",
+            in("rax") addr.as_u64(),
+            in("ecx") extensions,
+            in("edx") hints,
+            options(nostack, preserves_flags),
+        );
+    }
+}
+
+/// Wait for a write to the address range armed by [`monitor`] using the `mwait` instruction.
+///
+/// `hints` (passed in `EAX`) contains the requested C-state in bits 4 to 7 and the sub C-state
+/// in bits 0 to 3. `extensions` is passed in `ECX`; bit 0 allows interrupts to wake up the
+/// processor even if they are masked by `RFLAGS.IF`.
+///
+/// ## Safety
+///
+/// Not all processors allow `mwait` outside of ring 0. The requested C-state must be supported
+/// (see CPUID leaf 05H).
+#[inline]
+pub unsafe fn mwait(hints: u32, extensions: u32) {
+    unsafe {
+        asm!(
+            "mwait",
+            in("eax") hints,
+            in("ecx") extensions,
+            options(nostack, preserves_flags),
+        );
+    }
+}
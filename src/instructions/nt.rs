@@ -0,0 +1,82 @@
+//! Non-temporal stores, which bypass the caches.
+//!
+//! Non-temporal stores are useful for writing large memory regions that are not read again
+//! soon (e.g. zeroing a freshly allocated frame), since they don't evict other data from the
+//! caches. They are weakly ordered, so they must be followed by an
+//! [`sfence`](super::barrier::sfence) before the memory is observed by other processors or
+//! devices.
+
+use crate::VirtAddr;
+use core::arch::asm;
+use core::ptr;
+
+/// Stores `value` to `dst` using the `movnti` instruction.
+///
+/// The store is weakly ordered, see the [module documentation](self).
+///
+/// ## Safety
+///
+/// `dst` must be valid for writes. It should be 8-byte aligned, since unaligned non-temporal
+/// stores are slow.
+#[inline]
+pub unsafe fn movnti_u64(dst: *mut u64, value: u64) {
+    unsafe {
+        asm!("movnti [{}], {}", in(reg) dst, in(reg) value, options(nostack, preserves_flags));
+    }
+}
+
+/// Sets `len` bytes starting at `dst` to `value` using non-temporal stores, followed by an
+/// [`sfence`](super::barrier::sfence).
+///
+/// The 8-byte aligned part of the region is written with [`movnti_u64`]; the unaligned bytes
+/// at the start and end (if any) are written with normal stores.
+///
+/// ## Safety
+///
+/// The memory region must be valid for writes.
+#[inline]
+pub unsafe fn nt_memset(dst: VirtAddr, value: u8, len: usize) {
+    let start = dst.as_mut_ptr::<u8>();
+    let head = usize::min((dst.align_up(8u64) - dst) as usize, len);
+    let words = (len - head) / 8;
+    let tail = len - head - words * 8;
+    let pattern = u64::from_ne_bytes([value; 8]);
+
+    unsafe {
+        ptr::write_bytes(start, value, head);
+        let aligned = start.add(head).cast::<u64>();
+        for i in 0..words {
+            movnti_u64(aligned.add(i), pattern);
+        }
+        ptr::write_bytes(aligned.add(words).cast::<u8>(), value, tail);
+    }
+    super::barrier::sfence();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memset() {
+        let mut buffer = [0u64; 8];
+        let bytes = buffer.as_mut_ptr().cast::<u8>();
+        unsafe { nt_memset(VirtAddr::from_ptr(bytes.add(3)), 0xab, 50) };
+
+        let bytes = unsafe { core::slice::from_raw_parts(bytes, 64) };
+        assert!(bytes[..3].iter().all(|&b| b == 0));
+        assert!(bytes[3..53].iter().all(|&b| b == 0xab));
+        assert!(bytes[53..].iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn memset_short() {
+        let mut buffer = [0u64; 2];
+        let bytes = buffer.as_mut_ptr().cast::<u8>();
+        unsafe { nt_memset(VirtAddr::from_ptr(bytes.add(1)), 0xff, 4) };
+        assert_eq!(
+            buffer[0].to_ne_bytes(),
+            [0, 0xff, 0xff, 0xff, 0xff, 0, 0, 0]
+        );
+    }
+}
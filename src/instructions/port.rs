@@ -5,7 +5,7 @@ use core::fmt;
 use core::marker::PhantomData;
 
 use crate::sealed::Sealed;
-pub use crate::structures::port::{PortRead, PortWrite};
+pub use crate::structures::port::{PortRead, PortReadWrite, PortWrite};
 
 impl PortRead for u8 {
     #[inline]
@@ -67,6 +67,18 @@ impl PortWrite for u32 {
     }
 }
 
+/// Waits a short amount of time (usually 1 to 4 microseconds) by writing to the unused
+/// port `0x80`.
+///
+/// Port `0x80` is used for POST codes during boot, so writing to it afterwards has no effect
+/// apart from the delay of the I/O operation.
+#[inline]
+pub fn io_wait() {
+    unsafe {
+        asm!("out 0x80, al", in("al") 0u8, options(nomem, nostack, preserves_flags));
+    }
+}
+
 /// A marker trait for access types which allow accessing port values.
 pub trait PortAccess: Sealed {
     /// A string representation for debug output.
@@ -118,6 +130,15 @@ impl PortWriteAccess for ReadWriteAccess {}
 /// * `PortGeneric<T, ReadWriteAccess>` -> `Port<T>`
 /// * `PortGeneric<T, ReadOnlyAccess>` -> `PortReadOnly<T>`
 /// * `PortGeneric<T, WriteOnlyAccess>` -> `PortWriteOnly<T>`
+///
+/// `T` must be `u8`, `u16`, or `u32`, other types result in a compile error:
+///
+/// ```compile_fail
+/// use x86_64::instructions::port::Port;
+///
+/// let mut port = Port::<u64>::new(0x80);
+/// let value = unsafe { port.read() };
+/// ```
 pub struct PortGeneric<T, A> {
     port: u16,
     phantom: PhantomData<(T, A)>,
@@ -175,6 +196,21 @@ impl<T: PortWrite, A: PortWriteAccess> PortGeneric<T, A> {
     pub unsafe fn write(&mut self, value: T) {
         unsafe { T::write_to_port(self.port, value) }
     }
+
+    /// Writes to the port and then waits briefly using [`io_wait`].
+    ///
+    /// This is useful for slow legacy devices such as the 8259 PIC, which need some time to
+    /// process a write before the next one.
+    ///
+    /// ## Safety
+    ///
+    /// This function is unsafe because the I/O port could have side effects that violate memory
+    /// safety.
+    #[inline]
+    pub unsafe fn write_delayed(&mut self, value: T) {
+        unsafe { self.write(value) };
+        io_wait();
+    }
 }
 
 impl<T, A: PortAccess> fmt::Debug for PortGeneric<T, A> {
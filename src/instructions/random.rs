@@ -1,5 +1,50 @@
 //! Support for build-in RNGs
 
+use core::fmt;
+
+/// The number of attempts recommended by Intel before reporting an RDRAND failure.
+const RDRAND_RETRIES: usize = 10;
+
+/// RDSEED fails more often when it is under load, so it is retried more often than RDRAND.
+const RDSEED_RETRIES: usize = 100;
+
+/// Calls `f` until it returns `Some`, at most `retries` times.
+#[inline]
+fn retry<T>(retries: usize, mut f: impl FnMut() -> Option<T>) -> Option<T> {
+    for _ in 0..retries {
+        if let Some(value) = f() {
+            return Some(value);
+        }
+        super::pause();
+    }
+    None
+}
+
+/// Fills `dest` with random bytes obtained from `get_u64`.
+#[inline]
+fn fill_bytes(dest: &mut [u8], mut get_u64: impl FnMut() -> Option<u64>) -> Result<(), RngError> {
+    let mut chunks = dest.chunks_exact_mut(8);
+    for chunk in &mut chunks {
+        chunk.copy_from_slice(&get_u64().ok_or(RngError(()))?.to_ne_bytes());
+    }
+    let rest = chunks.into_remainder();
+    if !rest.is_empty() {
+        let bytes = get_u64().ok_or(RngError(()))?.to_ne_bytes();
+        rest.copy_from_slice(&bytes[..rest.len()]);
+    }
+    Ok(())
+}
+
+/// The hardware RNG failed to return a random number after retrying.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RngError(());
+
+impl fmt::Display for RngError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the hardware random number generator failed")
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 /// Used to obtain random numbers using x86_64's RDRAND opcode
 pub struct RdRand(());
@@ -8,10 +53,7 @@ impl RdRand {
     /// Creates Some(RdRand) if RDRAND is supported, None otherwise
     #[inline]
     pub fn new() -> Option<Self> {
-        // RDRAND support indicated by CPUID page 01h, ecx bit 30
-        // https://en.wikipedia.org/wiki/RdRand#Overview
-        let cpuid = unsafe { core::arch::x86_64::__cpuid(0x1) };
-        if cpuid.ecx & (1 << 30) != 0 {
+        if super::cpuid::has_rdrand() {
             Some(RdRand(()))
         } else {
             None
@@ -19,52 +61,188 @@ impl RdRand {
     }
 
     /// Uniformly sampled u64.
-    /// May fail in rare circumstances or heavy load.
+    /// Retries up to 10 times, so this only fails if the RNG is broken or under heavy load.
     #[inline]
     pub fn get_u64(self) -> Option<u64> {
-        let mut res: u64 = 0;
-        unsafe {
-            match core::arch::x86_64::_rdrand64_step(&mut res) {
-                1 => Some(res),
-                x => {
-                    debug_assert_eq!(x, 0, "rdrand64 returned non-binary value");
-                    None
+        retry(RDRAND_RETRIES, || {
+            let mut res: u64 = 0;
+            unsafe {
+                match core::arch::x86_64::_rdrand64_step(&mut res) {
+                    1 => Some(res),
+                    x => {
+                        debug_assert_eq!(x, 0, "rdrand64 returned non-binary value");
+                        None
+                    }
                 }
             }
-        }
+        })
     }
     /// Uniformly sampled u32.
-    /// May fail in rare circumstances or heavy load.
+    /// Retries up to 10 times, so this only fails if the RNG is broken or under heavy load.
     #[inline]
     pub fn get_u32(self) -> Option<u32> {
-        let mut res: u32 = 0;
-        unsafe {
-            match core::arch::x86_64::_rdrand32_step(&mut res) {
-                1 => Some(res),
-                x => {
-                    debug_assert_eq!(x, 0, "rdrand32 returned non-binary value");
-                    None
+        retry(RDRAND_RETRIES, || {
+            let mut res: u32 = 0;
+            unsafe {
+                match core::arch::x86_64::_rdrand32_step(&mut res) {
+                    1 => Some(res),
+                    x => {
+                        debug_assert_eq!(x, 0, "rdrand32 returned non-binary value");
+                        None
+                    }
                 }
             }
-        }
+        })
     }
     /// Uniformly sampled u16.
-    /// May fail in rare circumstances or heavy load.
+    /// Retries up to 10 times, so this only fails if the RNG is broken or under heavy load.
     #[inline]
     pub fn get_u16(self) -> Option<u16> {
-        let mut res: u16 = 0;
-        unsafe {
-            match core::arch::x86_64::_rdrand16_step(&mut res) {
-                1 => Some(res),
-                x => {
-                    debug_assert_eq!(x, 0, "rdrand16 returned non-binary value");
-                    None
+        retry(RDRAND_RETRIES, || {
+            let mut res: u16 = 0;
+            unsafe {
+                match core::arch::x86_64::_rdrand16_step(&mut res) {
+                    1 => Some(res),
+                    x => {
+                        debug_assert_eq!(x, 0, "rdrand16 returned non-binary value");
+                        None
+                    }
                 }
             }
+        })
+    }
+
+    /// Fills `dest` with random bytes.
+    #[inline]
+    pub fn try_fill_bytes(self, dest: &mut [u8]) -> Result<(), RngError> {
+        fill_bytes(dest, || self.get_u64())
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+/// Used to obtain random seeds using x86_64's RDSEED opcode
+///
+/// Unlike RDRAND, RDSEED returns values directly from the entropy source, which makes it
+/// suitable for seeding other RNGs, but also slower and more likely to fail.
+pub struct RdSeed(());
+
+impl RdSeed {
+    /// Creates Some(RdSeed) if RDSEED is supported, None otherwise
+    #[inline]
+    pub fn new() -> Option<Self> {
+        if super::cpuid::has_rdseed() {
+            Some(RdSeed(()))
+        } else {
+            None
         }
     }
+
+    /// Uniformly sampled u64.
+    /// Retries up to 100 times, but may still fail if the entropy source is exhausted.
+    #[inline]
+    pub fn get_u64(self) -> Option<u64> {
+        retry(RDSEED_RETRIES, || {
+            let mut res: u64 = 0;
+            unsafe {
+                match core::arch::x86_64::_rdseed64_step(&mut res) {
+                    1 => Some(res),
+                    x => {
+                        debug_assert_eq!(x, 0, "rdseed64 returned non-binary value");
+                        None
+                    }
+                }
+            }
+        })
+    }
+
+    /// Uniformly sampled u32.
+    /// Retries up to 100 times, but may still fail if the entropy source is exhausted.
+    #[inline]
+    pub fn get_u32(self) -> Option<u32> {
+        retry(RDSEED_RETRIES, || {
+            let mut res: u32 = 0;
+            unsafe {
+                match core::arch::x86_64::_rdseed32_step(&mut res) {
+                    1 => Some(res),
+                    x => {
+                        debug_assert_eq!(x, 0, "rdseed32 returned non-binary value");
+                        None
+                    }
+                }
+            }
+        })
+    }
+
+    /// Uniformly sampled u16.
+    /// Retries up to 100 times, but may still fail if the entropy source is exhausted.
+    #[inline]
+    pub fn get_u16(self) -> Option<u16> {
+        retry(RDSEED_RETRIES, || {
+            let mut res: u16 = 0;
+            unsafe {
+                match core::arch::x86_64::_rdseed16_step(&mut res) {
+                    1 => Some(res),
+                    x => {
+                        debug_assert_eq!(x, 0, "rdseed16 returned non-binary value");
+                        None
+                    }
+                }
+            }
+        })
+    }
+
+    /// Fills `dest` with random bytes.
+    #[inline]
+    pub fn try_fill_bytes(self, dest: &mut [u8]) -> Result<(), RngError> {
+        fill_bytes(dest, || self.get_u64())
+    }
 }
 
+#[cfg(feature = "rand_core")]
+impl rand_core::RngCore for RdSeed {
+    /// Returns a random u32.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if RDSEED still fails after retrying.
+    #[inline]
+    fn next_u32(&mut self) -> u32 {
+        self.get_u32().expect("RDSEED failed")
+    }
+
+    /// Returns a random u64.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if RDSEED still fails after retrying.
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        self.get_u64().expect("RDSEED failed")
+    }
+
+    /// Fills `dest` with random bytes.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if RDSEED still fails after retrying.
+    #[inline]
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        RdSeed::try_fill_bytes(*self, dest).expect("RDSEED failed")
+    }
+
+    #[inline]
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        RdSeed::try_fill_bytes(*self, dest).map_err(|_| {
+            let code = core::num::NonZeroU32::new(rand_core::Error::CUSTOM_START).unwrap();
+            rand_core::Error::from(code)
+        })
+    }
+}
+
+/// RDSEED returns values that come directly from the entropy source of the processor.
+#[cfg(feature = "rand_core")]
+impl rand_core::CryptoRng for RdSeed {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -81,4 +259,48 @@ mod tests {
             assert!(rand.is_none());
         }
     }
+
+    #[test]
+    pub fn test_rdseed() {
+        let seed = RdSeed::new();
+        if is_x86_feature_detected!("rdseed") {
+            let seed = seed.unwrap();
+            assert!(seed.get_u16().is_some());
+            assert!(seed.get_u32().is_some());
+            assert!(seed.get_u64().is_some());
+        } else {
+            assert!(seed.is_none());
+        }
+    }
+
+    #[test]
+    pub fn test_fill_bytes() {
+        let mut values = [0x0706_0504_0302_0100u64, 0x0f0e_0d0c_0b0a_0908]
+            .iter()
+            .copied();
+        let mut buf = [0xff; 11];
+        fill_bytes(&mut buf, || values.next()).unwrap();
+        assert_eq!(buf, [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+
+        let mut buf = [0; 16];
+        assert_eq!(fill_bytes(&mut buf, || None), Err(RngError(())));
+        assert_eq!(retry(3, || None::<u8>), None);
+
+        if let Some(rand) = RdRand::new() {
+            rand.try_fill_bytes(&mut buf).unwrap();
+        }
+    }
+    #[cfg(feature = "rand_core")]
+    #[test]
+    pub fn test_rdseed_rng_core() {
+        use rand_core::RngCore;
+
+        if let Some(mut seed) = RdSeed::new() {
+            let mut buf = [0; 13];
+            seed.fill_bytes(&mut buf);
+            RngCore::try_fill_bytes(&mut seed, &mut buf).unwrap();
+            seed.next_u32();
+            seed.next_u64();
+        }
+    }
 }
@@ -2,7 +2,10 @@
 
 pub use crate::registers::segmentation::{Segment, Segment64, CS, DS, ES, FS, GS, SS};
 use crate::{
-    registers::model_specific::{FsBase, GsBase, Msr},
+    registers::{
+        control::{Cr4, Cr4Flags},
+        model_specific::{FsBase, GsBase, Msr},
+    },
     structures::gdt::SegmentSelector,
     VirtAddr,
 };
@@ -56,6 +59,41 @@ macro_rules! segment64_impl {
                 }
             }
         }
+
+        impl $type {
+            /// Reads the segment base address, using [`Segment64::read_base`] if
+            /// [`CR4.FSGSBASE`](Cr4Flags::FSGSBASE) is set and the [`BASE`](Segment64::BASE)
+            /// MSR otherwise.
+            ///
+            /// Reading CR4 and the MSR requires ring 0.
+            #[inline]
+            pub fn read_base_checked() -> VirtAddr {
+                if Cr4::read().contains(Cr4Flags::FSGSBASE) {
+                    Self::read_base()
+                } else {
+                    <$base>::read()
+                }
+            }
+
+            /// Writes the segment base address, using [`Segment64::write_base`] if
+            /// [`CR4.FSGSBASE`](Cr4Flags::FSGSBASE) is set and the [`BASE`](Segment64::BASE)
+            /// MSR otherwise.
+            ///
+            /// Reading CR4 and writing the MSR requires ring 0.
+            ///
+            /// ## Safety
+            ///
+            /// The caller must ensure that this write operation has no unsafe side
+            /// effects, as the segment base address might be in use.
+            #[inline]
+            pub unsafe fn write_base_checked(base: VirtAddr) {
+                if Cr4::read().contains(Cr4Flags::FSGSBASE) {
+                    unsafe { Self::write_base(base) }
+                } else {
+                    <$base>::write(base)
+                }
+            }
+        }
     };
 }
 
@@ -70,6 +108,9 @@ impl Segment for CS {
     /// Note we cannot use a "far call" (`lcall`) or "far jmp" (`ljmp`) to do this because then we
     /// would only be able to jump to 32-bit instruction pointers. Only Intel implements support
     /// for 64-bit far calls/jumps in long-mode, AMD does not.
+    ///
+    /// After loading a new GDT, [`CS`] has to be reloaded this way before the old GDT is
+    /// freed, see [`load_data_segments`] for an example.
     #[inline]
     unsafe fn set_reg(sel: SegmentSelector) {
         unsafe {
@@ -109,3 +150,217 @@ impl GS {
         }
     }
 }
+
+/// Swap the `KernelGsBase` MSR and the `GsBase` MSR using the `swapgs` instruction.
+///
+/// This is the same as [`GS::swap`]. It is typically used on kernel entry from user mode
+/// (e.g. in a `syscall` handler) to load the kernel GS base and on exit to restore the user GS
+/// base. The current bases can be inspected with [`GS::read_base`] and [`KernelGsBase::read`].
+///
+/// [`KernelGsBase::read`]: crate::registers::model_specific::KernelGsBase::read
+///
+/// ## Safety
+///
+/// Every `swapgs` on kernel entry must be paired with a `swapgs` on the
+/// corresponding kernel exit. Otherwise the kernel ends up running with the
+/// user GS base or user code runs with the kernel GS base.
+#[inline]
+pub unsafe fn swapgs() {
+    unsafe { GS::swap() }
+}
+
+/// Load the given selector into the [`DS`], [`ES`], and [`SS`] registers.
+///
+/// This is typically used with the kernel data segment after loading a new GDT. The registers
+/// are loaded in the order `DS`, `ES`, `SS`. In 64-bit mode, a null selector can be loaded
+/// into `SS` at CPL 0 (and into `DS` and `ES` at any CPL), since the CPU doesn't use these
+/// segments for memory accesses. The individual registers can be loaded with
+/// [`Segment::set_reg`].
+///
+/// [`CS`] must be loaded separately using [`CS::set_reg`], which performs a far return. [`FS`]
+/// and [`GS`] are not loaded because loading them overwrites their base addresses.
+///
+/// A typical sequence after loading a new GDT reloads `CS` first and then the data segments:
+///
+/// ```no_run
+/// use x86_64::instructions::segmentation::{load_data_segments, Segment, CS};
+/// use x86_64::structures::gdt::{Descriptor, GlobalDescriptorTable, SegmentSelector};
+///
+/// static GDT: (GlobalDescriptorTable, SegmentSelector, SegmentSelector) = {
+///     let gdt = GlobalDescriptorTable::new();
+///     let (gdt, code) = gdt.const_append(Descriptor::kernel_code_segment());
+///     let (gdt, data) = gdt.const_append(Descriptor::kernel_data_segment());
+///     (gdt, code, data)
+/// };
+///
+/// let (gdt, code, data) = &GDT;
+/// gdt.load();
+/// unsafe {
+///     // `CS` can't be loaded with `mov`, so this uses a far return.
+///     CS::set_reg(*code);
+///     load_data_segments(*data);
+/// }
+/// ```
+///
+/// ## Safety
+///
+/// The caller must ensure that `sel` is a valid data segment selector for the current CPL
+/// (or a null selector).
+#[inline]
+pub unsafe fn load_data_segments(sel: SegmentSelector) {
+    unsafe {
+        DS::set_reg(sel);
+        ES::set_reg(sel);
+        SS::set_reg(sel);
+    }
+}
+
+/// Load the access rights of the segment descriptor referenced by `sel` using the `lar`
+/// instruction.
+///
+/// The returned value contains bits 8 to 23 of the high doubleword of the descriptor (i.e. the
+/// type, DPL, present, and granularity bits), all other bits are cleared. Returns `None` if the
+/// instruction clears ZF, which happens if `sel` is null, is outside the descriptor table
+/// limit, references a descriptor type that `lar` doesn't support, or isn't accessible at the
+/// current CPL and the selector's RPL. This makes it possible to validate a selector (e.g. one
+/// passed in from user space) without risking a fault when loading it.
+///
+/// ## Safety
+///
+/// This reads the descriptor tables, so the GDT (and LDT, if referenced) must be valid.
+#[inline]
+pub unsafe fn load_access_rights(sel: SegmentSelector) -> Option<u32> {
+    let rights: u32;
+    let valid: u8;
+    unsafe {
+        asm!(
+            "xor {rights:e}, {rights:e}",
+            "lar {rights:e}, {sel:e}",
+            "setz {valid}",
+            sel = in(reg) u32::from(sel.0),
+            rights = out(reg) rights,
+            valid = out(reg_byte) valid,
+            options(readonly, nostack),
+        );
+    }
+    if valid != 0 {
+        Some(rights)
+    } else {
+        None
+    }
+}
+
+/// Load the segment limit of the segment descriptor referenced by `sel` using the `lsl`
+/// instruction.
+///
+/// The returned limit is in bytes, i.e. it is already scaled if the granularity bit of the
+/// descriptor is set. Returns `None` if the instruction clears ZF, under the same conditions
+/// as [`load_access_rights`].
+///
+/// ## Safety
+///
+/// This reads the descriptor tables, so the GDT (and LDT, if referenced) must be valid.
+#[inline]
+pub unsafe fn load_segment_limit(sel: SegmentSelector) -> Option<u32> {
+    let limit: u32;
+    let valid: u8;
+    unsafe {
+        asm!(
+            "xor {limit:e}, {limit:e}",
+            "lsl {limit:e}, {sel:e}",
+            "setz {valid}",
+            sel = in(reg) u32::from(sel.0),
+            limit = out(reg) limit,
+            valid = out(reg_byte) valid,
+            options(readonly, nostack),
+        );
+    }
+    if valid != 0 {
+        Some(limit)
+    } else {
+        None
+    }
+}
+
+/// Check whether the segment referenced by `sel` is readable at the current CPL using the
+/// `verr` instruction.
+///
+/// Returns `true` if the instruction sets ZF, i.e. if `sel` references a data segment or a
+/// readable code segment that is accessible at the current CPL and the selector's RPL. A null
+/// selector yields `false`.
+///
+/// ## Safety
+///
+/// This reads the descriptor tables, so the GDT (and LDT, if referenced) must be valid.
+#[inline]
+pub unsafe fn verify_read(sel: SegmentSelector) -> bool {
+    let valid: u8;
+    unsafe {
+        asm!(
+            "verr {sel:x}",
+            "setz {valid}",
+            sel = in(reg) sel.0,
+            valid = out(reg_byte) valid,
+            options(readonly, nostack),
+        );
+    }
+    valid != 0
+}
+
+/// Check whether the segment referenced by `sel` is writable at the current CPL using the
+/// `verw` instruction.
+///
+/// Returns `true` if the instruction sets ZF, i.e. if `sel` references a writable data segment
+/// that is accessible at the current CPL and the selector's RPL. Code segments are never
+/// writable. A null selector yields `false`.
+///
+/// ## Safety
+///
+/// This reads the descriptor tables, so the GDT (and LDT, if referenced) must be valid.
+#[inline]
+pub unsafe fn verify_write(sel: SegmentSelector) -> bool {
+    let valid: u8;
+    unsafe {
+        asm!(
+            "verw {sel:x}",
+            "setz {valid}",
+            sel = in(reg) sel.0,
+            valid = out(reg_byte) valid,
+            options(readonly, nostack),
+        );
+    }
+    valid != 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_code_segment() {
+        let cs = CS::get_reg();
+        let rights = unsafe { load_access_rights(cs) }.unwrap();
+        // present, code segment
+        assert_eq!(rights & (1 << 15), 1 << 15);
+        assert_eq!(rights & (0b11 << 11), 0b11 << 11);
+        assert!(unsafe { load_segment_limit(cs) }.is_some());
+    }
+
+    #[test]
+    fn query_null_selector() {
+        let null = SegmentSelector(0);
+        assert_eq!(unsafe { load_access_rights(null) }, None);
+        assert_eq!(unsafe { load_segment_limit(null) }, None);
+        assert!(!unsafe { verify_read(null) });
+        assert!(!unsafe { verify_write(null) });
+    }
+
+    #[test]
+    fn verify_segments() {
+        let cs = CS::get_reg();
+        let ss = SS::get_reg();
+        assert!(!unsafe { verify_write(cs) });
+        assert!(unsafe { verify_read(ss) });
+        assert!(unsafe { verify_write(ss) });
+    }
+}
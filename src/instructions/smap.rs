@@ -0,0 +1,81 @@
+//! Temporarily allow supervisor-mode accesses to user-mode pages.
+//!
+//! If [`CR4.SMAP`][Cr4Flags::SUPERVISOR_MODE_ACCESS_PREVENTION] is set, supervisor-mode software
+//! can only access user-mode pages while the `AC` flag in `RFLAGS` is set. All functions in this
+//! module require SMAP support, see CPUID.(EAX=07H, ECX=0H):EBX bit 20 (`SMAP`). Otherwise the
+//! instructions throw a `#UD`.
+//!
+//! [Cr4Flags::SUPERVISOR_MODE_ACCESS_PREVENTION]: crate::registers::control::Cr4Flags::SUPERVISOR_MODE_ACCESS_PREVENTION
+
+use core::arch::asm;
+use core::marker::PhantomData;
+
+/// Set the `AC` flag using the `stac` instruction, which allows accesses to user-mode pages.
+///
+/// ## Safety
+///
+/// The caller must ensure that accesses to user-mode pages while the flag is set are
+/// intended, and that the flag is cleared with [`clac`] afterwards.
+#[inline]
+pub unsafe fn stac() {
+    unsafe {
+        asm!("stac", options(nostack));
+    }
+}
+
+/// Clear the `AC` flag using the `clac` instruction, which disallows accesses to user-mode pages.
+///
+/// ## Safety
+///
+/// Code that relies on access to user-mode pages (e.g. after a call to [`stac`]) must not run
+/// after this.
+#[inline]
+pub unsafe fn clac() {
+    unsafe {
+        asm!("clac", options(nostack));
+    }
+}
+
+/// A guard that allows accesses to user-mode pages while it is alive.
+///
+/// Creating the guard executes [`stac`], dropping it executes [`clac`].
+///
+/// ```no_run
+/// use x86_64::instructions::smap::AllowUserAccess;
+///
+/// # let user_ptr = 0x1000 as *const u8;
+/// let value = {
+///     let _guard = unsafe { AllowUserAccess::new() };
+///     unsafe { user_ptr.read_volatile() }
+/// };
+/// ```
+#[derive(Debug)]
+#[must_use = "user accesses are disallowed again once the guard is dropped"]
+pub struct AllowUserAccess {
+    // The `AC` flag belongs to the current logical processor.
+    _not_send: PhantomData<*const ()>,
+}
+
+impl AllowUserAccess {
+    /// Allow accesses to user-mode pages until the returned guard is dropped.
+    ///
+    /// ## Safety
+    ///
+    /// The guard must not be held across a context switch, because `RFLAGS.AC` would then
+    /// also be set for (or be cleared by) the code that runs in between. Nested guards clear the
+    /// flag when the innermost guard is dropped.
+    #[inline]
+    pub unsafe fn new() -> Self {
+        unsafe { stac() };
+        AllowUserAccess {
+            _not_send: PhantomData,
+        }
+    }
+}
+
+impl Drop for AllowUserAccess {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe { clac() };
+    }
+}
@@ -42,7 +42,14 @@ pub unsafe fn lidt(idt: &DescriptorTablePointer) {
     }
 }
 
-/// Get the address of the current GDT.
+/// Get the address of the current GDT using the `sgdt` instruction.
+///
+/// The returned base is a virtual address, so it is only meaningful in the address space that
+/// was active when the GDT was loaded. The returned pointer can be passed to
+/// [`lgdt`] to restore the GDT later.
+///
+/// If [`CR4.UMIP`][crate::registers::control::Cr4Flags::USER_MODE_INSTRUCTION_PREVENTION] is
+/// set, this instruction throws a `#GP` outside of ring 0.
 #[inline]
 pub fn sgdt() -> DescriptorTablePointer {
     let mut gdt: DescriptorTablePointer = DescriptorTablePointer {
@@ -55,7 +62,14 @@ pub fn sgdt() -> DescriptorTablePointer {
     gdt
 }
 
-/// Get the address of the current IDT.
+/// Get the address of the current IDT using the `sidt` instruction.
+///
+/// The returned base is a virtual address, so it is only meaningful in the address space that
+/// was active when the IDT was loaded. The returned pointer can be passed to
+/// [`lidt`] to restore the IDT later.
+///
+/// If [`CR4.UMIP`][crate::registers::control::Cr4Flags::USER_MODE_INSTRUCTION_PREVENTION] is
+/// set, this instruction throws a `#GP` outside of ring 0.
 #[inline]
 pub fn sidt() -> DescriptorTablePointer {
     let mut idt: DescriptorTablePointer = DescriptorTablePointer {
@@ -90,3 +104,40 @@ pub unsafe fn load_tss(sel: SegmentSelector) {
         asm!("ltr {0:x}", in(reg) sel.0, options(nostack, preserves_flags));
     }
 }
+
+/// Load the local descriptor table register using the `lldt` instruction.
+///
+/// The selector must reference an LDT descriptor in the GDT (see
+/// [`Descriptor::ldt`](crate::structures::gdt::Descriptor::ldt)). Loading the null selector
+/// marks the LDT as invalid.
+///
+/// ## Safety
+///
+/// This function is unsafe because the caller must ensure that the given
+/// `SegmentSelector` points to a valid LDT entry in the GDT and that the
+/// LDT stays valid while it is loaded.
+#[inline]
+pub unsafe fn load_ldt(sel: SegmentSelector) {
+    unsafe {
+        asm!("lldt {0:x}", in(reg) sel.0, options(nostack, preserves_flags));
+    }
+}
+
+/// Get the segment selector of the current task state segment using the `str` instruction.
+///
+/// This is the selector that was last loaded with [`load_tss`], or the null selector if no TSS
+/// was loaded yet.
+///
+/// ## Safety
+///
+/// If [`CR4.UMIP`][crate::registers::control::Cr4Flags::USER_MODE_INSTRUCTION_PREVENTION] is
+/// set, this instruction throws a `#GP` outside of ring 0, so the caller must ensure that this
+/// is not the case.
+#[inline]
+pub unsafe fn str() -> SegmentSelector {
+    let selector: u16;
+    unsafe {
+        asm!("str {0:x}", out(reg) selector, options(nomem, nostack, preserves_flags));
+    }
+    SegmentSelector(selector)
+}
@@ -20,6 +20,22 @@ pub fn flush(addr: VirtAddr) {
     }
 }
 
+/// Invalidate `count` consecutive pages of `page_size` bytes starting at `start` using one
+/// `invlpg` instruction per page.
+///
+/// `start` should be aligned to `page_size`. Flushing many pages this way can be slower than
+/// reloading CR3 with [`flush_all`], see [`flush_range_or_all`].
+///
+/// ## Panics
+///
+/// Panics if the range does not lie in the canonical address space.
+#[inline]
+pub fn flush_range(start: VirtAddr, count: usize, page_size: u64) {
+    for i in 0..count as u64 {
+        flush(start + i * page_size);
+    }
+}
+
 /// Invalidate the TLB completely by reloading the CR3 register.
 #[inline]
 pub fn flush_all() {
@@ -28,6 +44,51 @@ pub fn flush_all() {
     unsafe { Cr3::write(frame, flags) }
 }
 
+/// Invalidate the TLB completely, including global pages, by toggling `CR4.PGE`.
+///
+/// Unlike [`flush_all`], this also removes entries for pages mapped with
+/// [`PageTableFlags::GLOBAL`](crate::structures::paging::PageTableFlags::GLOBAL). If `CR4.PGE`
+/// is not set, there are no global entries and CR3 is reloaded instead.
+///
+/// This is expensive: all TLB entries need to be refilled afterwards, including those of the
+/// kernel. Interrupts are disabled while `CR4.PGE` is cleared, so that no code relying on a
+/// global mapping runs in the middle of the flush. Callers that already run with interrupts
+/// disabled don't pay any additional cost for this.
+#[inline]
+pub fn flush_all_global() {
+    use crate::instructions::interrupts;
+    use crate::registers::control::{Cr4, Cr4Flags};
+
+    interrupts::without_interrupts(|| {
+        let cr4 = Cr4::read_raw();
+        if cr4 & Cr4Flags::PAGE_GLOBAL.bits() == 0 {
+            flush_all();
+            return;
+        }
+        // Clearing `CR4.PGE` invalidates all TLB entries, including global ones.
+        unsafe {
+            Cr4::write_raw(cr4 & !Cr4Flags::PAGE_GLOBAL.bits());
+            Cr4::write_raw(cr4);
+        }
+    });
+}
+
+/// Invalidate the given pages, using [`flush_range`] if the range contains at most `threshold`
+/// pages and [`flush_all`] otherwise.
+///
+/// Which strategy is cheaper depends on the processor and on how much of the TLB is refilled
+/// afterwards. A threshold of about 32 pages is a common choice (Linux uses 33 by default). Note
+/// that [`flush_all`] does not invalidate global pages.
+#[inline]
+pub fn flush_range_or_all<S: PageSize>(range: PageRange<S>, threshold: usize) {
+    let count = range.len();
+    if count > threshold as u64 {
+        flush_all();
+    } else {
+        flush_range(range.start.start_address(), count as usize, S::SIZE);
+    }
+}
+
 /// The Invalidate PCID Command to execute.
 #[derive(Debug)]
 pub enum InvPcidCommand {
@@ -124,6 +185,80 @@ pub unsafe fn flush_pcid(command: InvPcidCommand) {
     }
 }
 
+/// Safe access to the `invpcid` instruction.
+///
+/// ```no_run
+/// use x86_64::VirtAddr;
+/// use x86_64::instructions::tlb::{Invpcid, Pcid};
+///
+/// // Check that `invpcid` is supported and that PCIDs are enabled.
+/// let invpcid = Invpcid::new().unwrap();
+///
+/// let pcid = Pcid::new(1).unwrap();
+/// invpcid.flush_addr(pcid, VirtAddr::new(0xf000_0000));
+/// invpcid.flush_pcid(pcid);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Invpcid(());
+
+impl Invpcid {
+    /// Check that `invpcid` is supported and that [`Cr4Flags::PCID`] is set.
+    ///
+    /// [`Cr4Flags::PCID`]: crate::registers::control::Cr4Flags::PCID
+    ///
+    /// # Panics
+    ///
+    /// Panics if the CPL is not 0.
+    pub fn new() -> Option<Self> {
+        use crate::registers::control::{Cr4, Cr4Flags};
+
+        let cs = CS::get_reg();
+        assert_eq!(cs.rpl(), PrivilegeLevel::Ring0);
+
+        // Check if the `INVPCID` instruction is supported.
+        // `__cpuid` is safe on newer compilers but unsafe on our MSRV.
+        #[allow(unused_unsafe)]
+        let cpuid = unsafe { core::arch::x86_64::__cpuid_count(7, 0) };
+        if !cpuid.ebx.get_bit(10) {
+            return None;
+        }
+
+        if !Cr4::read().contains(Cr4Flags::PCID) {
+            return None;
+        }
+
+        Some(Self(()))
+    }
+
+    /// Invalidate the mappings for the given address and PCID, except global translations
+    /// (INVPCID type 0).
+    #[inline]
+    pub fn flush_addr(&self, pcid: Pcid, addr: VirtAddr) {
+        unsafe { flush_pcid(InvPcidCommand::Address(addr, pcid)) }
+    }
+
+    /// Invalidate all mappings associated with the given PCID, except global translations
+    /// (INVPCID type 1).
+    #[inline]
+    pub fn flush_pcid(&self, pcid: Pcid) {
+        unsafe { flush_pcid(InvPcidCommand::Single(pcid)) }
+    }
+
+    /// Invalidate all mappings associated with any PCID, including global translations
+    /// (INVPCID type 2).
+    #[inline]
+    pub fn flush_all(&self) {
+        unsafe { flush_pcid(InvPcidCommand::All) }
+    }
+
+    /// Invalidate all mappings associated with any PCID, except global translations
+    /// (INVPCID type 3).
+    #[inline]
+    pub fn flush_all_except_global(&self) {
+        unsafe { flush_pcid(InvPcidCommand::AllExceptGlobal) }
+    }
+}
+
 /// Used to broadcast flushes to all logical processors.
 ///
 /// ```no_run
@@ -160,6 +295,8 @@ impl Invlpgb {
         assert_eq!(cs.rpl(), PrivilegeLevel::Ring0);
 
         // Check if the `INVLPGB` and `TLBSYNC` instruction are supported.
+        // `__cpuid` is safe on newer compilers but unsafe on our MSRV.
+        #[allow(unused_unsafe)]
         let cpuid = unsafe { core::arch::x86_64::__cpuid(0x8000_0008) };
         if !cpuid.ebx.get_bit(3) {
             return None;
@@ -169,6 +306,8 @@ impl Invlpgb {
         let invlpgb_count_max = cpuid.edx.get_bits(0..=15) as u16;
 
         // Figure out the number of supported ASIDs.
+        // `__cpuid` is safe on newer compilers but unsafe on our MSRV.
+        #[allow(unused_unsafe)]
         let cpuid = unsafe { core::arch::x86_64::__cpuid(0x8000_000a) };
         let nasid = cpuid.ebx;
 
@@ -0,0 +1,39 @@
+//! Read the time stamp counter (TSC).
+
+use core::arch::asm;
+
+/// Read the current value of the time stamp counter using the `rdtsc` instruction.
+///
+/// `rdtsc` is not serializing, so it might be executed before preceding instructions
+/// have completed.
+#[inline]
+pub fn read() -> u64 {
+    let (low, high): (u32, u32);
+    unsafe {
+        asm!("rdtsc", out("eax") low, out("edx") high, options(nomem, nostack, preserves_flags));
+    }
+    ((high as u64) << 32) | (low as u64)
+}
+
+/// Read the current value of the time stamp counter together with the value of the
+/// `IA32_TSC_AUX` MSR using the `rdtscp` instruction.
+///
+/// The `IA32_TSC_AUX` MSR usually contains an ID of the current processor, so the second value
+/// can be used to detect if the thread was migrated to another processor between two reads.
+/// Unlike `rdtsc`, `rdtscp` waits until all previous instructions have executed.
+///
+/// Requires CPUID.80000001H:EDX bit 27 (`RDTSCP`), otherwise the instruction throws a `#UD`.
+#[inline]
+pub fn read_tscp() -> (u64, u32) {
+    let (low, high, aux): (u32, u32, u32);
+    unsafe {
+        asm!(
+            "rdtscp",
+            out("eax") low,
+            out("edx") high,
+            out("ecx") aux,
+            options(nomem, nostack, preserves_flags),
+        );
+    }
+    (((high as u64) << 32) | (low as u64), aux)
+}
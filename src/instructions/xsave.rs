@@ -0,0 +1,183 @@
+//! Save and restore the extended processor state using the `XSAVE` feature set.
+//!
+//! All functions in this module require [`CR4.OSXSAVE`][Cr4Flags::OSXSAVE] to be set.
+//!
+//! [Cr4Flags::OSXSAVE]: crate::registers::control::Cr4Flags::OSXSAVE
+
+use crate::instructions::fxsave::FxsaveArea;
+use crate::registers::xcontrol::XCr0Flags;
+use bit_field::BitField;
+use core::arch::asm;
+
+/// The header of an [`XSaveArea`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct XSaveHeader {
+    /// The state components that are not in their initial configuration.
+    pub xstate_bv: u64,
+    /// Bit 63 indicates the compacted format, the other bits the state components that are
+    /// part of the compacted area.
+    pub xcomp_bv: u64,
+    reserved: [u64; 6],
+}
+
+/// The beginning of an XSAVE area: the legacy FXSAVE region and the XSAVE header.
+///
+/// The extended region with the state components enabled in [`XCr0`][crate::registers::xcontrol::XCr0]
+/// directly follows this struct. The complete area must be [`required_size`](Self::required_size)
+/// bytes large, so the memory passed to [`xsave`] and [`xrstor`] must usually be allocated
+/// dynamically (aligned to 64 bytes) and cast to `*mut XSaveArea`.
+#[repr(C, align(64))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct XSaveArea {
+    /// The legacy region, which uses the same layout as the `FXSAVE` area.
+    pub legacy_region: FxsaveArea,
+    /// The XSAVE header.
+    pub header: XSaveHeader,
+}
+
+impl XSaveArea {
+    /// Creates a new XSAVE area with all fields set to zero.
+    #[inline]
+    pub const fn new() -> Self {
+        XSaveArea {
+            legacy_region: FxsaveArea::new(),
+            header: XSaveHeader {
+                xstate_bv: 0,
+                xcomp_bv: 0,
+                reserved: [0; 6],
+            },
+        }
+    }
+
+    /// Returns the size in bytes of an XSAVE area containing all state components that are
+    /// currently enabled in `XCR0`.
+    ///
+    /// This queries CPUID leaf `0xD`.
+    #[inline]
+    pub fn required_size() -> usize {
+        // `__cpuid_count` is safe on newer compilers but unsafe on our MSRV.
+        #[allow(unused_unsafe)]
+        let cpuid = unsafe { core::arch::x86_64::__cpuid_count(0xd, 0) };
+        cpuid.ebx as usize
+    }
+
+    /// Returns the size in bytes of an XSAVE area containing all state components supported
+    /// by the processor.
+    ///
+    /// This queries CPUID leaf `0xD`.
+    #[inline]
+    pub fn max_size() -> usize {
+        // `__cpuid_count` is safe on newer compilers but unsafe on our MSRV.
+        #[allow(unused_unsafe)]
+        let cpuid = unsafe { core::arch::x86_64::__cpuid_count(0xd, 0) };
+        cpuid.ecx as usize
+    }
+}
+
+impl Default for XSaveArea {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns whether the `xsaveopt` instruction is supported.
+#[inline]
+pub fn xsaveopt_supported() -> bool {
+    // `__cpuid_count` is safe on newer compilers but unsafe on our MSRV.
+    #[allow(unused_unsafe)]
+    let cpuid = unsafe { core::arch::x86_64::__cpuid_count(0xd, 1) };
+    cpuid.eax.get_bit(0)
+}
+
+/// Save the state components selected by `mask` into the given area using the `xsave64`
+/// instruction.
+///
+/// ## Safety
+///
+/// `area` must be aligned to 64 bytes and point to writable memory of at least
+/// [`XSaveArea::required_size`] bytes.
+#[inline]
+pub unsafe fn xsave(area: *mut XSaveArea, mask: XCr0Flags) {
+    let mask = mask.bits();
+    unsafe {
+        asm!(
+            "xsave64 [{}]",
+            in(reg) area,
+            in("eax") mask as u32,
+            in("edx") (mask >> 32) as u32,
+            options(nostack, preserves_flags),
+        );
+    }
+}
+
+/// Save the state components selected by `mask` into the given area using the `xsaveopt64`
+/// instruction, which may skip components that were not modified since the last `xrstor`.
+///
+/// ## Safety
+///
+/// `xsaveopt` must be supported (see [`xsaveopt_supported`]). `area` must be aligned to
+/// 64 bytes and point to writable memory of at least [`XSaveArea::required_size`] bytes.
+#[inline]
+pub unsafe fn xsaveopt(area: *mut XSaveArea, mask: XCr0Flags) {
+    let mask = mask.bits();
+    unsafe {
+        asm!(
+            "xsaveopt64 [{}]",
+            in(reg) area,
+            in("eax") mask as u32,
+            in("edx") (mask >> 32) as u32,
+            options(nostack, preserves_flags),
+        );
+    }
+}
+
+/// Restore the state components selected by `mask` from the given area using the `xrstor64`
+/// instruction.
+///
+/// ## Safety
+///
+/// `area` must be aligned to 64 bytes and point to a valid XSAVE area of at least
+/// [`XSaveArea::required_size`] bytes, e.g. one that was written by [`xsave`]. Otherwise the
+/// instruction throws a `#GP`.
+///
+/// The x87, MMX and SSE registers are declared as clobbered, so the compiler doesn't keep
+/// values in them across this call. Other restored state isn't visible to the compiler: the
+/// caller must ensure that the restored `mxcsr` and x87 control word are what the surrounding
+/// Rust code expects, and that no state components beyond SSE (e.g. the upper halves of the
+/// AVX registers) are used by the code that was compiled to run across this call.
+#[inline]
+pub unsafe fn xrstor(area: *const XSaveArea, mask: XCr0Flags) {
+    let mask = mask.bits();
+    unsafe {
+        asm!(
+            "xrstor64 [{}]",
+            in(reg) area,
+            in("eax") mask as u32,
+            in("edx") (mask >> 32) as u32,
+            out("xmm0") _, out("xmm1") _, out("xmm2") _, out("xmm3") _,
+            out("xmm4") _, out("xmm5") _, out("xmm6") _, out("xmm7") _,
+            out("xmm8") _, out("xmm9") _, out("xmm10") _, out("xmm11") _,
+            out("xmm12") _, out("xmm13") _, out("xmm14") _, out("xmm15") _,
+            out("mm0") _, out("mm1") _, out("mm2") _, out("mm3") _,
+            out("mm4") _, out("mm5") _, out("mm6") _, out("mm7") _,
+            out("st(0)") _, out("st(1)") _, out("st(2)") _, out("st(3)") _,
+            out("st(4)") _, out("st(5)") _, out("st(6)") _, out("st(7)") _,
+            options(nostack, preserves_flags, readonly),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::mem::{align_of, size_of};
+
+    #[test]
+    fn xsave_area_layout() {
+        assert_eq!(size_of::<XSaveHeader>(), 64);
+        assert_eq!(size_of::<XSaveArea>(), 576);
+        assert_eq!(align_of::<XSaveArea>(), 64);
+    }
+}
@@ -9,6 +9,9 @@
 #![deny(missing_debug_implementations)]
 #![deny(unsafe_op_in_unsafe_fn)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
 pub use crate::addr::{align_down, align_up, PhysAddr, VirtAddr};
 
 pub mod addr;
@@ -62,6 +65,51 @@ impl PrivilegeLevel {
     }
 }
 
+impl core::convert::TryFrom<u16> for PrivilegeLevel {
+    type Error = InvalidPrivilegeLevel;
+
+    /// Creates a `PrivilegeLevel` from a numeric value, returning an error if the value is >3.
+    #[inline]
+    fn try_from(value: u16) -> Result<Self, Self::Error> {
+        match value {
+            0..=3 => Ok(PrivilegeLevel::from_u16(value)),
+            _ => Err(InvalidPrivilegeLevel(value)),
+        }
+    }
+}
+
+impl core::fmt::Display for PrivilegeLevel {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Ring {}", *self as u8)
+    }
+}
+
+/// A passed `u16` was not a valid privilege level.
+///
+/// This means that it was greater than 3. Contains the invalid value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InvalidPrivilegeLevel(pub u16);
+
+impl core::fmt::Display for InvalidPrivilegeLevel {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "invalid privilege level {}", self.0)
+    }
+}
+
 pub(crate) mod sealed {
     pub trait Sealed {}
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::TryFrom;
+
+    #[test]
+    fn privilege_level_try_from() {
+        assert_eq!(PrivilegeLevel::try_from(0), Ok(PrivilegeLevel::Ring0));
+        assert_eq!(PrivilegeLevel::try_from(3), Ok(PrivilegeLevel::Ring3));
+        assert_eq!(PrivilegeLevel::try_from(4), Err(InvalidPrivilegeLevel(4)));
+        assert_eq!(PrivilegeLevel::Ring2.to_string(), "Ring 2");
+    }
+}
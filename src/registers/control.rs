@@ -115,8 +115,13 @@ bitflags! {
         const OSXMMEXCPT_ENABLE = 1 << 10;
         /// Prevents the execution of the `SGDT`, `SIDT`, `SLDT`, `SMSW`, and `STR` instructions by
         /// user-mode software.
+        ///
+        /// Requires CPUID.(EAX=07H, ECX=0H):ECX bit 2 (`UMIP`).
         const USER_MODE_INSTRUCTION_PREVENTION = 1 << 11;
         /// Enables 5-level paging on supported CPUs (Intel Only).
+        ///
+        /// Requires CPUID.(EAX=07H, ECX=0H):ECX bit 16 (`LA57`). See also the `la57` feature of
+        /// this crate.
         const L5_PAGING = 1 << 12;
         /// Enables VMX instructions (Intel Only).
         const VIRTUAL_MACHINE_EXTENSIONS = 1 << 13;
@@ -145,29 +150,170 @@ bitflags! {
         ///
         /// Also enables access to the PKRU register (via the `RDPKRU`/`WRPKRU`
         /// instructions) to set user-mode protection key access controls.
+        ///
+        /// Requires CPUID.(EAX=07H, ECX=0H):ECX bit 3 (`PKU`).
         const PROTECTION_KEY_USER = 1 << 22;
         /// Enables Control-flow Enforcement Technology (CET)
         ///
         /// This enables the shadow stack feature, ensuring return addresses read
         /// via `RET` and `IRET` have not been corrupted.
+        ///
+        /// Requires CPUID.(EAX=07H, ECX=0H):ECX bit 7 (`CET_SS`) or
+        /// CPUID.(EAX=07H, ECX=0H):EDX bit 20 (`CET_IBT`).
         const CONTROL_FLOW_ENFORCEMENT = 1 << 23;
         /// Enables protection keys for supervisor-mode pages (Intel Only).
         ///
         /// Also enables the `IA32_PKRS` MSR to set supervisor-mode protection
         /// key access controls.
+        ///
+        /// Requires CPUID.(EAX=07H, ECX=0H):ECX bit 31 (`PKS`).
         const PROTECTION_KEY_SUPERVISOR = 1 << 24;
     }
 }
 
+/// The protection key rights register for user-mode pages (PKRU).
+///
+/// Contains two bits for each of the 16 protection keys that control the access to user-mode
+/// pages tagged with that key. Requires [`Cr4Flags::PROTECTION_KEY_USER`] to be set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[repr(transparent)]
+pub struct Pkru(u32);
+
+bitflags! {
+    /// The access rights of a single protection key in the [`Pkru`] register.
+    #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+    pub struct PkeyRights: u32 {
+        /// Disables all data accesses to pages tagged with the protection key.
+        const ACCESS_DISABLE = 1;
+        /// Disables writes to pages tagged with the protection key.
+        const WRITE_DISABLE = 1 << 1;
+    }
+}
+
+impl Pkru {
+    /// Creates a `Pkru` value that allows all accesses for all protection keys.
+    #[inline]
+    pub const fn new() -> Self {
+        Pkru(0)
+    }
+
+    /// Creates a `Pkru` value from its raw representation.
+    #[inline]
+    pub const fn from_bits(bits: u32) -> Self {
+        Pkru(bits)
+    }
+
+    /// Returns the raw representation of the register.
+    #[inline]
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Returns the access rights bits of the given protection key.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `key` is not in the range `0..16`.
+    #[inline]
+    pub const fn rights(self, key: u8) -> PkeyRights {
+        PkeyRights::from_bits_truncate(self.0 >> Self::shift(key))
+    }
+
+    /// Sets the access rights bits of the given protection key.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `key` is not in the range `0..16`.
+    #[inline]
+    pub fn set_rights(&mut self, key: u8, rights: PkeyRights) {
+        let shift = Self::shift(key);
+        self.0 = (self.0 & !(0b11 << shift)) | (rights.bits() << shift);
+    }
+
+    /// Returns a copy with all data accesses disabled for the given protection key.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `key` is not in the range `0..16`.
+    #[inline]
+    #[must_use]
+    pub const fn access_disable(self, key: u8) -> Self {
+        Pkru(self.0 | (PkeyRights::ACCESS_DISABLE.bits() << Self::shift(key)))
+    }
+
+    /// Returns a copy with writes disabled for the given protection key.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `key` is not in the range `0..16`.
+    #[inline]
+    #[must_use]
+    pub const fn write_disable(self, key: u8) -> Self {
+        Pkru(self.0 | (PkeyRights::WRITE_DISABLE.bits() << Self::shift(key)))
+    }
+
+    const fn shift(key: u8) -> u32 {
+        assert!(key < 16, "protection key must be in the range 0..16");
+        key as u32 * 2
+    }
+}
+
 #[cfg(all(feature = "instructions", target_arch = "x86_64"))]
 mod x86_64 {
     use super::*;
     use crate::{
-        addr::VirtAddrNotValid, instructions::tlb::Pcid, structures::paging::PhysFrame, PhysAddr,
-        VirtAddr,
+        addr::VirtAddrNotValid,
+        instructions::{cache::wbinvd, interrupts, tlb::Pcid},
+        structures::paging::PhysFrame,
+        PhysAddr, VirtAddr,
     };
     use core::arch::asm;
 
+    impl Pkru {
+        /// Read the current value of the PKRU register using the `rdpkru` instruction.
+        ///
+        /// Requires [`Cr4Flags::PROTECTION_KEY_USER`] to be set, otherwise the
+        /// instruction throws a `#UD`.
+        #[inline]
+        pub fn read() -> Pkru {
+            let value: u32;
+
+            unsafe {
+                asm!(
+                    "rdpkru",
+                    in("ecx") 0,
+                    out("eax") value,
+                    out("edx") _,
+                    options(nomem, nostack, preserves_flags),
+                );
+            }
+
+            Pkru(value)
+        }
+
+        /// Write the PKRU register using the `wrpkru` instruction.
+        ///
+        /// Requires [`Cr4Flags::PROTECTION_KEY_USER`] to be set, otherwise the
+        /// instruction throws a `#UD`.
+        ///
+        /// ## Safety
+        ///
+        /// This function is unsafe because it's possible to
+        /// revoke access to memory that is still in use.
+        #[inline]
+        pub unsafe fn write(pkru: Pkru) {
+            unsafe {
+                asm!(
+                    "wrpkru",
+                    in("eax") pkru.0,
+                    in("ecx") 0,
+                    in("edx") 0,
+                    options(nostack, preserves_flags),
+                );
+            }
+        }
+    }
+
     impl Cr0 {
         /// Read the current set of CR0 flags.
         #[inline]
@@ -240,11 +386,54 @@ mod x86_64 {
                 Self::write(flags);
             }
         }
+
+        /// Runs `f` with disabled caches.
+        ///
+        /// Interrupts are disabled, [`CACHE_DISABLE`](Cr0Flags::CACHE_DISABLE) is set and
+        /// [`NOT_WRITE_THROUGH`](Cr0Flags::NOT_WRITE_THROUGH) is cleared, and the caches are
+        /// flushed with [`wbinvd`] before calling `f`. Afterwards,
+        /// the caches are flushed again and the previous CR0 value is restored.
+        ///
+        /// For updating the MTRRs, which additionally requires flushing the TLB, use
+        /// [`Mtrr::update`](crate::registers::mtrr::Mtrr::update) instead.
+        ///
+        /// ## Safety
+        ///
+        /// The caller must ensure that running `f` with disabled caches doesn't violate memory
+        /// safety, e.g. because of a changed memory type.
+        #[inline]
+        pub unsafe fn with_caches_disabled<F, R>(f: F) -> R
+        where
+            F: FnOnce() -> R,
+        {
+            interrupts::without_interrupts(|| {
+                let cr0 = Self::read_raw();
+                let disabled =
+                    (cr0 | Cr0Flags::CACHE_DISABLE.bits()) & !Cr0Flags::NOT_WRITE_THROUGH.bits();
+                unsafe {
+                    Self::write_raw(disabled);
+                }
+                wbinvd();
+
+                let ret = f();
+
+                wbinvd();
+                unsafe {
+                    Self::write_raw(cr0);
+                }
+                ret
+            })
+        }
     }
 
     impl Cr2 {
         /// Read the current page fault linear address from the CR2 register.
         ///
+        /// CR2 is overwritten by every page fault, so it should be read at the beginning of the
+        /// page fault handler. If another page fault occurs before that, e.g. in an NMI handler
+        /// or because the handler itself accesses an unmapped page, the value belongs to that
+        /// fault instead.
+        ///
         /// # Errors
         ///
         /// This method returns a [`VirtAddrNotValid`] error if the CR2 register contains a
@@ -495,5 +684,77 @@ mod x86_64 {
                 Self::write(flags);
             }
         }
+
+        /// Sets the given CR4 flags in a single read-modify-write and returns the previous
+        /// flags.
+        ///
+        /// This is intended for enabling protection features such as
+        /// [`SUPERVISOR_MODE_EXECUTION_PROTECTION`](Cr4Flags::SUPERVISOR_MODE_EXECUTION_PROTECTION),
+        /// [`SUPERVISOR_MODE_ACCESS_PREVENTION`](Cr4Flags::SUPERVISOR_MODE_ACCESS_PREVENTION)
+        /// and [`USER_MODE_INSTRUCTION_PREVENTION`](Cr4Flags::USER_MODE_INSTRUCTION_PREVENTION)
+        /// without clobbering other flags. The features must be supported by the processor,
+        /// otherwise the write causes a general protection fault.
+        ///
+        /// ## Safety
+        ///
+        /// This function is unsafe because it's possible to violate memory
+        /// safety through it, e.g. by enabling SMAP while code still accesses
+        /// user-mode pages.
+        #[inline]
+        pub unsafe fn enable_protections(flags: Cr4Flags) -> Cr4Flags {
+            let old_value = Self::read_raw();
+            unsafe {
+                Self::write_raw(old_value | flags.bits());
+            }
+            Cr4Flags::from_bits_truncate(old_value)
+        }
+
+        /// Clears the given CR4 flags in a single read-modify-write and returns the previous
+        /// flags.
+        ///
+        /// This is the counterpart to [`enable_protections`](Self::enable_protections).
+        ///
+        /// ## Safety
+        ///
+        /// This function is unsafe because it's possible to violate memory
+        /// safety through it, e.g. by clearing the physical address extension
+        /// flag.
+        #[inline]
+        pub unsafe fn disable_protections(flags: Cr4Flags) -> Cr4Flags {
+            let old_value = Self::read_raw();
+            unsafe {
+                Self::write_raw(old_value & !flags.bits());
+            }
+            Cr4Flags::from_bits_truncate(old_value)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pkru_rights() {
+        let pkru = Pkru::new()
+            .access_disable(0)
+            .write_disable(1)
+            .write_disable(15);
+        assert_eq!(pkru.bits(), 0b1001 | (0b10 << 30));
+        assert_eq!(pkru.rights(0), PkeyRights::ACCESS_DISABLE);
+        assert_eq!(pkru.rights(1), PkeyRights::WRITE_DISABLE);
+        assert_eq!(pkru.rights(2), PkeyRights::empty());
+        assert_eq!(pkru.rights(15), PkeyRights::WRITE_DISABLE);
+
+        let mut pkru = Pkru::from_bits(pkru.bits());
+        pkru.set_rights(1, PkeyRights::all());
+        pkru.set_rights(15, PkeyRights::empty());
+        assert_eq!(pkru.bits(), 0b1101);
+    }
+
+    #[test]
+    #[should_panic]
+    fn pkru_invalid_key() {
+        let _ = Pkru::new().rights(16);
     }
 }
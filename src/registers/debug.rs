@@ -160,6 +160,13 @@ impl Dr6Flags {
             DebugAddressRegisterNumber::Dr3 => Self::TRAP3,
         }
     }
+
+    /// Returns the debug address registers whose breakpoint conditions were met.
+    pub fn triggered(self) -> impl Iterator<Item = DebugAddressRegisterNumber> {
+        (0..4)
+            .filter_map(DebugAddressRegisterNumber::new)
+            .filter(move |&n| self.contains(Self::trap(n)))
+    }
 }
 
 bitflags! {
@@ -437,6 +444,98 @@ impl Dr7Value {
 #[derive(Debug)]
 pub struct Dr7;
 
+/// A hardware breakpoint that can be installed in a debug address register.
+#[cfg_attr(
+    all(feature = "instructions", target_arch = "x86_64"),
+    doc = "
+```no_run
+use x86_64::registers::debug::{
+    BreakpointCondition, BreakpointSize, DebugAddressRegisterNumber, HardwareBreakpoint,
+};
+
+# let addr = 0x1000;
+HardwareBreakpoint::new(DebugAddressRegisterNumber::Dr0, addr)
+    .condition(BreakpointCondition::DataWrites)
+    .size(BreakpointSize::Length8B)
+    .enable();
+```"
+)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HardwareBreakpoint {
+    n: DebugAddressRegisterNumber,
+    addr: u64,
+    condition: BreakpointCondition,
+    size: BreakpointSize,
+    global: bool,
+}
+
+impl HardwareBreakpoint {
+    /// Creates a local instruction breakpoint at the given address.
+    #[inline]
+    pub const fn new(n: DebugAddressRegisterNumber, addr: u64) -> Self {
+        Self {
+            n,
+            addr,
+            condition: BreakpointCondition::InstructionExecution,
+            size: BreakpointSize::Length1B,
+            global: false,
+        }
+    }
+
+    /// Sets the condition of the breakpoint.
+    #[inline]
+    pub const fn condition(mut self, condition: BreakpointCondition) -> Self {
+        self.condition = condition;
+        self
+    }
+
+    /// Sets the size of the breakpoint.
+    ///
+    /// Instruction breakpoints must have a size of 1 byte. For data breakpoints, the address
+    /// must be aligned to the size.
+    #[inline]
+    pub const fn size(mut self, size: BreakpointSize) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Enables the breakpoint for all tasks instead of only for the current task.
+    #[inline]
+    pub const fn global(mut self) -> Self {
+        self.global = true;
+        self
+    }
+
+    /// Returns the debug address register that is used for this breakpoint.
+    #[inline]
+    pub const fn register(&self) -> DebugAddressRegisterNumber {
+        self.n
+    }
+
+    /// Returns the address of this breakpoint.
+    #[inline]
+    pub const fn addr(&self) -> u64 {
+        self.addr
+    }
+
+    /// Sets the condition, size, and enable fields of this breakpoint in the given DR7 value.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if this is an instruction breakpoint with a size other than 1 byte.
+    pub fn apply(&self, dr7: &mut Dr7Value) {
+        assert!(
+            self.condition != BreakpointCondition::InstructionExecution
+                || self.size == BreakpointSize::Length1B,
+            "instruction breakpoints must have a size of 1 byte"
+        );
+        dr7.set_condition(self.n, self.condition);
+        dr7.set_size(self.n, self.size);
+        dr7.set_flags(Dr7Flags::local_breakpoint_enable(self.n), !self.global);
+        dr7.set_flags(Dr7Flags::global_breakpoint_enable(self.n), self.global);
+    }
+}
+
 #[cfg(all(feature = "instructions", target_arch = "x86_64"))]
 mod x86_64 {
     use super::*;
@@ -513,4 +612,75 @@ mod x86_64 {
             Self::write(value);
         }
     }
+
+    impl HardwareBreakpoint {
+        /// Writes the address of this breakpoint to its debug address register and enables it
+        /// in [`Dr7`].
+        ///
+        /// ## Panics
+        ///
+        /// Panics if this is an instruction breakpoint with a size other than 1 byte.
+        pub fn enable(&self) {
+            match self.n {
+                DebugAddressRegisterNumber::Dr0 => Dr0::write(self.addr),
+                DebugAddressRegisterNumber::Dr1 => Dr1::write(self.addr),
+                DebugAddressRegisterNumber::Dr2 => Dr2::write(self.addr),
+                DebugAddressRegisterNumber::Dr3 => Dr3::write(self.addr),
+            }
+            Dr7::update(|dr7| self.apply(dr7));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn breakpoint_dr7_bits() {
+        let mut dr7 = Dr7Value::from(Dr7Flags::empty());
+        HardwareBreakpoint::new(DebugAddressRegisterNumber::Dr1, 0x1000)
+            .condition(BreakpointCondition::DataReadsWrites)
+            .size(BreakpointSize::Length4B)
+            .apply(&mut dr7);
+        // L1, R/W1 = 0b11, LEN1 = 0b11
+        assert_eq!(dr7.bits(), (1 << 2) | (0b1111 << 20));
+
+        HardwareBreakpoint::new(DebugAddressRegisterNumber::Dr3, 0x2000)
+            .condition(BreakpointCondition::DataWrites)
+            .size(BreakpointSize::Length8B)
+            .global()
+            .apply(&mut dr7);
+        // G3, R/W3 = 0b01, LEN3 = 0b10
+        assert_eq!(
+            dr7.bits(),
+            (1 << 2) | (0b1111 << 20) | (1 << 7) | (0b1001 << 28)
+        );
+        assert_eq!(
+            dr7.condition(DebugAddressRegisterNumber::Dr3),
+            BreakpointCondition::DataWrites
+        );
+        assert_eq!(
+            dr7.size(DebugAddressRegisterNumber::Dr3),
+            BreakpointSize::Length8B
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn instruction_breakpoint_size() {
+        let mut dr7 = Dr7Value::from(Dr7Flags::empty());
+        HardwareBreakpoint::new(DebugAddressRegisterNumber::Dr0, 0x1000)
+            .size(BreakpointSize::Length2B)
+            .apply(&mut dr7);
+    }
+
+    #[test]
+    fn dr6_triggered() {
+        let dr6 = Dr6Flags::TRAP0 | Dr6Flags::TRAP2;
+        let mut triggered = dr6.triggered();
+        assert_eq!(triggered.next(), Some(DebugAddressRegisterNumber::Dr0));
+        assert_eq!(triggered.next(), Some(DebugAddressRegisterNumber::Dr2));
+        assert_eq!(triggered.next(), None);
+    }
 }
@@ -3,6 +3,7 @@
 pub mod control;
 pub mod debug;
 pub mod model_specific;
+pub mod mtrr;
 pub mod mxcsr;
 pub mod rflags;
 pub mod segmentation;
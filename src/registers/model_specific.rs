@@ -5,6 +5,130 @@ use bitflags::bitflags;
 #[cfg(doc)]
 use crate::registers::segmentation::{FS, GS};
 
+/// The indices of common model specific registers, for use with [`Msr::new`].
+///
+/// The names follow the Intel SDM (volume 4).
+pub mod msr_index {
+    /// The time-stamp counter.
+    pub const IA32_TIME_STAMP_COUNTER: u32 = 0x10;
+    /// Platform ID (Intel-only).
+    pub const IA32_PLATFORM_ID: u32 = 0x17;
+    /// APIC base address and flags, see [`ApicBase`](super::ApicBase).
+    pub const IA32_APIC_BASE: u32 = 0x1B;
+    /// Feature control, see [`FeatureControl`](super::FeatureControl).
+    pub const IA32_FEATURE_CONTROL: u32 = 0x3A;
+    /// Adjustment of the time-stamp counter.
+    pub const IA32_TSC_ADJUST: u32 = 0x3B;
+    /// Speculation control.
+    pub const IA32_SPEC_CTRL: u32 = 0x48;
+    /// Prediction command, e.g. for issuing an IBPB.
+    pub const IA32_PRED_CMD: u32 = 0x49;
+    /// Microcode update signature.
+    pub const IA32_BIOS_SIGN_ID: u32 = 0x8B;
+    /// MTRR capabilities, see [`mtrr`](crate::registers::mtrr).
+    pub const IA32_MTRRCAP: u32 = 0xFE;
+    /// Enumeration of architectural features, e.g. vulnerability mitigations.
+    pub const IA32_ARCH_CAPABILITIES: u32 = 0x10A;
+    /// Code segment used by `sysenter`.
+    pub const IA32_SYSENTER_CS: u32 = 0x174;
+    /// Stack pointer used by `sysenter`.
+    pub const IA32_SYSENTER_ESP: u32 = 0x175;
+    /// Instruction pointer used by `sysenter`.
+    pub const IA32_SYSENTER_EIP: u32 = 0x176;
+    /// Machine check capabilities.
+    pub const IA32_MCG_CAP: u32 = 0x179;
+    /// Machine check status.
+    pub const IA32_MCG_STATUS: u32 = 0x17A;
+    /// Current performance state.
+    pub const IA32_PERF_STATUS: u32 = 0x198;
+    /// Performance control.
+    pub const IA32_PERF_CTL: u32 = 0x199;
+    /// Thermal status.
+    pub const IA32_THERM_STATUS: u32 = 0x19C;
+    /// Miscellaneous features (Intel-only).
+    pub const IA32_MISC_ENABLE: u32 = 0x1A0;
+    /// Base of the first variable range MTRR, see [`mtrr_physbase`].
+    pub const IA32_MTRR_PHYSBASE0: u32 = 0x200;
+    /// Mask of the first variable range MTRR, see [`mtrr_physmask`].
+    pub const IA32_MTRR_PHYSMASK0: u32 = 0x201;
+    /// Fixed range MTRR for 0x00000 to 0x7FFFF.
+    pub const IA32_MTRR_FIX64K_00000: u32 = 0x250;
+    /// Fixed range MTRR for 0x80000 to 0x9FFFF.
+    pub const IA32_MTRR_FIX16K_80000: u32 = 0x258;
+    /// Fixed range MTRR for 0xA0000 to 0xBFFFF.
+    pub const IA32_MTRR_FIX16K_A0000: u32 = 0x259;
+    /// Fixed range MTRR for 0xC0000 to 0xC7FFF.
+    pub const IA32_MTRR_FIX4K_C0000: u32 = 0x268;
+    /// Fixed range MTRR for 0xC8000 to 0xCFFFF.
+    pub const IA32_MTRR_FIX4K_C8000: u32 = 0x269;
+    /// Fixed range MTRR for 0xD0000 to 0xD7FFF.
+    pub const IA32_MTRR_FIX4K_D0000: u32 = 0x26A;
+    /// Fixed range MTRR for 0xD8000 to 0xDFFFF.
+    pub const IA32_MTRR_FIX4K_D8000: u32 = 0x26B;
+    /// Fixed range MTRR for 0xE0000 to 0xE7FFF.
+    pub const IA32_MTRR_FIX4K_E0000: u32 = 0x26C;
+    /// Fixed range MTRR for 0xE8000 to 0xEFFFF.
+    pub const IA32_MTRR_FIX4K_E8000: u32 = 0x26D;
+    /// Fixed range MTRR for 0xF0000 to 0xF7FFF.
+    pub const IA32_MTRR_FIX4K_F0000: u32 = 0x26E;
+    /// Fixed range MTRR for 0xF8000 to 0xFFFFF.
+    pub const IA32_MTRR_FIX4K_F8000: u32 = 0x26F;
+    /// Page attribute table, see [`Pat`](super::Pat).
+    pub const IA32_PAT: u32 = 0x277;
+    /// Default MTRR memory type.
+    pub const IA32_MTRR_DEF_TYPE: u32 = 0x2FF;
+    /// User mode CET configuration, see [`UCet`](super::UCet).
+    pub const IA32_U_CET: u32 = 0x6A0;
+    /// Supervisor mode CET configuration, see [`SCet`](super::SCet).
+    pub const IA32_S_CET: u32 = 0x6A2;
+    /// Ring 0 shadow stack pointer, see [`Pl0Ssp`](super::Pl0Ssp).
+    pub const IA32_PL0_SSP: u32 = 0x6A4;
+    /// Ring 1 shadow stack pointer, see [`Pl1Ssp`](super::Pl1Ssp).
+    pub const IA32_PL1_SSP: u32 = 0x6A5;
+    /// Ring 2 shadow stack pointer, see [`Pl2Ssp`](super::Pl2Ssp).
+    pub const IA32_PL2_SSP: u32 = 0x6A6;
+    /// Ring 3 shadow stack pointer, see [`Pl3Ssp`](super::Pl3Ssp).
+    pub const IA32_PL3_SSP: u32 = 0x6A7;
+    /// Interrupt shadow stack table, see [`InterruptSspTable`](super::InterruptSspTable).
+    pub const IA32_INTERRUPT_SSP_TABLE_ADDR: u32 = 0x6A8;
+    /// TSC deadline of the local APIC timer, see [`TscDeadline`](super::TscDeadline).
+    pub const IA32_TSC_DEADLINE: u32 = 0x6E0;
+    /// Protection key rights for supervisor pages.
+    pub const IA32_PKRS: u32 = 0x6E1;
+    /// Supervisor state components enabled for `xsaves`.
+    pub const IA32_XSS: u32 = 0xDA0;
+    /// Extended feature enable register, see [`Efer`](super::Efer).
+    pub const IA32_EFER: u32 = 0xC000_0080;
+    /// `syscall` segment selectors, see [`Star`](super::Star).
+    pub const IA32_STAR: u32 = 0xC000_0081;
+    /// `syscall` target in 64-bit mode, see [`LStar`](super::LStar).
+    pub const IA32_LSTAR: u32 = 0xC000_0082;
+    /// `syscall` target in compatibility mode, see [`CStar`](super::CStar).
+    pub const IA32_CSTAR: u32 = 0xC000_0083;
+    /// `syscall` RFLAGS mask, see [`SFMask`](super::SFMask).
+    pub const IA32_FMASK: u32 = 0xC000_0084;
+    /// FS base, see [`FsBase`](super::FsBase).
+    pub const IA32_FS_BASE: u32 = 0xC000_0100;
+    /// GS base, see [`GsBase`](super::GsBase).
+    pub const IA32_GS_BASE: u32 = 0xC000_0101;
+    /// GS base swapped by `swapgs`, see [`KernelGsBase`](super::KernelGsBase).
+    pub const IA32_KERNEL_GS_BASE: u32 = 0xC000_0102;
+    /// Auxiliary TSC value, see [`TscAux`](super::TscAux).
+    pub const IA32_TSC_AUX: u32 = 0xC000_0103;
+
+    /// Returns the index of IA32_MTRR_PHYSBASEn.
+    #[inline]
+    pub const fn mtrr_physbase(n: u8) -> u32 {
+        IA32_MTRR_PHYSBASE0 + 2 * n as u32
+    }
+
+    /// Returns the index of IA32_MTRR_PHYSMASKn.
+    #[inline]
+    pub const fn mtrr_physmask(n: u8) -> u32 {
+        IA32_MTRR_PHYSMASK0 + 2 * n as u32
+    }
+}
+
 /// A model specific register.
 #[cfg_attr(
     not(all(feature = "instructions", target_arch = "x86_64")),
@@ -21,6 +145,106 @@ impl Msr {
     }
 }
 
+/// An output register of the `cpuid` instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CpuidRegister {
+    /// The `EAX` register.
+    Eax,
+    /// The `EBX` register.
+    Ebx,
+    /// The `ECX` register.
+    Ecx,
+    /// The `EDX` register.
+    Edx,
+}
+
+/// A feature bit reported by the `cpuid` instruction, e.g. CPUID.80000001H:EDX bit 27
+/// (`RDTSCP`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CpuidFeature {
+    /// The leaf, which is passed in `EAX`.
+    pub leaf: u32,
+    /// The subleaf, which is passed in `ECX`.
+    pub subleaf: u32,
+    /// The register that contains the feature bit.
+    pub register: CpuidRegister,
+    /// The position of the feature bit in the register.
+    pub bit: u8,
+}
+
+impl CpuidFeature {
+    /// Creates a new feature bit.
+    ///
+    /// Panics if `bit` is not smaller than 32.
+    #[inline]
+    pub const fn new(leaf: u32, subleaf: u32, register: CpuidRegister, bit: u8) -> Self {
+        assert!(bit < 32, "CPUID feature bit must be smaller than 32");
+        CpuidFeature {
+            leaf,
+            subleaf,
+            register,
+            bit,
+        }
+    }
+}
+
+/// A model specific register that is only accessed if the processor supports it.
+///
+/// Accessing an MSR that isn't implemented causes a general protection fault. A `CheckedMsr`
+/// checks the [`CpuidFeature`] that indicates support for the MSR once when it is created,
+/// and refuses all accesses if the feature isn't supported.
+#[cfg_attr(
+    all(feature = "instructions", target_arch = "x86_64"),
+    doc = "
+
+```
+use x86_64::registers::model_specific::{CpuidFeature, CpuidRegister, Msr};
+
+// IA32_TSC_AUX is supported if CPUID.80000001H:EDX.RDTSCP[bit 27] is set.
+const RDTSCP: CpuidFeature = CpuidFeature::new(0x8000_0001, 0, CpuidRegister::Edx, 27);
+let tsc_aux = Msr::new_checked(0xC000_0103, RDTSCP);
+assert_eq!(tsc_aux.is_supported(), RDTSCP.is_supported());
+
+// Unsupported MSRs are never accessed.
+let unsupported = CpuidFeature::new(0x7fff_ffff, 0, CpuidRegister::Eax, 0);
+assert!(unsafe { Msr::new_checked(0x1234, unsupported).try_read() }.is_err());
+```"
+)]
+#[cfg_attr(
+    not(all(feature = "instructions", target_arch = "x86_64")),
+    allow(dead_code)
+)]
+#[derive(Debug)]
+pub struct CheckedMsr {
+    msr: Msr,
+    feature: CpuidFeature,
+    supported: bool,
+}
+
+/// The error returned when accessing a [`CheckedMsr`] that isn't supported by the processor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MsrError {
+    feature: CpuidFeature,
+}
+
+impl MsrError {
+    /// Returns the feature bit that isn't set.
+    #[inline]
+    pub const fn feature(&self) -> CpuidFeature {
+        self.feature
+    }
+}
+
+impl core::fmt::Display for MsrError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "MSR is not supported: CPUID.{:X}H.{:X}H:{:?} bit {} is not set",
+            self.feature.leaf, self.feature.subleaf, self.feature.register, self.feature.bit
+        )
+    }
+}
+
 /// The Extended Feature Enable Register.
 #[derive(Debug)]
 pub struct Efer;
@@ -55,6 +279,10 @@ pub struct Star;
 #[derive(Debug)]
 pub struct LStar;
 
+/// Syscall Register: CSTAR
+#[derive(Debug)]
+pub struct CStar;
+
 /// Syscall Register: SFMASK
 #[derive(Debug)]
 pub struct SFMask;
@@ -67,6 +295,27 @@ pub struct UCet;
 #[derive(Debug)]
 pub struct SCet;
 
+/// IA32_PL0_SSP: shadow stack pointer that is loaded when switching to ring 0
+#[derive(Debug)]
+pub struct Pl0Ssp;
+
+/// IA32_PL1_SSP: shadow stack pointer that is loaded when switching to ring 1
+#[derive(Debug)]
+pub struct Pl1Ssp;
+
+/// IA32_PL2_SSP: shadow stack pointer that is loaded when switching to ring 2
+#[derive(Debug)]
+pub struct Pl2Ssp;
+
+/// IA32_PL3_SSP: shadow stack pointer that is loaded when switching to ring 3
+#[derive(Debug)]
+pub struct Pl3Ssp;
+
+/// IA32_INTERRUPT_SSP_TABLE_ADDR: address of the table of shadow stack pointers that are
+/// used for interrupts with an IST index
+#[derive(Debug)]
+pub struct InterruptSspTable;
+
 /// IA32_PAT: Page Attribute Table.
 #[derive(Debug)]
 pub struct Pat;
@@ -77,54 +326,114 @@ pub struct Pat;
 #[derive(Debug)]
 pub struct ApicBase;
 
+/// IA32_TSC_AUX: auxiliary value returned by `rdtscp` and `rdpid`
+///
+/// This usually contains an ID of the current processor.
+#[derive(Debug)]
+pub struct TscAux;
+
+/// IA32_TSC_DEADLINE: the deadline of the local APIC timer in TSC-deadline mode
+///
+/// The timer fires once the time-stamp counter reaches the written value. Writing 0 disarms
+/// the timer.
+#[derive(Debug)]
+pub struct TscDeadline;
+
+/// IA32_FEATURE_CONTROL: enables VMX, SMX and SGX (Intel-only)
+///
+/// The MSR is usually configured and locked by the firmware. Once
+/// [`LOCK`](FeatureControlFlags::LOCK) is set, it can't be written until the next reset.
+#[derive(Debug)]
+pub struct FeatureControl;
+
+/// The error returned when writing [`FeatureControl`] after it was locked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FeatureControlLocked;
+
+impl core::fmt::Display for FeatureControlLocked {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("IA32_FEATURE_CONTROL is already locked")
+    }
+}
+
 impl Efer {
     /// The underlying model specific register.
-    pub const MSR: Msr = Msr(0xC000_0080);
+    pub const MSR: Msr = Msr(msr_index::IA32_EFER);
 }
 
 impl FsBase {
     /// The underlying model specific register.
-    pub const MSR: Msr = Msr(0xC000_0100);
+    pub const MSR: Msr = Msr(msr_index::IA32_FS_BASE);
 }
 
 impl GsBase {
     /// The underlying model specific register.
-    pub const MSR: Msr = Msr(0xC000_0101);
+    pub const MSR: Msr = Msr(msr_index::IA32_GS_BASE);
 }
 
 impl KernelGsBase {
     /// The underlying model specific register.
-    pub const MSR: Msr = Msr(0xC000_0102);
+    pub const MSR: Msr = Msr(msr_index::IA32_KERNEL_GS_BASE);
 }
 
 impl Star {
     /// The underlying model specific register.
-    pub const MSR: Msr = Msr(0xC000_0081);
+    pub const MSR: Msr = Msr(msr_index::IA32_STAR);
 }
 
 impl LStar {
     /// The underlying model specific register.
-    pub const MSR: Msr = Msr(0xC000_0082);
+    pub const MSR: Msr = Msr(msr_index::IA32_LSTAR);
+}
+
+impl CStar {
+    /// The underlying model specific register.
+    pub const MSR: Msr = Msr(msr_index::IA32_CSTAR);
 }
 
 impl SFMask {
     /// The underlying model specific register.
-    pub const MSR: Msr = Msr(0xC000_0084);
+    pub const MSR: Msr = Msr(msr_index::IA32_FMASK);
 }
 
 impl UCet {
     /// The underlying model specific register.
-    pub const MSR: Msr = Msr(0x6A0);
+    pub const MSR: Msr = Msr(msr_index::IA32_U_CET);
 }
 
 impl SCet {
     /// The underlying model specific register.
-    pub const MSR: Msr = Msr(0x6A2);
+    pub const MSR: Msr = Msr(msr_index::IA32_S_CET);
+}
+
+impl Pl0Ssp {
+    /// The underlying model specific register.
+    pub const MSR: Msr = Msr(msr_index::IA32_PL0_SSP);
+}
+
+impl Pl1Ssp {
+    /// The underlying model specific register.
+    pub const MSR: Msr = Msr(msr_index::IA32_PL1_SSP);
+}
+
+impl Pl2Ssp {
+    /// The underlying model specific register.
+    pub const MSR: Msr = Msr(msr_index::IA32_PL2_SSP);
+}
+
+impl Pl3Ssp {
+    /// The underlying model specific register.
+    pub const MSR: Msr = Msr(msr_index::IA32_PL3_SSP);
+}
+
+impl InterruptSspTable {
+    /// The underlying model specific register.
+    pub const MSR: Msr = Msr(msr_index::IA32_INTERRUPT_SSP_TABLE_ADDR);
 }
 
 impl Pat {
     /// The underlying model specific register.
-    pub const MSR: Msr = Msr(0x277);
+    pub const MSR: Msr = Msr(msr_index::IA32_PAT);
     /// The default PAT configuration following a power up or reset of the processor.
     pub const DEFAULT: [PatMemoryType; 8] = [
         PatMemoryType::WriteBack,
@@ -140,7 +449,22 @@ impl Pat {
 
 impl ApicBase {
     /// The underlying model specific register.
-    pub const MSR: Msr = Msr(0x1B);
+    pub const MSR: Msr = Msr(msr_index::IA32_APIC_BASE);
+}
+
+impl TscAux {
+    /// The underlying model specific register.
+    pub const MSR: Msr = Msr(msr_index::IA32_TSC_AUX);
+}
+
+impl TscDeadline {
+    /// The underlying model specific register.
+    pub const MSR: Msr = Msr(msr_index::IA32_TSC_DEADLINE);
+}
+
+impl FeatureControl {
+    /// The underlying model specific register.
+    pub const MSR: Msr = Msr(msr_index::IA32_FEATURE_CONTROL);
 }
 
 bitflags! {
@@ -194,20 +518,23 @@ bitflags! {
 
 #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
 /// Memory types used in the [PAT](Pat).
-#[repr(u8)]
 pub enum PatMemoryType {
     /// Uncacheable (UC).
-    StrongUncacheable = 0x00,
+    StrongUncacheable,
     /// Uses a write combining (WC) cache policy.
-    WriteCombining = 0x01,
+    WriteCombining,
     /// Uses a write through (WT) cache policy.
-    WriteThrough = 0x04,
+    WriteThrough,
     /// Uses a write protected (WP) cache policy.
-    WriteProtected = 0x05,
+    WriteProtected,
     /// Uses a write back (WB) cache policy.
-    WriteBack = 0x06,
+    WriteBack,
     /// Same as strong uncacheable, but can be overridden to be write combining by MTRRs (UC-).
-    Uncacheable = 0x07,
+    Uncacheable,
+    /// A reserved encoding.
+    ///
+    /// This is only returned when reading the PAT and can't be written to it.
+    Unknown(u8),
 }
 impl PatMemoryType {
     /// Converts from bits, returning `None` if the value is invalid.
@@ -225,7 +552,15 @@ impl PatMemoryType {
 
     /// Gets the underlying bits.
     pub const fn bits(self) -> u8 {
-        self as u8
+        match self {
+            Self::StrongUncacheable => 0x00,
+            Self::WriteCombining => 0x01,
+            Self::WriteThrough => 0x04,
+            Self::WriteProtected => 0x05,
+            Self::WriteBack => 0x06,
+            Self::Uncacheable => 0x07,
+            Self::Unknown(bits) => bits,
+        }
     }
 }
 
@@ -246,11 +581,56 @@ bitflags! {
     }
 }
 
+bitflags! {
+    /// Flags of the IA32_FEATURE_CONTROL MSR.
+    #[repr(transparent)]
+    #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+    pub struct FeatureControlFlags: u64 {
+        /// Locks the MSR until the next reset.
+        const LOCK = 1;
+        /// Allows `vmxon` in SMX operation.
+        const VMX_INSIDE_SMX = 1 << 1;
+        /// Allows `vmxon` outside of SMX operation.
+        const VMX_OUTSIDE_SMX = 1 << 2;
+        /// Enables the local functions of `getsec[senter]` (bits 8 to 14).
+        const SENTER_LOCAL_FUNCTIONS = 0x7f << 8;
+        /// Enables `getsec[senter]`.
+        const SENTER_GLOBAL_ENABLE = 1 << 15;
+        /// Allows changing the SGX launch enclave public key hash.
+        const SGX_LAUNCH_CONTROL = 1 << 17;
+        /// Enables SGX.
+        const SGX_GLOBAL_ENABLE = 1 << 18;
+        /// Enables local machine check exceptions.
+        const LMCE_ON = 1 << 20;
+    }
+}
+
+impl FeatureControlFlags {
+    /// Returns whether the MSR is locked.
+    #[inline]
+    pub const fn locked(&self) -> bool {
+        self.contains(Self::LOCK)
+    }
+
+    /// Returns whether `vmxon` is allowed in SMX operation.
+    #[inline]
+    pub const fn vmx_inside_smx_enabled(&self) -> bool {
+        self.contains(Self::VMX_INSIDE_SMX)
+    }
+
+    /// Returns whether `vmxon` is allowed outside of SMX operation.
+    #[inline]
+    pub const fn vmx_outside_smx_enabled(&self) -> bool {
+        self.contains(Self::VMX_OUTSIDE_SMX)
+    }
+}
+
 #[cfg(all(feature = "instructions", target_arch = "x86_64"))]
 mod x86_64 {
     use super::*;
     use crate::addr::VirtAddr;
     use crate::registers::rflags::RFlags;
+    use crate::registers::segmentation::{Segment64, FS, GS};
     use crate::structures::gdt::SegmentSelector;
     use crate::structures::paging::Page;
     use crate::structures::paging::PhysFrame;
@@ -264,7 +644,7 @@ mod x86_64 {
     #[cfg(doc)]
     use crate::registers::{
         control::Cr4Flags,
-        segmentation::{Segment, Segment64, CS, SS},
+        segmentation::{Segment, CS, SS},
     };
     use core::arch::asm;
 
@@ -309,6 +689,93 @@ mod x86_64 {
                 );
             }
         }
+
+        /// Create a [`CheckedMsr`] for the given register, which is only accessed if the
+        /// processor reports the given feature.
+        ///
+        /// The feature is checked once by executing `cpuid`.
+        #[inline]
+        pub fn new_checked(reg: u32, feature: CpuidFeature) -> CheckedMsr {
+            CheckedMsr {
+                msr: Msr(reg),
+                feature,
+                supported: feature.is_supported(),
+            }
+        }
+    }
+
+    impl CpuidFeature {
+        /// Checks whether the current processor reports this feature.
+        ///
+        /// Returns `false` if the leaf isn't supported by the processor.
+        pub fn is_supported(self) -> bool {
+            use core::arch::x86_64::{__cpuid, __cpuid_count};
+
+            // `__cpuid` is safe on newer compilers but unsafe on our MSRV.
+            #[allow(unused_unsafe)]
+            let max_leaf = unsafe { __cpuid(self.leaf & 0x8000_0000) }.eax;
+            if self.leaf > max_leaf {
+                return false;
+            }
+
+            // `__cpuid_count` is safe on newer compilers but unsafe on our MSRV.
+            #[allow(unused_unsafe)]
+            let cpuid = unsafe { __cpuid_count(self.leaf, self.subleaf) };
+            let value = match self.register {
+                CpuidRegister::Eax => cpuid.eax,
+                CpuidRegister::Ebx => cpuid.ebx,
+                CpuidRegister::Ecx => cpuid.ecx,
+                CpuidRegister::Edx => cpuid.edx,
+            };
+            value.get_bit(self.bit.into())
+        }
+    }
+
+    impl CheckedMsr {
+        /// Returns whether the processor supports the MSR.
+        #[inline]
+        pub fn is_supported(&self) -> bool {
+            self.supported
+        }
+
+        /// Returns the underlying MSR, which allows unchecked accesses.
+        #[inline]
+        pub fn msr(&self) -> &Msr {
+            &self.msr
+        }
+
+        /// Read 64 bits from the MSR if it is supported.
+        ///
+        /// ## Safety
+        ///
+        /// The caller must ensure that this read operation has no unsafe side
+        /// effects.
+        #[inline]
+        pub unsafe fn try_read(&self) -> Result<u64, MsrError> {
+            if !self.supported {
+                return Err(MsrError {
+                    feature: self.feature,
+                });
+            }
+            Ok(unsafe { self.msr.read() })
+        }
+
+        /// Write 64 bits to the MSR if it is supported.
+        ///
+        /// ## Safety
+        ///
+        /// The caller must ensure that this write operation has no unsafe side
+        /// effects.
+        #[inline]
+        pub unsafe fn try_write(&mut self, value: u64) -> Result<(), MsrError> {
+            if !self.supported {
+                return Err(MsrError {
+                    feature: self.feature,
+                });
+            }
+            unsafe { self.msr.write(value) };
+            Ok(())
+        }
     }
 
     impl Efer {
@@ -399,6 +866,33 @@ mod x86_64 {
             let mut msr = Self::MSR;
             unsafe { msr.write(address.as_u64()) };
         }
+
+        /// Read the current FS.Base using the `rdfsbase` instruction instead of
+        /// the MSR. This is faster and also works outside of ring 0.
+        ///
+        /// [`CR4.FSGSBASE`][Cr4Flags::FSGSBASE] must be set before calling this method,
+        /// otherwise the instruction throws a `#UD`. Falling back to [`FsBase::read`] is the
+        /// caller's responsibility.
+        #[inline]
+        pub fn read_fast() -> VirtAddr {
+            FS::read_base()
+        }
+
+        /// Write a given virtual address to FS.Base using the `wrfsbase`
+        /// instruction instead of the MSR. This is faster and also works outside of ring 0.
+        ///
+        /// [`CR4.FSGSBASE`][Cr4Flags::FSGSBASE] must be set before calling this method,
+        /// otherwise the instruction throws a `#UD`. Falling back to [`FsBase::write`] is the
+        /// caller's responsibility.
+        ///
+        /// ## Safety
+        ///
+        /// The caller must ensure that this write operation has no unsafe side
+        /// effects, as the segment base address might be in use.
+        #[inline]
+        pub unsafe fn write_fast(address: VirtAddr) {
+            unsafe { FS::write_base(address) }
+        }
     }
 
     impl GsBase {
@@ -420,6 +914,33 @@ mod x86_64 {
             let mut msr = Self::MSR;
             unsafe { msr.write(address.as_u64()) };
         }
+
+        /// Read the current GS.Base using the `rdgsbase` instruction instead of
+        /// the MSR. This is faster and also works outside of ring 0.
+        ///
+        /// [`CR4.FSGSBASE`][Cr4Flags::FSGSBASE] must be set before calling this method,
+        /// otherwise the instruction throws a `#UD`. Falling back to [`GsBase::read`] is the
+        /// caller's responsibility.
+        #[inline]
+        pub fn read_fast() -> VirtAddr {
+            GS::read_base()
+        }
+
+        /// Write a given virtual address to GS.Base using the `wrgsbase`
+        /// instruction instead of the MSR. This is faster and also works outside of ring 0.
+        ///
+        /// [`CR4.FSGSBASE`][Cr4Flags::FSGSBASE] must be set before calling this method,
+        /// otherwise the instruction throws a `#UD`. Falling back to [`GsBase::write`] is the
+        /// caller's responsibility.
+        ///
+        /// ## Safety
+        ///
+        /// The caller must ensure that this write operation has no unsafe side
+        /// effects, as the segment base address might be in use.
+        #[inline]
+        pub unsafe fn write_fast(address: VirtAddr) {
+            unsafe { GS::write_base(address) }
+        }
     }
 
     impl KernelGsBase {
@@ -545,6 +1066,37 @@ mod x86_64 {
 
             Ok(())
         }
+
+        /// Write the Ring 0 and Ring 3 segment bases from the kernel and user code segment
+        /// selectors.
+        ///
+        /// `SYSCALL` and `SYSRET` derive the stack segments from the code segments, so the GDT
+        /// must contain the kernel code segment followed by the kernel data segment, and the
+        /// user data segment followed by the user code segment, i.e. `user_cs` must be
+        /// `kernel_cs + 16` if both pairs are adjacent. This function will fail if `user_cs`
+        /// does not accompany a user data segment at `user_cs - 8` or if the selectors do not
+        /// have the correct privileges.
+        #[inline]
+        pub fn write_selectors(
+            kernel_cs: SegmentSelector,
+            user_cs: SegmentSelector,
+        ) -> Result<(), InvalidStarSegmentSelectors> {
+            if user_cs.0 < 16 {
+                return Err(InvalidStarSegmentSelectors::SysretOffset);
+            }
+            if kernel_cs.rpl() != PrivilegeLevel::Ring0 {
+                return Err(InvalidStarSegmentSelectors::SyscallPrivilegeLevel);
+            }
+            if user_cs.rpl() != PrivilegeLevel::Ring3 {
+                return Err(InvalidStarSegmentSelectors::SysretPrivilegeLevel);
+            }
+            Self::write(
+                user_cs,
+                SegmentSelector(user_cs.0 - 8),
+                kernel_cs,
+                SegmentSelector(kernel_cs.0 + 8),
+            )
+        }
     }
 
     #[derive(Debug)]
@@ -587,6 +1139,26 @@ mod x86_64 {
         }
     }
 
+    impl CStar {
+        /// Read the current CStar register.
+        /// This holds the target RIP of a syscall from compatibility mode.
+        #[inline]
+        pub fn read() -> VirtAddr {
+            VirtAddr::new(unsafe { Self::MSR.read() })
+        }
+
+        /// Write a given virtual address to the CStar register.
+        /// This holds the target RIP of a syscall from compatibility mode.
+        ///
+        /// Intel processors don't support `SYSCALL` in compatibility mode, so this register
+        /// is only used on AMD processors.
+        #[inline]
+        pub fn write(address: VirtAddr) {
+            let mut msr = Self::MSR;
+            unsafe { msr.write(address.as_u64()) };
+        }
+    }
+
     impl SFMask {
         /// Read to the SFMask register.
         /// The SFMASK register is used to specify which RFLAGS bits
@@ -724,16 +1296,96 @@ mod x86_64 {
         }
     }
 
+    impl Pl0Ssp {
+        /// Read the shadow stack pointer for ring 0.
+        #[inline]
+        pub fn read() -> VirtAddr {
+            VirtAddr::new(unsafe { Self::MSR.read() })
+        }
+
+        /// Write the shadow stack pointer for ring 0.
+        #[inline]
+        pub fn write(address: VirtAddr) {
+            let mut msr = Self::MSR;
+            unsafe { msr.write(address.as_u64()) };
+        }
+    }
+
+    impl Pl1Ssp {
+        /// Read the shadow stack pointer for ring 1.
+        #[inline]
+        pub fn read() -> VirtAddr {
+            VirtAddr::new(unsafe { Self::MSR.read() })
+        }
+
+        /// Write the shadow stack pointer for ring 1.
+        #[inline]
+        pub fn write(address: VirtAddr) {
+            let mut msr = Self::MSR;
+            unsafe { msr.write(address.as_u64()) };
+        }
+    }
+
+    impl Pl2Ssp {
+        /// Read the shadow stack pointer for ring 2.
+        #[inline]
+        pub fn read() -> VirtAddr {
+            VirtAddr::new(unsafe { Self::MSR.read() })
+        }
+
+        /// Write the shadow stack pointer for ring 2.
+        #[inline]
+        pub fn write(address: VirtAddr) {
+            let mut msr = Self::MSR;
+            unsafe { msr.write(address.as_u64()) };
+        }
+    }
+
+    impl Pl3Ssp {
+        /// Read the shadow stack pointer for ring 3.
+        #[inline]
+        pub fn read() -> VirtAddr {
+            VirtAddr::new(unsafe { Self::MSR.read() })
+        }
+
+        /// Write the shadow stack pointer for ring 3.
+        #[inline]
+        pub fn write(address: VirtAddr) {
+            let mut msr = Self::MSR;
+            unsafe { msr.write(address.as_u64()) };
+        }
+    }
+
+    impl InterruptSspTable {
+        /// Read the address of the interrupt shadow stack pointer table.
+        #[inline]
+        pub fn read() -> VirtAddr {
+            VirtAddr::new(unsafe { Self::MSR.read() })
+        }
+
+        /// Write the address of the interrupt shadow stack pointer table.
+        ///
+        /// Entry `n` of this table is used as shadow stack pointer for interrupts with IST
+        /// index `n`. Entry 0 is not used.
+        #[inline]
+        pub fn write(address: VirtAddr) {
+            let mut msr = Self::MSR;
+            unsafe { msr.write(address.as_u64()) };
+        }
+    }
+
     impl Pat {
         /// Reads IA32_PAT.
         ///
         /// The PAT must be supported on the CPU, otherwise a general protection exception will
         /// occur. Support can be detected using the `cpuid` instruction.
+        ///
+        /// Entries with a reserved encoding are returned as [`PatMemoryType::Unknown`].
         #[inline]
         pub fn read() -> [PatMemoryType; 8] {
             unsafe { Self::MSR.read() }
                 .to_ne_bytes()
-                .map(|bits| PatMemoryType::from_bits(bits).unwrap())
+                .map(|bits| PatMemoryType::from_bits(bits).unwrap_or(PatMemoryType::Unknown(bits)))
         }
 
         /// Writes IA32_PAT.
@@ -741,6 +1393,10 @@ mod x86_64 {
         /// The PAT must be supported on the CPU, otherwise a general protection exception will
         /// occur. Support can be detected using the `cpuid` instruction.
         ///
+        /// # Panics
+        ///
+        /// Panics if any entry is [`PatMemoryType::Unknown`].
+        ///
         /// # Safety
         ///
         /// All affected pages must be flushed from the TLB. Processor caches may also need to be
@@ -748,6 +1404,12 @@ mod x86_64 {
         /// type.
         #[inline]
         pub unsafe fn write(table: [PatMemoryType; 8]) {
+            assert!(
+                !table
+                    .iter()
+                    .any(|ty| matches!(ty, PatMemoryType::Unknown(_))),
+                "PAT entries must not use a reserved memory type"
+            );
             let bits = u64::from_ne_bytes(table.map(PatMemoryType::bits));
             let mut msr = Self::MSR;
             unsafe {
@@ -807,5 +1469,130 @@ mod x86_64 {
                 msr.write(flags | addr.as_u64());
             }
         }
+
+        /// Updates the IA32_APIC_BASE MSR.
+        ///
+        /// Preserves the value of reserved fields.
+        ///
+        /// ## Safety
+        ///
+        /// Unsafe because changing the APIC base address allows hijacking a page of physical memory space in ways that would violate Rust's memory rules.
+        #[inline]
+        pub unsafe fn update<F>(f: F)
+        where
+            F: FnOnce(&mut PhysFrame, &mut ApicBaseFlags),
+        {
+            let (mut frame, mut flags) = Self::read();
+            f(&mut frame, &mut flags);
+            unsafe {
+                Self::write(frame, flags);
+            }
+        }
+    }
+    impl TscAux {
+        /// Reads the IA32_TSC_AUX MSR.
+        #[inline]
+        pub fn read() -> u32 {
+            // The upper 32 bits are reserved.
+            unsafe { Self::MSR.read() as u32 }
+        }
+
+        /// Writes the IA32_TSC_AUX MSR.
+        ///
+        /// The value is returned by [`read_tscp`](crate::instructions::tsc::read_tscp) and
+        /// [`read_pid`](crate::instructions::read_pid).
+        ///
+        /// ## Safety
+        ///
+        /// Code that uses the value to identify the current processor (e.g. to access per-CPU
+        /// data) relies on it being unique to each processor.
+        #[inline]
+        pub unsafe fn write(value: u32) {
+            let mut msr = Self::MSR;
+            unsafe {
+                msr.write(u64::from(value));
+            }
+        }
+    }
+    impl FeatureControl {
+        /// Reads the IA32_FEATURE_CONTROL MSR.
+        ///
+        /// This MSR only exists on Intel processors (and processors that report VMX or SMX
+        /// support in CPUID.01H:ECX). Reading it on other processors, e.g. on AMD processors,
+        /// causes a general protection fault.
+        #[inline]
+        pub fn read() -> FeatureControlFlags {
+            FeatureControlFlags::from_bits_truncate(Self::read_raw())
+        }
+
+        /// Reads the raw IA32_FEATURE_CONTROL MSR.
+        ///
+        /// Like [`read`](Self::read), this causes a general protection fault on processors
+        /// that don't implement the MSR.
+        #[inline]
+        pub fn read_raw() -> u64 {
+            unsafe { Self::MSR.read() }
+        }
+
+        /// Writes the given flags and locks the IA32_FEATURE_CONTROL MSR.
+        ///
+        /// Preserves the value of reserved fields. Returns an error without writing the
+        /// MSR if it is already locked.
+        ///
+        /// ## Safety
+        ///
+        /// The write can't be undone until the next reset, so the caller must ensure that
+        /// `flags` contains all features that will be needed later. Enabling features that the
+        /// processor doesn't support (e.g. SMX without `CPUID.01H:ECX.SMX`) causes a general
+        /// protection fault, as does accessing the MSR on processors that don't implement it.
+        #[inline]
+        pub unsafe fn lock_with(flags: FeatureControlFlags) -> Result<(), FeatureControlLocked> {
+            let old_value = Self::read_raw();
+            if FeatureControlFlags::from_bits_truncate(old_value).locked() {
+                return Err(FeatureControlLocked);
+            }
+            let reserved = old_value & !(FeatureControlFlags::all().bits());
+            let new_value = reserved | flags.bits() | FeatureControlFlags::LOCK.bits();
+
+            let mut msr = Self::MSR;
+            unsafe { msr.write(new_value) };
+            Ok(())
+        }
+    }
+
+    impl TscDeadline {
+        /// Reads the IA32_TSC_DEADLINE MSR.
+        ///
+        /// Returns 0 if the timer is not armed.
+        #[inline]
+        pub fn read() -> u64 {
+            unsafe { Self::MSR.read() }
+        }
+
+        /// Writes the IA32_TSC_DEADLINE MSR.
+        ///
+        /// The local APIC timer must be in TSC-deadline mode, otherwise the write is ignored.
+        /// Writing 0 disarms the timer.
+        ///
+        /// ## Safety
+        ///
+        /// The caller must ensure that the timer interrupt is handled correctly.
+        #[inline]
+        pub unsafe fn write(deadline: u64) {
+            let mut msr = Self::MSR;
+            unsafe { msr.write(deadline) };
+        }
+
+        /// Arms the timer to fire `ticks` cycles of the time-stamp counter from now, as read
+        /// by [`tsc::read`](crate::instructions::tsc::read).
+        ///
+        /// ## Safety
+        ///
+        /// The caller must ensure that the timer interrupt is handled correctly.
+        #[inline]
+        pub unsafe fn arm_in(ticks: u64) {
+            let now = crate::instructions::tsc::read();
+            unsafe { Self::write(now.saturating_add(ticks)) };
+        }
     }
 }
@@ -0,0 +1,481 @@
+//! Memory type range registers (MTRRs).
+//!
+//! MTRRs assign memory types to ranges of physical memory. The memory type of an access is
+//! determined by combining the MTRR memory type with the memory type selected by the page
+//! table entry through the [PAT](crate::registers::model_specific::Pat).
+
+use crate::PhysAddr;
+use bitflags::bitflags;
+
+#[cfg(all(feature = "instructions", target_arch = "x86_64"))]
+pub use self::x86_64::*;
+
+/// Memory types used in MTRRs.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+pub enum MtrrMemoryType {
+    /// Uncacheable (UC).
+    Uncacheable,
+    /// Uses a write combining (WC) cache policy.
+    WriteCombining,
+    /// Uses a write through (WT) cache policy.
+    WriteThrough,
+    /// Uses a write protected (WP) cache policy.
+    WriteProtected,
+    /// Uses a write back (WB) cache policy.
+    WriteBack,
+    /// A reserved encoding.
+    ///
+    /// This is only returned when reading MTRRs and can't be written to them.
+    Unknown(u8),
+}
+
+impl MtrrMemoryType {
+    /// Converts from bits, returning `None` if the value is invalid.
+    pub const fn from_bits(bits: u8) -> Option<Self> {
+        match bits {
+            0x00 => Some(Self::Uncacheable),
+            0x01 => Some(Self::WriteCombining),
+            0x04 => Some(Self::WriteThrough),
+            0x05 => Some(Self::WriteProtected),
+            0x06 => Some(Self::WriteBack),
+            _ => None,
+        }
+    }
+
+    /// Gets the underlying bits.
+    pub const fn bits(self) -> u8 {
+        match self {
+            Self::Uncacheable => 0x00,
+            Self::WriteCombining => 0x01,
+            Self::WriteThrough => 0x04,
+            Self::WriteProtected => 0x05,
+            Self::WriteBack => 0x06,
+            Self::Unknown(bits) => bits,
+        }
+    }
+
+    /// Converts from bits, returning [`MtrrMemoryType::Unknown`] for reserved encodings.
+    const fn from_bits_or_unknown(bits: u8) -> Self {
+        match Self::from_bits(bits) {
+            Some(ty) => ty,
+            None => Self::Unknown(bits),
+        }
+    }
+}
+
+bitflags! {
+    /// Flags stored in IA32_MTRRCAP.
+    #[repr(transparent)]
+    #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+    pub struct MtrrCapFlags: u64 {
+        /// The fixed range MTRRs are supported.
+        const FIXED_RANGE = 1 << 8;
+        /// The write combining memory type is supported.
+        const WRITE_COMBINING = 1 << 10;
+        /// The system management range registers are supported.
+        const SMRR = 1 << 11;
+    }
+}
+
+bitflags! {
+    /// Flags stored in IA32_MTRR_DEF_TYPE.
+    #[repr(transparent)]
+    #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+    pub struct MtrrDefTypeFlags: u64 {
+        /// Enables the fixed range MTRRs. Has no effect if [`ENABLE`](Self::ENABLE) is not set.
+        const FIXED_ENABLE = 1 << 10;
+        /// Enables the MTRRs. If this is not set, all memory is uncacheable.
+        const ENABLE = 1 << 11;
+    }
+}
+
+/// A range of physical memory described by a variable range MTRR.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub struct VariableRange {
+    /// The start address of the range. It must be aligned to `size`.
+    pub base: PhysAddr,
+    /// The size of the range in bytes. It must be a power of two and at least 4 KiB.
+    pub size: u64,
+    /// The memory type of the range.
+    pub memory_type: MtrrMemoryType,
+}
+
+/// The bit of IA32_MTRR_PHYSMASKn that marks the range as valid.
+const PHYSMASK_VALID: u64 = 1 << 11;
+
+impl VariableRange {
+    /// Decodes the values of an IA32_MTRR_PHYSBASEn/IA32_MTRR_PHYSMASKn pair.
+    ///
+    /// `phys_addr_bits` is the physical address width of the processor. Returns `None` if the
+    /// range is not marked as valid.
+    pub fn from_msrs(phys_base: u64, phys_mask: u64, phys_addr_bits: u8) -> Option<Self> {
+        if phys_mask & PHYSMASK_VALID == 0 {
+            return None;
+        }
+        let addr_mask = phys_addr_mask(phys_addr_bits);
+        let mask = phys_mask & addr_mask;
+        Some(VariableRange {
+            base: PhysAddr::new_truncate(phys_base & addr_mask),
+            // The lowest 12 bits of the mask are implicitly zero.
+            size: (!mask & addr_mask) + 0x1000,
+            memory_type: MtrrMemoryType::from_bits_or_unknown(phys_base as u8),
+        })
+    }
+
+    /// Encodes the range as the values of an IA32_MTRR_PHYSBASEn/IA32_MTRR_PHYSMASKn pair.
+    ///
+    /// `phys_addr_bits` is the physical address width of the processor.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `size` is not a power of two of at least 4 KiB, if `base` is not aligned to
+    /// `size`, if the range doesn't fit into the physical address width, or if the memory type
+    /// is [`MtrrMemoryType::Unknown`].
+    pub fn to_msrs(&self, phys_addr_bits: u8) -> (u64, u64) {
+        let addr_mask = phys_addr_mask(phys_addr_bits);
+        assert!(
+            self.size.is_power_of_two() && self.size >= 0x1000,
+            "MTRR ranges must be a power of two of at least 4 KiB"
+        );
+        assert!(
+            self.base.is_aligned(self.size),
+            "MTRR ranges must be aligned to their size"
+        );
+        assert!(
+            self.size - 1 <= addr_mask && self.base.as_u64() & !addr_mask == 0,
+            "MTRR ranges must fit into the physical address width"
+        );
+        assert!(
+            !matches!(self.memory_type, MtrrMemoryType::Unknown(_)),
+            "MTRRs must not use a reserved memory type"
+        );
+        let phys_base = self.base.as_u64() | u64::from(self.memory_type.bits());
+        let phys_mask = (!(self.size - 1) & addr_mask) | PHYSMASK_VALID;
+        (phys_base, phys_mask)
+    }
+}
+
+/// Returns the mask of the address bits in IA32_MTRR_PHYSBASEn and IA32_MTRR_PHYSMASKn.
+fn phys_addr_mask(phys_addr_bits: u8) -> u64 {
+    let bits = u32::from(phys_addr_bits).min(52);
+    ((1 << bits) - 1) & !0xfff
+}
+
+#[cfg(all(feature = "instructions", target_arch = "x86_64"))]
+mod x86_64 {
+    use super::*;
+    use crate::instructions::{cache::wbinvd, interrupts, tlb};
+    use crate::registers::control::{Cr0, Cr0Flags};
+    use crate::registers::model_specific::{msr_index, Msr};
+
+    /// The fixed range MTRRs in order of their addresses. Each register describes eight
+    /// ranges of 64 KiB, 16 KiB, or 4 KiB.
+    const FIXED_RANGE_MSRS: [u32; 11] = [
+        msr_index::IA32_MTRR_FIX64K_00000,
+        msr_index::IA32_MTRR_FIX16K_80000,
+        msr_index::IA32_MTRR_FIX16K_A0000,
+        msr_index::IA32_MTRR_FIX4K_C0000,
+        msr_index::IA32_MTRR_FIX4K_C8000,
+        msr_index::IA32_MTRR_FIX4K_D0000,
+        msr_index::IA32_MTRR_FIX4K_D8000,
+        msr_index::IA32_MTRR_FIX4K_E0000,
+        msr_index::IA32_MTRR_FIX4K_E8000,
+        msr_index::IA32_MTRR_FIX4K_F0000,
+        msr_index::IA32_MTRR_FIX4K_F8000,
+    ];
+
+    /// Access to the MTRRs of the current processor.
+    ///
+    /// The MTRRs must be configured identically on all processors.
+    #[derive(Debug, Clone, Copy)]
+    pub struct Mtrr {
+        variable_count: u8,
+        flags: MtrrCapFlags,
+        phys_addr_bits: u8,
+    }
+
+    impl Mtrr {
+        /// IA32_MTRRCAP
+        pub const MTRRCAP: Msr = Msr::new(msr_index::IA32_MTRRCAP);
+        /// IA32_MTRR_DEF_TYPE
+        pub const DEF_TYPE: Msr = Msr::new(msr_index::IA32_MTRR_DEF_TYPE);
+
+        /// Creates `Some(Mtrr)` if MTRRs are supported, `None` otherwise.
+        ///
+        /// This reads the number of variable range MTRRs from IA32_MTRRCAP and the physical
+        /// address width from CPUID.
+        #[inline]
+        pub fn new() -> Option<Self> {
+            // MTRR support indicated by CPUID page 01h, edx bit 12
+            // `__cpuid` is safe on newer compilers but unsafe on our MSRV.
+            #[allow(unused_unsafe)]
+            let cpuid = unsafe { core::arch::x86_64::__cpuid(0x1) };
+            if cpuid.edx & (1 << 12) == 0 {
+                return None;
+            }
+            // The physical address width is indicated by CPUID page 8000_0008h, eax bits 0-7.
+            // If that page is not supported, it is 36 bits.
+            #[allow(unused_unsafe)]
+            let max_extended = unsafe { core::arch::x86_64::__cpuid(0x8000_0000) }.eax;
+            let phys_addr_bits = if max_extended >= 0x8000_0008 {
+                #[allow(unused_unsafe)]
+                let cpuid = unsafe { core::arch::x86_64::__cpuid(0x8000_0008) };
+                cpuid.eax as u8
+            } else {
+                36
+            };
+            let cap = unsafe { Self::MTRRCAP.read() };
+            Some(Mtrr {
+                variable_count: cap as u8,
+                flags: MtrrCapFlags::from_bits_truncate(cap),
+                phys_addr_bits,
+            })
+        }
+
+        /// Returns the number of variable range MTRRs.
+        #[inline]
+        pub fn variable_count(&self) -> u8 {
+            self.variable_count
+        }
+
+        /// Returns the flags stored in IA32_MTRRCAP.
+        #[inline]
+        pub fn capabilities(&self) -> MtrrCapFlags {
+            self.flags
+        }
+
+        /// Returns the physical address width of the processor.
+        #[inline]
+        pub fn phys_addr_bits(&self) -> u8 {
+            self.phys_addr_bits
+        }
+
+        /// Reads the default memory type and the flags of IA32_MTRR_DEF_TYPE.
+        #[inline]
+        pub fn read_default(&self) -> (MtrrMemoryType, MtrrDefTypeFlags) {
+            let value = unsafe { Self::DEF_TYPE.read() };
+            (
+                MtrrMemoryType::from_bits_or_unknown(value as u8),
+                MtrrDefTypeFlags::from_bits_truncate(value),
+            )
+        }
+
+        /// Writes the default memory type and the flags of IA32_MTRR_DEF_TYPE.
+        ///
+        /// ## Panics
+        ///
+        /// Panics if `memory_type` is [`MtrrMemoryType::Unknown`].
+        ///
+        /// ## Safety
+        ///
+        /// Changing memory types can break memory coherency. This should only be called within
+        /// [`Mtrr::update`].
+        #[inline]
+        pub unsafe fn write_default(&self, memory_type: MtrrMemoryType, flags: MtrrDefTypeFlags) {
+            assert!(
+                !matches!(memory_type, MtrrMemoryType::Unknown(_)),
+                "MTRRs must not use a reserved memory type"
+            );
+            let mut msr = Self::DEF_TYPE;
+            unsafe { msr.write(u64::from(memory_type.bits()) | flags.bits()) };
+        }
+
+        /// Reads the variable range MTRR with the given index.
+        ///
+        /// Returns `None` if the range is disabled.
+        ///
+        /// ## Panics
+        ///
+        /// Panics if `index` is not smaller than [`variable_count`](Self::variable_count).
+        #[inline]
+        pub fn read_variable(&self, index: u8) -> Option<VariableRange> {
+            let (base, mask) = self.variable_msrs(index);
+            let (phys_base, phys_mask) = unsafe { (base.read(), mask.read()) };
+            VariableRange::from_msrs(phys_base, phys_mask, self.phys_addr_bits)
+        }
+
+        /// Writes the variable range MTRR with the given index. `None` disables the range.
+        ///
+        /// ## Panics
+        ///
+        /// Panics if `index` is not smaller than [`variable_count`](Self::variable_count) or if
+        /// the range can't be encoded (see [`VariableRange::to_msrs`]).
+        ///
+        /// ## Safety
+        ///
+        /// Changing memory types can break memory coherency. This should only be called within
+        /// [`Mtrr::update`].
+        #[inline]
+        pub unsafe fn write_variable(&self, index: u8, range: Option<VariableRange>) {
+            let (mut base, mut mask) = self.variable_msrs(index);
+            let (phys_base, phys_mask) = match range {
+                Some(range) => range.to_msrs(self.phys_addr_bits),
+                None => (0, 0),
+            };
+            unsafe {
+                // Disable the range first, so that it's never valid with a partial update.
+                mask.write(0);
+                base.write(phys_base);
+                mask.write(phys_mask);
+            }
+        }
+
+        /// Returns the IA32_MTRR_PHYSBASEn and IA32_MTRR_PHYSMASKn registers for `index`.
+        fn variable_msrs(&self, index: u8) -> (Msr, Msr) {
+            assert!(
+                index < self.variable_count,
+                "variable range MTRR index out of range"
+            );
+            (
+                Msr::new(msr_index::mtrr_physbase(index)),
+                Msr::new(msr_index::mtrr_physmask(index)),
+            )
+        }
+
+        /// Reads the memory types of the fixed range MTRRs.
+        ///
+        /// The returned array contains the memory types of the first MiB of physical memory in
+        /// order of their addresses: 8 ranges of 64 KiB, 16 ranges of 16 KiB and 64 ranges of
+        /// 4 KiB.
+        ///
+        /// The fixed range MTRRs must be supported (see [`MtrrCapFlags::FIXED_RANGE`]),
+        /// otherwise a general protection exception will occur.
+        #[inline]
+        pub fn read_fixed(&self) -> [MtrrMemoryType; 88] {
+            let mut types = [MtrrMemoryType::Uncacheable; 88];
+            for (chunk, &msr) in types.chunks_exact_mut(8).zip(FIXED_RANGE_MSRS.iter()) {
+                let bytes = unsafe { Msr::new(msr).read() }.to_le_bytes();
+                for (ty, bits) in chunk.iter_mut().zip(bytes) {
+                    *ty = MtrrMemoryType::from_bits_or_unknown(bits);
+                }
+            }
+            types
+        }
+
+        /// Writes the memory types of the fixed range MTRRs, in the same order as
+        /// [`read_fixed`](Self::read_fixed).
+        ///
+        /// The fixed range MTRRs must be supported (see [`MtrrCapFlags::FIXED_RANGE`]),
+        /// otherwise a general protection exception will occur.
+        ///
+        /// ## Panics
+        ///
+        /// Panics if any entry is [`MtrrMemoryType::Unknown`].
+        ///
+        /// ## Safety
+        ///
+        /// Changing memory types can break memory coherency. This should only be called within
+        /// [`Mtrr::update`].
+        #[inline]
+        pub unsafe fn write_fixed(&self, types: &[MtrrMemoryType; 88]) {
+            assert!(
+                !types
+                    .iter()
+                    .any(|ty| matches!(ty, MtrrMemoryType::Unknown(_))),
+                "MTRRs must not use a reserved memory type"
+            );
+            for (chunk, &msr) in types.chunks_exact(8).zip(FIXED_RANGE_MSRS.iter()) {
+                let mut bytes = [0; 8];
+                for (bits, ty) in bytes.iter_mut().zip(chunk) {
+                    *bits = ty.bits();
+                }
+                let mut msr = Msr::new(msr);
+                unsafe { msr.write(u64::from_le_bytes(bytes)) };
+            }
+        }
+
+        /// Runs `f` while the caches and the MTRRs are disabled, following the sequence
+        /// described in the Intel SDM (volume 3, section 12.11.7.2).
+        ///
+        /// Interrupts are disabled, CR0.CD is set, the caches and the TLB are flushed, and the
+        /// MTRRs are disabled before calling `f`. Afterwards, the caches and the TLB are
+        /// flushed again and the previous state is restored. IA32_MTRR_DEF_TYPE is restored to
+        /// its previous value, unless `f` changed it using [`write_default`](Self::write_default).
+        ///
+        /// This is slow and should only be done during initialization. On multiprocessor
+        /// systems, all processors must perform the same update at the same time.
+        ///
+        /// ## Safety
+        ///
+        /// The memory types configured by `f` must be compatible with how the memory is used,
+        /// e.g. memory-mapped device registers must be uncacheable.
+        pub unsafe fn update<F, R>(&self, f: F) -> R
+        where
+            F: FnOnce(&Self) -> R,
+        {
+            interrupts::without_interrupts(|| {
+                let cr0 = Cr0::read();
+                unsafe {
+                    Cr0::write((cr0 | Cr0Flags::CACHE_DISABLE) - Cr0Flags::NOT_WRITE_THROUGH);
+                }
+                wbinvd();
+                tlb::flush_all_global();
+
+                let mut def_type = Self::DEF_TYPE;
+                let saved_def_type = unsafe { def_type.read() };
+                let disabled_def_type = saved_def_type & !MtrrDefTypeFlags::ENABLE.bits();
+                unsafe { def_type.write(disabled_def_type) };
+
+                let ret = f(self);
+
+                // Restore the previous value, unless `f` wrote a new one.
+                let new_def_type = unsafe { def_type.read() };
+                wbinvd();
+                tlb::flush_all_global();
+                unsafe {
+                    if new_def_type == disabled_def_type {
+                        def_type.write(saved_def_type);
+                    }
+                    Cr0::write(cr0);
+                }
+                ret
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memory_type_bits() {
+        for bits in 0..=0xff {
+            if let Some(ty) = MtrrMemoryType::from_bits(bits) {
+                assert_eq!(ty.bits(), bits);
+            }
+        }
+        assert_eq!(MtrrMemoryType::from_bits(2), None);
+        assert_eq!(MtrrMemoryType::from_bits(7), None);
+    }
+
+    #[test]
+    fn variable_range_encoding() {
+        let range = VariableRange {
+            base: PhysAddr::new(0x8000_0000),
+            size: 0x1000_0000,
+            memory_type: MtrrMemoryType::WriteCombining,
+        };
+        let (base, mask) = range.to_msrs(36);
+        assert_eq!(base, 0x8000_0001);
+        assert_eq!(mask, 0xf_f000_0800);
+        assert_eq!(VariableRange::from_msrs(base, mask, 36), Some(range));
+
+        let (base, mask) = range.to_msrs(46);
+        assert_eq!(mask, 0x3fff_f000_0800);
+        assert_eq!(VariableRange::from_msrs(base, mask, 46), Some(range));
+
+        assert_eq!(VariableRange::from_msrs(base, mask & !(1 << 11), 46), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn variable_range_unaligned() {
+        let range = VariableRange {
+            base: PhysAddr::new(0x1000),
+            size: 0x2000,
+            memory_type: MtrrMemoryType::WriteBack,
+        };
+        range.to_msrs(36);
+    }
+}
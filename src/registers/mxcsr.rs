@@ -60,6 +60,43 @@ impl Default for MxCsr {
     }
 }
 
+/// The rounding mode used by SSE instructions, stored in the rounding control field of MXCSR.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum RoundingMode {
+    /// Round to nearest (even)
+    Nearest = 0,
+    /// Round toward negative infinity
+    Down = 1,
+    /// Round toward positive infinity
+    Up = 2,
+    /// Round toward zero (truncate)
+    TowardZero = 3,
+}
+
+impl MxCsr {
+    /// All exception mask bits.
+    pub const ALL_EXCEPTION_MASKS: Self = Self::from_bits_truncate(0x3f << 7);
+
+    /// Returns the rounding mode selected by the rounding control field.
+    #[inline]
+    pub const fn rounding_mode(self) -> RoundingMode {
+        match (self.bits() >> 13) & 0b11 {
+            0 => RoundingMode::Nearest,
+            1 => RoundingMode::Down,
+            2 => RoundingMode::Up,
+            _ => RoundingMode::TowardZero,
+        }
+    }
+
+    /// Sets the rounding control field to the given rounding mode.
+    #[inline]
+    pub fn set_rounding_mode(&mut self, mode: RoundingMode) {
+        self.remove(Self::ROUNDING_CONTROL_ZERO);
+        self.insert(Self::from_bits_truncate((mode as u32) << 13));
+    }
+}
+
 #[cfg(all(feature = "instructions", target_arch = "x86_64"))]
 mod x86_64 {
     use super::*;
@@ -118,3 +155,33 @@ mod x86_64 {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_default() {
+        let mxcsr = MxCsr::from_bits_truncate(0x1F80);
+        assert_eq!(mxcsr, MxCsr::default());
+        assert!(mxcsr.contains(MxCsr::ALL_EXCEPTION_MASKS));
+        assert_eq!(mxcsr.rounding_mode(), RoundingMode::Nearest);
+        assert!(!mxcsr.intersects(MxCsr::FLUSH_TO_ZERO | MxCsr::DENORMALS_ARE_ZEROS));
+    }
+
+    #[test]
+    fn rounding_mode() {
+        let mut mxcsr = MxCsr::default();
+        for mode in [
+            RoundingMode::Down,
+            RoundingMode::Up,
+            RoundingMode::TowardZero,
+            RoundingMode::Nearest,
+        ] {
+            mxcsr.set_rounding_mode(mode);
+            assert_eq!(mxcsr.rounding_mode(), mode);
+            assert!(mxcsr.contains(MxCsr::ALL_EXCEPTION_MASKS));
+        }
+        assert_eq!(mxcsr, MxCsr::default());
+    }
+}
@@ -3,6 +3,7 @@
 #[cfg(all(feature = "instructions", target_arch = "x86_64"))]
 pub use self::x86_64::*;
 
+use crate::PrivilegeLevel;
 use bitflags::bitflags;
 
 bitflags! {
@@ -64,6 +65,70 @@ bitflags! {
     }
 }
 
+impl RFlags {
+    /// Iterates over the named fields that are set, yielding the name and value of each field.
+    ///
+    /// The value of a single-bit flag is always 1. The two bits of the I/O Privilege Level are
+    /// yielded as a single `"IOPL"` field (after all other flags) with the 2-bit privilege
+    /// level as its value, if it is not zero.
+    ///
+    /// This method isn't called `iter_names` because `bitflags` already generates a method
+    /// with that name, which yields [`IOPL_HIGH`](Self::IOPL_HIGH) and
+    /// [`IOPL_LOW`](Self::IOPL_LOW) as separate flags.
+    pub fn iter_fields(self) -> impl Iterator<Item = (&'static str, u64)> {
+        let iopl = self.iopl() as u64;
+        self.difference(Self::IOPL_HIGH | Self::IOPL_LOW)
+            .iter_names()
+            .map(|(name, _)| (name, 1))
+            .chain((iopl != 0).then(|| ("IOPL", iopl)))
+    }
+
+    /// Returns the I/O Privilege Level stored in bits 12 and 13.
+    #[inline]
+    pub const fn iopl(self) -> PrivilegeLevel {
+        PrivilegeLevel::from_u16((self.bits() >> 12) as u16 & 0b11)
+    }
+
+    /// Sets the I/O Privilege Level stored in bits 12 and 13, leaving all other flags untouched.
+    #[inline]
+    pub fn set_iopl(&mut self, level: PrivilegeLevel) {
+        let iopl_bits = Self::IOPL_HIGH | Self::IOPL_LOW;
+        *self = self.difference(iopl_bits) | Self::from_bits_truncate((level as u64) << 12);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn iter_fields() {
+        let flags = RFlags::INTERRUPT_FLAG | RFlags::IOPL_HIGH | RFlags::CARRY_FLAG;
+        let mut fields = flags.iter_fields();
+        assert_eq!(fields.next(), Some(("INTERRUPT_FLAG", 1)));
+        assert_eq!(fields.next(), Some(("CARRY_FLAG", 1)));
+        assert_eq!(fields.next(), Some(("IOPL", 2)));
+        assert_eq!(fields.next(), None);
+
+        let flags = RFlags::IOPL_HIGH | RFlags::IOPL_LOW;
+        assert_eq!(flags.iter_fields().collect::<Vec<_>>(), [("IOPL", 3)]);
+        assert_eq!(RFlags::empty().iter_fields().count(), 0);
+    }
+
+    #[test]
+    fn iopl() {
+        let mut flags = RFlags::INTERRUPT_FLAG | RFlags::IOPL_LOW;
+        assert_eq!(flags.iopl(), PrivilegeLevel::Ring1);
+
+        flags.set_iopl(PrivilegeLevel::Ring3);
+        assert_eq!(flags.bits(), 0x3000 | RFlags::INTERRUPT_FLAG.bits());
+        assert_eq!(flags.iopl(), PrivilegeLevel::Ring3);
+
+        flags.set_iopl(PrivilegeLevel::Ring0);
+        assert_eq!(flags, RFlags::INTERRUPT_FLAG);
+    }
+}
+
 #[cfg(all(feature = "instructions", target_arch = "x86_64"))]
 mod x86_64 {
     use super::*;
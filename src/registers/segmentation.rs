@@ -100,6 +100,26 @@ impl SegmentSelector {
     pub fn set_rpl(&mut self, rpl: PrivilegeLevel) {
         self.0.set_bits(0..2, rpl as u16);
     }
+
+    /// Returns the descriptor table that this selector points into, as specified by the table
+    /// indicator bit.
+    #[inline]
+    pub fn table(self) -> SegmentTable {
+        if self.0.get_bit(2) {
+            SegmentTable::Ldt
+        } else {
+            SegmentTable::Gdt
+        }
+    }
+}
+
+/// The descriptor table referenced by a [`SegmentSelector`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SegmentTable {
+    /// The global descriptor table.
+    Gdt,
+    /// The local descriptor table.
+    Ldt,
 }
 
 impl fmt::Debug for SegmentSelector {
@@ -107,6 +127,7 @@ impl fmt::Debug for SegmentSelector {
         let mut s = f.debug_struct("SegmentSelector");
         s.field("index", &self.index());
         s.field("rpl", &self.rpl());
+        s.field("table", &self.table());
         s.finish()
     }
 }
@@ -168,3 +189,21 @@ pub struct FS;
 /// base often points to a per-cpu kernel data structure.
 #[derive(Debug)]
 pub struct GS;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segment_selector_fields() {
+        let mut selector = SegmentSelector(0x2b);
+        assert_eq!(selector.index(), 5);
+        assert_eq!(selector.rpl(), PrivilegeLevel::Ring3);
+        assert_eq!(selector.table(), SegmentTable::Gdt);
+
+        selector.set_rpl(PrivilegeLevel::Ring0);
+        assert_eq!(selector, SegmentSelector::new(5, PrivilegeLevel::Ring0));
+        assert_eq!(SegmentSelector(0x2f).table(), SegmentTable::Ldt);
+        assert_eq!(SegmentSelector(0x2f).index(), 5);
+    }
+}
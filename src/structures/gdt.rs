@@ -1,8 +1,8 @@
 //! Types for the Global Descriptor Table and segment selectors.
 
-pub use crate::registers::segmentation::SegmentSelector;
-use crate::structures::tss::TaskStateSegment;
-use crate::PrivilegeLevel;
+pub use crate::registers::segmentation::{SegmentSelector, SegmentTable};
+use crate::structures::tss::{ReadyTssPointer, TaskStateSegment};
+use crate::{PrivilegeLevel, VirtAddr};
 use bit_field::BitField;
 use bitflags::bitflags;
 use core::fmt;
@@ -178,6 +178,42 @@ impl<const MAX: usize> GlobalDescriptorTable<MAX> {
         Self { table, len }
     }
 
+    /// Copies the GDT described by `pointer`, e.g. a GDT set up by the bootloader that was
+    /// read with [`sgdt`](crate::instructions::tables::sgdt).
+    ///
+    /// The entries are copied, so subsequent calls to [`append`](Self::append) add entries
+    /// after the existing ones and all existing segment selectors stay valid. The new GDT has
+    /// the same [`limit`](Self::limit) as the original one, but it lives at a different
+    /// address, so loading it with [`load`](Self::load) changes the base of the GDTR. Use
+    /// [`BorrowedGlobalDescriptorTable`] to keep using the original table in place.
+    ///
+    /// Panics under the same conditions as [`from_raw_entries`](Self::from_raw_entries).
+    ///
+    /// ## Safety
+    ///
+    /// `pointer` must describe a valid GDT, i.e. its base must point to `limit + 1` readable
+    /// bytes. The base doesn't need to be aligned.
+    #[cfg_attr(
+        not(all(feature = "instructions", target_arch = "x86_64")),
+        allow(rustdoc::broken_intra_doc_links)
+    )]
+    #[inline]
+    pub unsafe fn from_pointer(pointer: &super::DescriptorTablePointer) -> Self {
+        let len = (usize::from(pointer.limit) + 1) / core::mem::size_of::<u64>();
+        assert!(
+            len <= MAX,
+            "cannot initialize GDT with slice exceeding the maximum length"
+        );
+        // The GDTR base doesn't have to be aligned, so the entries are read unaligned.
+        let base = pointer.base.as_ptr::<u8>().cast::<u64>();
+        let mut entries = [0; MAX];
+        for (idx, entry) in entries[..len].iter_mut().enumerate() {
+            // SAFETY: The caller guarantees that the table is valid for reads.
+            *entry = unsafe { base.add(idx).read_unaligned() };
+        }
+        Self::from_raw_entries(&entries[..len])
+    }
+
     /// Get a reference to the internal [`Entry`] table.
     ///
     /// The resulting slice may contain system descriptors, which span two [`Entry`]s.
@@ -195,25 +231,63 @@ impl<const MAX: usize> GlobalDescriptorTable<MAX> {
     #[inline]
     #[rustversion::attr(since(1.83), const)]
     pub fn append(&mut self, entry: Descriptor) -> SegmentSelector {
-        let index = match entry {
-            Descriptor::UserSegment(value) => {
-                if self.len > self.table.len().saturating_sub(1) {
-                    panic!("GDT full")
-                }
-                self.push(value)
-            }
-            Descriptor::SystemSegment(value_low, value_high) => {
-                if self.len > self.table.len().saturating_sub(2) {
-                    panic!("GDT requires two free spaces to hold a SystemSegment")
-                }
-                let index = self.push(value_low);
-                self.push(value_high);
-                index
-            }
-        };
+        let (values, count) = descriptor_entries(entry, self.len, self.table.len());
+        let index = self.len;
+        let mut i = 0;
+        while i < count {
+            self.push(values[i]);
+            i += 1;
+        }
         SegmentSelector::new(index as u16, entry.dpl())
     }
 
+    /// Appends the given segment descriptor to the GDT by value, returning the GDT and the
+    /// segment selector.
+    ///
+    /// Unlike [`append`](Self::append), this function can be used in const contexts on all
+    /// supported compiler versions, so a complete GDT can be built at compile time:
+    ///
+    /// ```
+    /// use x86_64::structures::gdt::{Descriptor, GlobalDescriptorTable, SegmentSelector};
+    ///
+    /// const fn build() -> (GlobalDescriptorTable, SegmentSelector, SegmentSelector) {
+    ///     let gdt = GlobalDescriptorTable::new();
+    ///     let (gdt, code) = gdt.const_append(Descriptor::kernel_code_segment());
+    ///     let (gdt, data) = gdt.const_append(Descriptor::kernel_data_segment());
+    ///     (gdt, code, data)
+    /// }
+    ///
+    /// static GDT: (GlobalDescriptorTable, SegmentSelector, SegmentSelector) = build();
+    /// ```
+    ///
+    /// Like `append`, this appends two entries for a system descriptor. Panics if the GDT
+    /// doesn't have enough free entries.
+    #[inline]
+    pub const fn const_append(mut self, entry: Descriptor) -> (Self, SegmentSelector) {
+        let (values, count) = descriptor_entries(entry, self.len, self.table.len());
+        let index = self.len;
+        let mut i = 0;
+        while i < count {
+            self.table[index + i] = Entry::new(values[i]);
+            i += 1;
+        }
+        self.len += count;
+        (self, SegmentSelector::new(index as u16, entry.dpl()))
+    }
+
+    /// Appends a TSS descriptor for the given TSS to the GDT, returning the segment selector.
+    ///
+    /// The descriptor's limit is set to `size_of::<TaskStateSegment<N>>() - 1`,
+    /// so the whole I/O permission bitmap (including its terminating byte) is
+    /// covered by the segment.
+    ///
+    /// Panics if the GDT doesn't have two free entries.
+    #[inline]
+    pub fn append_tss<const N: usize>(&mut self, tss: ReadyTssPointer<N>) -> SegmentSelector {
+        // SAFETY: A `ReadyTssPointer` always points to a TSS with a static lifetime.
+        self.append(unsafe { Descriptor::tss_segment_unchecked(tss.as_ptr()) })
+    }
+
     /// Loads the GDT in the CPU using the `lgdt` instruction. This does **not** alter any of the
     /// segment registers; you **must** (re)load them yourself using [the appropriate
     /// functions](crate::instructions::segmentation):
@@ -273,6 +347,148 @@ impl<const MAX: usize> GlobalDescriptorTable<MAX> {
     }
 }
 
+/// A GDT that is stored in an existing table, e.g. the GDT set up by the bootloader.
+///
+/// Unlike [`GlobalDescriptorTable::from_pointer`], which copies the entries into a new
+/// table, this type reinterprets the existing table in place. Loading it therefore results in
+/// the same base address as the original table, and, until entries are appended, the same
+/// limit. [`append`](Self::append) adds entries after the ones that are already in use,
+/// without touching existing entries, and the table must be [loaded](Self::load) again
+/// before the new entries can be used.
+#[cfg_attr(
+    all(feature = "instructions", target_arch = "x86_64"),
+    doc = "
+# Example
+
+```no_run
+use x86_64::instructions::tables::sgdt;
+use x86_64::structures::gdt::{BorrowedGlobalDescriptorTable, Descriptor};
+
+let pointer = sgdt();
+let len = (usize::from(pointer.limit) + 1) / 8;
+// SAFETY: the bootloader reserved 16 entries for the GDT, which are never used otherwise.
+let table = unsafe { core::slice::from_raw_parts_mut(pointer.base.as_mut_ptr(), 16) };
+let mut gdt = BorrowedGlobalDescriptorTable::from_raw_slice(table, len);
+gdt.append(Descriptor::user_code_segment());
+gdt.load();
+```"
+)]
+#[cfg_attr(
+    not(all(feature = "instructions", target_arch = "x86_64")),
+    allow(rustdoc::broken_intra_doc_links)
+)]
+#[derive(Debug)]
+pub struct BorrowedGlobalDescriptorTable {
+    table: &'static mut [Entry],
+    len: usize,
+}
+
+impl BorrowedGlobalDescriptorTable {
+    /// Reinterprets `table` as a GDT, of which the first `len` entries are in use.
+    ///
+    /// The whole slice can be used by the GDT, so subsequent calls to
+    /// [`append`](Self::append) store entries in `table[len..]`. Like
+    /// [`GlobalDescriptorTable::from_raw_entries`], this is safe even if the table contains
+    /// invalid entries.
+    ///
+    /// Panics if:
+    /// * `len` is zero or larger than the slice
+    /// * the slice has more than 2^13 entries
+    /// * the first entry is not zero
+    #[inline]
+    pub fn from_raw_slice(table: &'static mut [u64], len: usize) -> Self {
+        assert!(len > 0, "cannot initialize GDT with empty slice");
+        assert!(len <= table.len(), "GDT length exceeds the slice");
+        assert!(
+            table.len() <= (1 << 13),
+            "A GDT can only have at most 2^13 entries"
+        );
+        assert!(table[0] == 0, "first GDT entry must be zero");
+
+        // SAFETY: `Entry` is a transparent wrapper around a `u64` or an `AtomicU64`, which has
+        // the same in-memory representation as `u64`.
+        let table = unsafe { &mut *(table as *mut [u64] as *mut [Entry]) };
+        Self { table, len }
+    }
+
+    /// Get a reference to the used part of the [`Entry`] table.
+    ///
+    /// The resulting slice may contain system descriptors, which span two [`Entry`]s.
+    #[inline]
+    pub fn entries(&self) -> &[Entry] {
+        &self.table[..self.len]
+    }
+
+    /// Appends the given segment descriptor to the GDT, returning the segment selector.
+    ///
+    /// See [`GlobalDescriptorTable::append`]. Panics if the table doesn't have enough free
+    /// entries.
+    #[inline]
+    pub fn append(&mut self, entry: Descriptor) -> SegmentSelector {
+        let (values, count) = descriptor_entries(entry, self.len, self.table.len());
+        let index = self.len;
+        for (slot, value) in self.table[index..index + count].iter_mut().zip(values) {
+            *slot = Entry::new(value);
+        }
+        self.len += count;
+        SegmentSelector::new(index as u16, entry.dpl())
+    }
+
+    /// Returns the value of the limit for a gdt pointer. It is one less than the number of
+    /// bytes of the used part of the table.
+    #[inline]
+    pub fn limit(&self) -> u16 {
+        // 0 < self.len <= 2^13, so the limit calculation will not underflow or overflow.
+        (self.len * core::mem::size_of::<u64>() - 1) as u16
+    }
+
+    /// Loads the GDT in the CPU using the `lgdt` instruction.
+    ///
+    /// The base is the address of the original table. Like [`GlobalDescriptorTable::load`],
+    /// this does **not** alter any of the segment registers.
+    #[cfg(all(feature = "instructions", target_arch = "x86_64"))]
+    #[inline]
+    pub fn load(&self) {
+        use crate::instructions::tables::lgdt;
+        // SAFETY: The table has a static lifetime and entries that are in use are never
+        // modified through this type.
+        unsafe { lgdt(&self.pointer()) };
+    }
+
+    /// Creates the descriptor pointer for this table.
+    #[cfg(all(feature = "instructions", target_arch = "x86_64"))]
+    #[inline]
+    fn pointer(&self) -> super::DescriptorTablePointer {
+        super::DescriptorTablePointer {
+            base: VirtAddr::from_ptr(self.table.as_ptr()),
+            limit: self.limit(),
+        }
+    }
+}
+
+/// Returns the raw entries of `entry` and the number of entries it uses.
+///
+/// Panics if the descriptor doesn't fit into a table with `capacity` entries, of which `len`
+/// are already used. All append functions use this, so they handle system descriptors the
+/// same way.
+#[inline]
+const fn descriptor_entries(entry: Descriptor, len: usize, capacity: usize) -> ([u64; 2], usize) {
+    match entry {
+        Descriptor::UserSegment(value) => {
+            if len > capacity.saturating_sub(1) {
+                panic!("GDT full")
+            }
+            ([value, 0], 1)
+        }
+        Descriptor::SystemSegment(value_low, value_high) => {
+            if len > capacity.saturating_sub(2) {
+                panic!("GDT requires two free spaces to hold a SystemSegment")
+            }
+            ([value_low, value_high], 2)
+        }
+    }
+}
+
 /// A 64-bit mode segment descriptor.
 ///
 /// Segmentation is no longer supported in 64-bit mode, so most of the descriptor
@@ -389,6 +605,27 @@ impl Descriptor {
         PrivilegeLevel::from_u16(dpl as u16)
     }
 
+    /// Returns the base address and the limit of a system segment descriptor (e.g. a TSS or
+    /// LDT descriptor).
+    ///
+    /// The limit is the raw 20-bit value stored in the descriptor, i.e. the size of the segment
+    /// in bytes minus one (the granularity bit is not taken into account). Returns `None` for a
+    /// [`UserSegment`](Descriptor::UserSegment), since its base and limit are ignored in 64-bit
+    /// mode.
+    #[inline]
+    pub const fn try_base_limit(self) -> Option<(u64, u32)> {
+        match self {
+            Descriptor::UserSegment(_) => None,
+            Descriptor::SystemSegment(low, high) => {
+                let base = ((low >> 16) & 0xff_ffff)
+                    | (((low >> 56) & 0xff) << 24)
+                    | ((high & 0xffff_ffff) << 32);
+                let limit = (low & 0xffff) | (((low >> 48) & 0xf) << 16);
+                Some((base, limit as u32))
+            }
+        }
+    }
+
     /// Creates a segment descriptor for a 64-bit kernel code segment. Suitable
     /// for use with `syscall` or 64-bit `sysenter`.
     #[inline]
@@ -426,7 +663,7 @@ impl Descriptor {
     /// Then, each of these descriptors should be placed in a GDT (which can
     /// either be global or per-CPU).
     #[inline]
-    pub fn tss_segment(tss: &'static TaskStateSegment) -> Descriptor {
+    pub fn tss_segment<const N: usize>(tss: &'static TaskStateSegment<N>) -> Descriptor {
         // SAFETY: The pointer is derived from a &'static reference, which ensures its validity.
         unsafe { Self::tss_segment_unchecked(tss) }
     }
@@ -438,23 +675,50 @@ impl Descriptor {
     /// The caller must ensure that the passed pointer is valid for as long as the descriptor is
     /// being used.
     #[inline]
-    pub unsafe fn tss_segment_unchecked(tss: *const TaskStateSegment) -> Descriptor {
-        use self::DescriptorFlags as Flags;
-        use core::mem::size_of;
+    pub unsafe fn tss_segment_unchecked<const N: usize>(
+        tss: *const TaskStateSegment<N>,
+    ) -> Descriptor {
+        // limit (the size minus one, since the bound is inclusive)
+        let limit = TaskStateSegment::<N>::limit().into();
+        // type (0b1001 = available 64-bit tss)
+        Self::system_segment(tss as u64, limit, 0b1001)
+    }
+
+    /// Creates a LDT system descriptor for the LDT at the given address.
+    ///
+    /// `limit` is the size of the LDT in bytes minus one and must fit into 20 bits. The entries
+    /// of an LDT use the same format as the entries of a GDT, so a [`GlobalDescriptorTable`]
+    /// can be used as LDT too: its base is the address of [`entries`](GlobalDescriptorTable::entries)
+    /// and its limit is [`GlobalDescriptorTable::limit`].
+    ///
+    /// The descriptor must be appended to the GDT, the resulting selector can then be loaded
+    /// with [`load_ldt`](crate::instructions::tables::load_ldt).
+    #[cfg_attr(
+        not(all(feature = "instructions", target_arch = "x86_64")),
+        allow(rustdoc::broken_intra_doc_links)
+    )]
+    #[inline]
+    pub fn ldt(base: VirtAddr, limit: u32) -> Descriptor {
+        assert!(limit < (1 << 20), "LDT limit must fit into 20 bits");
+        // type (0b0010 = ldt)
+        Self::system_segment(base.as_u64(), limit.into(), 0b0010)
+    }
 
-        let ptr = tss as u64;
+    fn system_segment(base: u64, limit: u64, ty: u64) -> Descriptor {
+        use self::DescriptorFlags as Flags;
 
         let mut low = Flags::PRESENT.bits();
         // base
-        low.set_bits(16..40, ptr.get_bits(0..24));
-        low.set_bits(56..64, ptr.get_bits(24..32));
-        // limit (the `-1` in needed since the bound is inclusive)
-        low.set_bits(0..16, (size_of::<TaskStateSegment>() - 1) as u64);
-        // type (0b1001 = available 64-bit tss)
-        low.set_bits(40..44, 0b1001);
+        low.set_bits(16..40, base.get_bits(0..24));
+        low.set_bits(56..64, base.get_bits(24..32));
+        // limit
+        low.set_bits(0..16, limit.get_bits(0..16));
+        low.set_bits(48..52, limit.get_bits(16..20));
+        // type
+        low.set_bits(40..44, ty);
 
         let mut high = 0;
-        high.set_bits(0..32, ptr.get_bits(32..64));
+        high.set_bits(0..32, base.get_bits(32..64));
 
         Descriptor::SystemSegment(low, high)
     }
@@ -527,6 +791,165 @@ mod tests {
         gdt.append(Descriptor::tss_segment(&TSS));
     }
 
+    #[test]
+    pub fn append_tss() {
+        let tss = Box::leak(Box::new(TaskStateSegment::<16>::new_with_iomap()));
+        let (tss, _iomap) = tss.ready_to_activate();
+        let ptr = tss.as_ptr() as u64;
+
+        let mut gdt = GlobalDescriptorTable::new();
+        let selector = gdt.append_tss(tss);
+        assert_eq!(selector.index(), 1);
+        assert_eq!(gdt.len, 3);
+
+        let low = gdt.entries()[1].raw();
+        let high = gdt.entries()[2].raw();
+        assert_eq!(low.get_bits(0..16), 0x68 + 16);
+        assert_eq!(low.get_bits(40..44), 0b1001);
+        assert!(low.get_bit(47));
+        assert_eq!(low.get_bits(16..40), ptr.get_bits(0..24));
+        assert_eq!(low.get_bits(56..64), ptr.get_bits(24..32));
+        assert_eq!(high, ptr.get_bits(32..64));
+
+        let descriptor = Descriptor::SystemSegment(low, high);
+        assert_eq!(descriptor.try_base_limit(), Some((ptr, 0x68 + 16)));
+    }
+
+    #[test]
+    pub fn ldt_descriptor() {
+        let ldt = Box::leak(Box::new(GlobalDescriptorTable::<4>::empty()));
+        ldt.append(Descriptor::user_data_segment());
+        let base = VirtAddr::from_ptr(ldt.entries().as_ptr());
+
+        let mut gdt = GlobalDescriptorTable::new();
+        gdt.append(Descriptor::kernel_code_segment());
+        let selector = gdt.append(Descriptor::ldt(base, ldt.limit().into()));
+        assert_eq!(selector.index(), 2);
+        assert_eq!(gdt.len, 4);
+
+        let low = gdt.entries()[2].raw();
+        let high = gdt.entries()[3].raw();
+        assert_eq!(low.get_bits(0..16), 15);
+        assert_eq!(low.get_bits(40..44), 0b0010);
+        assert!(low.get_bit(47));
+        assert!(!low.get_bit(44));
+        assert_eq!(low.get_bits(16..40), base.as_u64().get_bits(0..24));
+        assert_eq!(low.get_bits(56..64), base.as_u64().get_bits(24..32));
+        assert_eq!(high, base.as_u64().get_bits(32..64));
+    }
+
+    #[test]
+    pub fn descriptor_base_limit() {
+        let base = VirtAddr::new(0xffff_8123_4567_89ab);
+        let ldt = Descriptor::ldt(base, 0xf_fedc);
+        assert_eq!(ldt.try_base_limit(), Some((base.as_u64(), 0xf_fedc)));
+
+        let tss = Descriptor::tss_segment(&TSS);
+        let ptr = &TSS as *const _ as u64;
+        assert_eq!(tss.try_base_limit(), Some((ptr, 0x68)));
+
+        assert_eq!(Descriptor::kernel_code_segment().try_base_limit(), None);
+        assert_eq!(Descriptor::user_data_segment().try_base_limit(), None);
+    }
+
+    #[test]
+    pub fn const_append() {
+        // Pointers can't be converted to integers in const contexts, so use a raw TSS
+        // descriptor with a zero base.
+        const TSS_DESCRIPTOR: Descriptor = Descriptor::SystemSegment(0x0000_8900_0000_0067, 0);
+
+        const fn build() -> (GlobalDescriptorTable, [SegmentSelector; 3]) {
+            let gdt = GlobalDescriptorTable::new();
+            let (gdt, code) = gdt.const_append(Descriptor::kernel_code_segment());
+            let (gdt, data) = gdt.const_append(Descriptor::kernel_data_segment());
+            let (gdt, tss) = gdt.const_append(TSS_DESCRIPTOR);
+            (gdt, [code, data, tss])
+        }
+        static GDT: (GlobalDescriptorTable, [SegmentSelector; 3]) = build();
+
+        let (gdt, [code, data, tss]) = &GDT;
+        assert_eq!(code.index(), 1);
+        assert_eq!(data.index(), 2);
+        assert_eq!(tss.index(), 3);
+        assert_eq!(gdt.len, 5);
+        assert_eq!(gdt.entries()[1].raw(), Flags::KERNEL_CODE64.bits());
+        assert_eq!(gdt.entries()[3].raw(), 0x0000_8900_0000_0067);
+
+        let (gdt, _) = gdt.clone().const_append(Descriptor::user_data_segment());
+        assert_eq!(gdt.len, 6);
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn panic_const_append_system_segment() {
+        let (gdt, _) = make_six_entry_gdt().const_append(Descriptor::user_data_segment());
+        // We have one free slot, but the GDT requires two
+        let _ = gdt.const_append(Descriptor::tss_segment(&TSS));
+    }
+
+    #[test]
+    pub fn from_pointer() {
+        let mut original = make_six_entry_gdt();
+        original.append(Descriptor::tss_segment(&TSS));
+        let pointer = crate::structures::DescriptorTablePointer {
+            base: VirtAddr::from_ptr(original.entries().as_ptr()),
+            limit: original.limit(),
+        };
+
+        let mut gdt = unsafe { GlobalDescriptorTable::<10>::from_pointer(&pointer) };
+        assert_eq!(gdt.entries(), original.entries());
+        assert_eq!(gdt.limit(), pointer.limit);
+
+        let selector = gdt.append(Descriptor::user_data_segment());
+        assert_eq!(selector.index(), 8);
+    }
+
+    #[test]
+    pub fn from_pointer_unaligned() {
+        let original = make_six_entry_gdt();
+        let mut buffer = [0u8; 6 * 8 + 1];
+        // Make sure that the table doesn't start at an 8-byte boundary.
+        let offset = usize::from(buffer.as_ptr() as usize % 8 == 0);
+        let table = &mut buffer[offset..][..6 * 8];
+        for (chunk, entry) in table.chunks_exact_mut(8).zip(original.entries()) {
+            chunk.copy_from_slice(&entry.raw().to_ne_bytes());
+        }
+        let pointer = crate::structures::DescriptorTablePointer {
+            base: VirtAddr::from_ptr(table.as_ptr()),
+            limit: original.limit(),
+        };
+
+        let gdt = unsafe { GlobalDescriptorTable::<8>::from_pointer(&pointer) };
+        assert_eq!(gdt.entries(), original.entries());
+    }
+
+    #[test]
+    pub fn borrowed() {
+        let table = std::boxed::Box::leak(std::boxed::Box::new([0u64; 6]));
+        table[1] = Flags::KERNEL_CODE64.bits();
+        table[2] = Flags::KERNEL_DATA.bits();
+        let base = VirtAddr::from_ptr(table.as_ptr());
+
+        let mut gdt = BorrowedGlobalDescriptorTable::from_raw_slice(table, 3);
+        assert_eq!(VirtAddr::from_ptr(gdt.entries().as_ptr()), base);
+        assert_eq!(gdt.limit(), 3 * 8 - 1);
+
+        let selector = gdt.append(Descriptor::tss_segment(&TSS));
+        assert_eq!(selector.index(), 3);
+        assert_eq!(gdt.entries().len(), 5);
+        assert_eq!(gdt.entries()[1].raw(), Flags::KERNEL_CODE64.bits());
+        assert_eq!(VirtAddr::from_ptr(gdt.entries().as_ptr()), base);
+        assert_eq!(gdt.limit(), 5 * 8 - 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "GDT full")]
+    pub fn borrowed_full() {
+        let table = std::boxed::Box::leak(std::boxed::Box::new([0u64; 2]));
+        let mut gdt = BorrowedGlobalDescriptorTable::from_raw_slice(table, 2);
+        gdt.append(Descriptor::kernel_code_segment());
+    }
+
     #[test]
     pub fn from_entries() {
         let raw = [0, Flags::KERNEL_CODE64.bits(), Flags::KERNEL_DATA.bits()];
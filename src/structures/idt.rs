@@ -17,10 +17,12 @@
 //! - [`HandlerFunc`]
 //! - [`HandlerFuncWithErrCode`]
 //! - [`PageFaultHandlerFunc`]
+//! - [`SelectorErrorHandlerFunc`]
 //!
 //! These types are defined for the compatibility with the Nightly Rust build.
 
 use crate::registers::rflags::RFlags;
+use crate::structures::tss::IstIndex;
 use crate::{PrivilegeLevel, VirtAddr};
 use bit_field::BitField;
 use bitflags::bitflags;
@@ -216,6 +218,38 @@ pub struct InterruptDescriptorTable {
     /// and the program cannot be restarted.
     ///
     /// The vector number of the `#DF` exception is 8.
+    ///
+    /// The handler must not return, so it has to be a [`DivergingHandlerFuncWithErrCode`].
+    #[cfg_attr(
+        feature = "abi_x86_interrupt",
+        doc = r#"
+```
+#![feature(abi_x86_interrupt)]
+use x86_64::structures::idt::{InterruptDescriptorTable, InterruptStackFrame};
+
+extern "x86-interrupt" fn double_fault_handler(
+    stack_frame: InterruptStackFrame,
+    _error_code: u64,
+) -> ! {
+    panic!("EXCEPTION: DOUBLE FAULT\n{:#?}", stack_frame);
+}
+
+let mut idt = InterruptDescriptorTable::new();
+idt.double_fault.set_handler_fn(double_fault_handler);
+```
+
+A handler that returns is rejected at compile time:
+
+```compile_fail
+#![feature(abi_x86_interrupt)]
+use x86_64::structures::idt::{InterruptDescriptorTable, InterruptStackFrame};
+
+extern "x86-interrupt" fn double_fault_handler(_: InterruptStackFrame, _: u64) {}
+
+let mut idt = InterruptDescriptorTable::new();
+idt.double_fault.set_handler_fn(double_fault_handler);
+```"#
+    )]
     pub double_fault: Entry<DivergingHandlerFuncWithErrCode>,
 
     /// This interrupt vector is reserved. It is for a discontinued exception originally used
@@ -229,11 +263,12 @@ pub struct InterruptDescriptorTable {
     /// a gate descriptor that results in an invalid stack-segment reference using an `SS`
     /// selector in the TSS.
     ///
-    /// The returned error code is the `SS` segment selector. The saved instruction pointer
-    /// points to the control-transfer instruction that caused the `#TS`.
+    /// The returned error code is the `SS` segment selector, see [`SelectorErrorCode`]. The
+    /// saved instruction pointer points to the control-transfer instruction that caused the
+    /// `#TS`.
     ///
     /// The vector number of the `#TS` exception is 10.
-    pub invalid_tss: Entry<HandlerFuncWithErrCode>,
+    pub invalid_tss: Entry<SelectorErrorHandlerFunc>,
 
     /// An segment-not-present exception (`#NP`) occurs when an attempt is made to load a
     /// segment or gate with a clear present bit.
@@ -243,7 +278,7 @@ pub struct InterruptDescriptorTable {
     /// that loaded the segment selector resulting in the `#NP`.
     ///
     /// The vector number of the `#NP` exception is 11.
-    pub segment_not_present: Entry<HandlerFuncWithErrCode>,
+    pub segment_not_present: Entry<SelectorErrorHandlerFunc>,
 
     /// An stack segment exception (`#SS`) can occur in the following situations:
     ///
@@ -260,7 +295,7 @@ pub struct InterruptDescriptorTable {
     /// caused the `#SS`.
     ///
     /// The vector number of the `#NP` exception is 12.
-    pub stack_segment_fault: Entry<HandlerFuncWithErrCode>,
+    pub stack_segment_fault: Entry<SelectorErrorHandlerFunc>,
 
     /// A general protection fault (`#GP`) can occur in various situations. Common causes include:
     ///
@@ -276,7 +311,7 @@ pub struct InterruptDescriptorTable {
     /// the instruction that caused the `#GP`.
     ///
     /// The vector number of the `#GP` exception is 13.
-    pub general_protection_fault: Entry<HandlerFuncWithErrCode>,
+    pub general_protection_fault: Entry<SelectorErrorHandlerFunc>,
 
     /// A page fault (`#PF`) can occur during a memory access in any of the following situations:
     ///
@@ -498,6 +533,9 @@ impl InterruptDescriptorTable {
 
     /// Loads the IDT in the CPU using the `lidt` command.
     ///
+    /// Unlike [`load`](Self::load), this function does not require a `'static` reference, so it
+    /// can be used for IDTs that are stored in heap allocated per-CPU structures.
+    ///
     /// # Safety
     ///
     /// As long as it is the active IDT, you must ensure that:
@@ -506,6 +544,9 @@ impl InterruptDescriptorTable {
     /// - `self` always stays at the same memory location. It is recommended to wrap it in
     ///   a `Box`.
     ///
+    /// Since the CPU reads the entries directly from memory, entries that are modified while the
+    /// IDT is loaded take effect immediately.
+    ///
     #[cfg(all(feature = "instructions", target_arch = "x86_64"))]
     #[inline]
     pub unsafe fn load_unsafe(&self) {
@@ -759,6 +800,24 @@ pub type PageFaultHandlerFunc =
 #[derive(Copy, Clone, Debug)]
 pub struct PageFaultHandlerFunc(());
 
+/// A handler function for an exception that pushes a selector error code, e.g. for a general
+/// protection fault.
+///
+/// This type alias is only usable with the `abi_x86_interrupt` feature enabled.
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    feature = "abi_x86_interrupt"
+))]
+pub type SelectorErrorHandlerFunc =
+    extern "x86-interrupt" fn(InterruptStackFrame, error_code: SelectorErrorCode);
+/// This type is not usable without the `abi_x86_interrupt` feature.
+#[cfg(not(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    feature = "abi_x86_interrupt"
+)))]
+#[derive(Copy, Clone, Debug)]
+pub struct SelectorErrorHandlerFunc(());
+
 /// A handler function that must not return, e.g. for a machine check exception.
 ///
 /// This type alias is only usable with the `abi_x86_interrupt` feature enabled.
@@ -795,6 +854,9 @@ pub struct DivergingHandlerFuncWithErrCode(());
 /// A general handler function for an interrupt or an exception with the interrupt/exceptions's index and an optional error code.
 pub type GeneralHandlerFunc = fn(InterruptStackFrame, index: u8, error_code: Option<u64>);
 
+/// A general handler function for an interrupt or an exception with the interrupt/exceptions's index and the decoded error code.
+pub type TypedGeneralHandlerFunc = fn(InterruptStackFrame, index: u8, error: ExceptionError);
+
 impl<F> Entry<F> {
     /// Creates a non-present IDT entry (but sets the must-be-one bits).
     #[inline]
@@ -819,10 +881,16 @@ impl<F> Entry<F> {
     /// The function returns a mutable reference to the entry's options that allows
     /// further customization.
     ///
+    /// Unlike [`set_handler_fn`](Entry::set_handler_fn), this doesn't impose a calling
+    /// convention, so it can be used to install custom entry code, e.g. a naked function that
+    /// saves all registers before calling into Rust.
+    ///
     /// # Safety
     ///
     /// The caller must ensure that `addr` is the address of a valid interrupt handler function,
-    /// and the signature of such a function is correct for the entry type.
+    /// and the signature of such a function is correct for the entry type. Custom entry code is
+    /// responsible for popping the error code (if the vector pushes one), preserving all
+    /// registers, and returning with `iretq`.
     #[cfg(all(feature = "instructions", target_arch = "x86_64"))]
     #[inline]
     pub unsafe fn set_handler_addr(&mut self, addr: VirtAddr) -> &mut EntryOptions {
@@ -874,9 +942,31 @@ impl<F: HandlerFuncType> Entry<F> {
 
 /// A common trait for all handler functions usable in [`Entry`].
 ///
+/// This trait is implemented for all handler function types of this module, e.g.
+/// [`HandlerFunc`] and [`PageFaultHandlerFunc`]. [`Entry::set_handler_fn`] is implemented once
+/// for all `Entry<F>` with `F: HandlerFuncType`, so each entry still only accepts handlers with
+/// its own signature. This also allows writing code that works with all entries.
+#[cfg_attr(
+    all(feature = "instructions", target_arch = "x86_64"),
+    doc = "
+```
+use x86_64::structures::idt::{Entry, HandlerFuncType};
+
+fn set_ring3_handler<F: HandlerFuncType>(entry: &mut Entry<F>, handler: F) {
+    entry
+        .set_handler_fn(handler)
+        .set_privilege_level(x86_64::PrivilegeLevel::Ring3);
+}
+```"
+)]
+///
 /// # Safety
 ///
 /// Implementors have to ensure that `to_virt_addr` returns a valid address.
+#[cfg_attr(
+    not(all(feature = "instructions", target_arch = "x86_64")),
+    allow(rustdoc::broken_intra_doc_links)
+)]
 pub unsafe trait HandlerFuncType {
     /// Get the virtual address of the handler function.
     fn to_virt_addr(self) -> VirtAddr;
@@ -906,6 +996,7 @@ macro_rules! impl_handler_func_type {
 impl_handler_func_type!(HandlerFunc);
 impl_handler_func_type!(HandlerFuncWithErrCode);
 impl_handler_func_type!(PageFaultHandlerFunc);
+impl_handler_func_type!(SelectorErrorHandlerFunc);
 impl_handler_func_type!(DivergingHandlerFunc);
 impl_handler_func_type!(DivergingHandlerFuncWithErrCode);
 
@@ -963,6 +1054,8 @@ impl EntryOptions {
 
     /// Let the CPU disable hardware interrupts when the handler is invoked. By default,
     /// interrupts are disabled on handler invocation.
+    ///
+    /// Passing `false` turns the entry into a trap gate, which leaves `RFLAGS.IF` unchanged.
     #[inline]
     pub fn disable_interrupts(&mut self, disable: bool) -> &mut Self {
         self.bits.set_bit(8, !disable);
@@ -988,7 +1081,9 @@ impl EntryOptions {
     /// An IST stack is specified by an IST index between 0 and 6 (inclusive). Using the same
     /// stack for multiple interrupts can be dangerous when nested interrupts are possible.
     ///
-    /// This function panics if the index is not in the range 0..7.
+    /// This function panics if the index is not in the range 0..7. See
+    /// [`set_ist_index`](Self::set_ist_index) for a variant that takes the hardware index used
+    /// by [`TaskStateSegment::set_ist`](crate::structures::tss::TaskStateSegment::set_ist).
     ///
     /// ## Safety
     ///
@@ -1002,6 +1097,20 @@ impl EntryOptions {
         self
     }
 
+    /// Assigns the Interrupt Stack Table (IST) stack with the given [`IstIndex`] to this
+    /// handler, just like [`set_stack_index`](Self::set_stack_index).
+    ///
+    /// ## Safety
+    ///
+    /// The caller must ensure that the IST entry of the active TSS with the given index points
+    /// to a valid stack that is not used by other interrupts. Otherwise, memory safety
+    /// violations are possible.
+    #[inline]
+    pub unsafe fn set_ist_index(&mut self, index: IstIndex) -> &mut Self {
+        self.bits.set_bits(0..3, index.get().into());
+        self
+    }
+
     fn stack_index(&self) -> Option<u16> {
         self.bits.get_bits(0..3).checked_sub(1)
     }
@@ -1038,6 +1147,23 @@ impl InterruptStackFrame {
 
     /// Gives mutable access to the contents of the interrupt stack frame.
     ///
+    /// The modifications are written directly to the frame on the stack, so they are used by
+    /// the `iretq` at the end of the handler. This can be used to resume execution at a
+    /// different location, e.g. to skip an emulated instruction:
+    ///
+    /// ```no_run
+    /// # #![feature(abi_x86_interrupt)]
+    /// use x86_64::structures::idt::{InterruptStackFrame, SelectorErrorCode};
+    ///
+    /// extern "x86-interrupt" fn general_protection_fault_handler(
+    ///     mut frame: InterruptStackFrame,
+    ///     _error_code: SelectorErrorCode,
+    /// ) {
+    ///     // Skip the two byte instruction that caused the fault.
+    ///     unsafe { frame.as_mut().update(|frame| frame.instruction_pointer += 2u64) };
+    /// }
+    /// ```
+    ///
     /// The `Volatile` wrapper is used because LLVM optimizations remove non-volatile
     /// modifications of the interrupt stack frame.
     ///
@@ -1048,6 +1174,11 @@ impl InterruptStackFrame {
     /// the instruction pointer field, the CPU can jump to arbitrary code at the end of the
     /// interrupt.
     ///
+    /// **`iretq` faults if the new values are invalid.** In particular, a non-canonical
+    /// instruction pointer, a code segment selector that doesn't reference a valid code
+    /// segment, reserved bits in the flags or an invalid stack segment for the target
+    /// privilege level cause a `#GP` on return from the handler.
+    ///
     /// Also, it is not fully clear yet whether modifications of the interrupt stack frame are
     /// officially supported by LLVM's x86 interrupt calling convention.
     #[inline]
@@ -1160,6 +1291,100 @@ impl fmt::Debug for InterruptStackFrameValue {
     }
 }
 
+/// The general purpose registers saved by custom interrupt entry code.
+///
+/// The registers can't be captured by a regular function because the compiler is free to use
+/// them before the first line of the function runs. Instead, the entry code has to push them
+/// on the stack before calling into Rust. This struct describes the layout that results from
+/// pushing `rax` first and `r15` last, so a pointer to it is the stack pointer after the last
+/// push. The [`InterruptStackFrameValue`] pushed by the CPU follows directly after it (for
+/// vectors that don't push an error code).
+///
+/// Only saving the general purpose registers is not enough before calling a regular Rust
+/// function: the function may freely use (and clobber) the vector registers `xmm0`-`xmm15`,
+/// and the calling convention requires the direction flag to be cleared. So the entry code
+/// also has to save the SSE state (e.g. with `fxsave`, or with `xsave` if AVX is enabled) and
+/// execute `cld`, unless the handler is compiled without SSE support (as on the
+/// `x86_64-unknown-none` target).
+#[cfg_attr(
+    all(feature = "instructions", target_arch = "x86_64"),
+    doc = r#"
+```no_run
+use core::arch::global_asm;
+use x86_64::structures::idt::{InterruptDescriptorTable, SavedRegisters};
+use x86_64::VirtAddr;
+
+extern "C" fn breakpoint_handler(registers: &mut SavedRegisters) {
+    // Modifications are restored into the registers by the entry code.
+    registers.rax = 42;
+}
+
+global_asm!(
+    ".global breakpoint_entry",
+    "breakpoint_entry:",
+    "push rax", "push rbx", "push rcx", "push rdx", "push rsi", "push rdi", "push rbp",
+    "push r8", "push r9", "push r10", "push r11", "push r12", "push r13", "push r14",
+    "push r15",
+    // The stack is 16-byte aligned here, as required for `fxsave` and the call.
+    "sub rsp, 512",
+    "fxsave [rsp]",
+    "lea rdi, [rsp + 512]",
+    "cld",
+    "call {handler}",
+    "fxrstor [rsp]",
+    "add rsp, 512",
+    "pop r15", "pop r14", "pop r13", "pop r12", "pop r11", "pop r10", "pop r9", "pop r8",
+    "pop rbp", "pop rdi", "pop rsi", "pop rdx", "pop rcx", "pop rbx", "pop rax",
+    "iretq",
+    handler = sym breakpoint_handler,
+);
+
+extern "C" {
+    fn breakpoint_entry();
+}
+
+let mut idt = InterruptDescriptorTable::new();
+unsafe {
+    idt.breakpoint
+        .set_handler_addr(VirtAddr::new(breakpoint_entry as usize as u64));
+}
+```"#
+)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct SavedRegisters {
+    /// The saved value of `r15`.
+    pub r15: u64,
+    /// The saved value of `r14`.
+    pub r14: u64,
+    /// The saved value of `r13`.
+    pub r13: u64,
+    /// The saved value of `r12`.
+    pub r12: u64,
+    /// The saved value of `r11`.
+    pub r11: u64,
+    /// The saved value of `r10`.
+    pub r10: u64,
+    /// The saved value of `r9`.
+    pub r9: u64,
+    /// The saved value of `r8`.
+    pub r8: u64,
+    /// The saved value of `rbp`.
+    pub rbp: u64,
+    /// The saved value of `rdi`.
+    pub rdi: u64,
+    /// The saved value of `rsi`.
+    pub rsi: u64,
+    /// The saved value of `rdx`.
+    pub rdx: u64,
+    /// The saved value of `rcx`.
+    pub rcx: u64,
+    /// The saved value of `rbx`.
+    pub rbx: u64,
+    /// The saved value of `rax`.
+    pub rax: u64,
+}
+
 bitflags! {
     /// Describes an page fault error code.
     ///
@@ -1191,15 +1416,18 @@ bitflags! {
         /// instruction fetch.
         const INSTRUCTION_FETCH = 1 << 4;
 
-        /// If this flag is set, it indicates that the page fault was caused by a protection key.
+        /// If this flag is set (bit 5), it indicates that the page fault was caused by a
+        /// protection key, i.e. the access was disallowed by the access rights of the page's
+        /// protection key in [`PKRU`](crate::registers::control::Pkru) (user pages) or `PKRS`
+        /// (supervisor pages).
         const PROTECTION_KEY = 1 << 5;
 
-        /// If this flag is set, it indicates that the page fault was caused by a shadow stack
-        /// access.
+        /// If this flag is set (bit 6), it indicates that the page fault was caused by a shadow
+        /// stack access.
         const SHADOW_STACK = 1 << 6;
 
-        /// If this flag is set, it indicates that the page fault was caused by SGX access-control
-        /// requirements (Intel-only).
+        /// If this flag is set (bit 15), it indicates that the page fault was caused by SGX
+        /// access-control requirements (Intel-only).
         const SGX = 1 << 15;
 
         /// If this flag is set, it indicates that the page fault is a result of the processor
@@ -1238,6 +1466,18 @@ impl SelectorErrorCode {
         self.flags.get_bit(0)
     }
 
+    /// Returns the raw error code.
+    pub const fn bits(&self) -> u64 {
+        self.flags
+    }
+
+    /// The descriptor table this error code refers to.
+    ///
+    /// This is the same as [`descriptor_table`](Self::descriptor_table).
+    pub fn table(&self) -> DescriptorTable {
+        self.descriptor_table()
+    }
+
     /// The descriptor table this error code refers to.
     pub fn descriptor_table(&self) -> DescriptorTable {
         match self.flags.get_bits(1..3) {
@@ -1250,8 +1490,8 @@ impl SelectorErrorCode {
     }
 
     /// The index of the selector which caused the error.
-    pub fn index(&self) -> u64 {
-        self.flags.get_bits(3..16)
+    pub fn index(&self) -> u16 {
+        self.flags.get_bits(3..16) as u16
     }
 
     /// If true, the #SS or #GP has returned zero as opposed to a SelectorErrorCode.
@@ -1283,6 +1523,47 @@ pub enum DescriptorTable {
     Ldt,
 }
 
+/// The error code of an interrupt or an exception, decoded according to its index.
+///
+/// This is passed to general handlers set with [`set_typed_general_handler!`](crate::set_typed_general_handler).
+#[cfg_attr(
+    not(all(
+        feature = "instructions",
+        feature = "abi_x86_interrupt",
+        target_arch = "x86_64"
+    )),
+    allow(rustdoc::broken_intra_doc_links)
+)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExceptionError {
+    /// The interrupt or exception doesn't push an error code.
+    None,
+    /// The error code of a page fault (vector 14).
+    PageFault(PageFaultErrorCode),
+    /// The error code of an invalid TSS, segment not present, stack segment fault or general
+    /// protection fault (vectors 10 to 13).
+    Selector(SelectorErrorCode),
+    /// The error code of any other exception, e.g. a double fault or an alignment check.
+    Other(u64),
+}
+
+impl ExceptionError {
+    /// Decodes the raw error code of the interrupt or exception with the given index.
+    #[inline]
+    pub const fn new(index: u8, error_code: Option<u64>) -> Self {
+        match (index, error_code) {
+            (_, None) => ExceptionError::None,
+            (14, Some(code)) => {
+                ExceptionError::PageFault(PageFaultErrorCode::from_bits_retain(code))
+            }
+            (10..=13, Some(code)) => {
+                ExceptionError::Selector(SelectorErrorCode::new_truncate(code))
+            }
+            (_, Some(code)) => ExceptionError::Other(code),
+        }
+    }
+}
+
 /// This structure defines the CPU-internal exception vector numbers.
 ///
 /// The values are defined by the following manual sections:
@@ -1474,6 +1755,68 @@ macro_rules! set_general_handler {
     }};
 }
 
+#[cfg(all(
+    feature = "instructions",
+    feature = "abi_x86_interrupt",
+    target_arch = "x86_64"
+))]
+#[macro_export]
+/// Set a general handler that receives a decoded [`ExceptionError`] in an
+/// [`InterruptDescriptorTable`].
+///
+/// This works like [`set_general_handler!`], but the error code is decoded based on the
+/// index of the entry.
+/// ```
+/// #![feature(abi_x86_interrupt)]
+/// use x86_64::set_typed_general_handler;
+/// use x86_64::structures::idt::{ExceptionError, InterruptDescriptorTable, InterruptStackFrame};
+///
+/// let mut idt = InterruptDescriptorTable::new();
+/// fn my_general_handler(
+///    stack_frame: InterruptStackFrame,
+///    index: u8,
+///    error: ExceptionError,
+/// ) {
+///     match error {
+///         ExceptionError::PageFault(error_code) => todo!("page fault: {:?}", error_code),
+///         ExceptionError::Selector(error_code) => todo!("selector error: {:?}", error_code),
+///         _ => todo!("handle irq {}", index),
+///     }
+/// }
+///
+/// # // there seems to be a bug in LLVM that causes rustc to crash on windows when compiling this test:
+/// # // https://github.com/rust-osdev/x86_64/pull/285#issuecomment-962642984
+/// # #[cfg(not(windows))]
+/// set_typed_general_handler!(&mut idt, my_general_handler, 0..32);
+/// ```
+macro_rules! set_typed_general_handler {
+    ($idt:expr, $handler:ident) => {
+        $crate::set_typed_general_handler!($idt, $handler, 0..=255);
+    };
+    ($idt:expr, $handler:ident, $idx:literal) => {
+        $crate::set_typed_general_handler!($idt, $handler, $idx..=$idx);
+    };
+    ($idt:expr, $handler:ident, $range:expr) => {{
+        // See `set_general_handler!` for why the handler is stored in a constant.
+        const TYPED_GENERAL_HANDLER: $crate::structures::idt::TypedGeneralHandlerFunc = $handler;
+
+        {
+            // Items are visible in the whole block, so this must not be in the same block as
+            // `TYPED_GENERAL_HANDLER`, which would otherwise pick it up if `$handler` has the
+            // same name.
+            fn decoding_general_handler(
+                frame: $crate::structures::idt::InterruptStackFrame,
+                index: u8,
+                error_code: ::core::option::Option<u64>,
+            ) {
+                let error = $crate::structures::idt::ExceptionError::new(index, error_code);
+                TYPED_GENERAL_HANDLER(frame, index, error);
+            }
+            $crate::set_general_handler!($idt, decoding_general_handler, $range);
+        }
+    }};
+}
+
 #[cfg(all(
     feature = "instructions",
     feature = "abi_x86_interrupt",
@@ -1522,36 +1865,36 @@ macro_rules! set_general_handler_entry {
     ($idt:expr, $handler:ident, $idx:ident, 0, 0, 0, 0, 1, 0, 1, 0) => {{
         extern "x86-interrupt" fn handler(
             frame: $crate::structures::idt::InterruptStackFrame,
-            error_code: u64,
+            error_code: $crate::structures::idt::SelectorErrorCode,
         ) {
-            $handler(frame, $idx.into(), Some(error_code));
+            $handler(frame, $idx.into(), Some(error_code.bits()));
         }
         $idt.invalid_tss.set_handler_fn(handler);
     }};
     ($idt:expr, $handler:ident, $idx:ident, 0, 0, 0, 0, 1, 0, 1, 1) => {{
         extern "x86-interrupt" fn handler(
             frame: $crate::structures::idt::InterruptStackFrame,
-            error_code: u64,
+            error_code: $crate::structures::idt::SelectorErrorCode,
         ) {
-            $handler(frame, $idx.into(), Some(error_code));
+            $handler(frame, $idx.into(), Some(error_code.bits()));
         }
         $idt.segment_not_present.set_handler_fn(handler);
     }};
     ($idt:expr, $handler:ident, $idx:ident, 0, 0, 0, 0, 1, 1, 0, 0) => {{
         extern "x86-interrupt" fn handler(
             frame: $crate::structures::idt::InterruptStackFrame,
-            error_code: u64,
+            error_code: $crate::structures::idt::SelectorErrorCode,
         ) {
-            $handler(frame, $idx.into(), Some(error_code));
+            $handler(frame, $idx.into(), Some(error_code.bits()));
         }
         $idt.stack_segment_fault.set_handler_fn(handler);
     }};
     ($idt:expr, $handler:ident, $idx:ident, 0, 0, 0, 0, 1, 1, 0, 1) => {{
         extern "x86-interrupt" fn handler(
             frame: $crate::structures::idt::InterruptStackFrame,
-            error_code: u64,
+            error_code: $crate::structures::idt::SelectorErrorCode,
         ) {
-            $handler(frame, $idx.into(), Some(error_code));
+            $handler(frame, $idx.into(), Some(error_code.bits()));
         }
         $idt.general_protection_fault.set_handler_fn(handler);
     }};
@@ -1720,6 +2063,60 @@ mod test {
         }
     }
 
+    #[cfg(all(
+        feature = "instructions",
+        feature = "abi_x86_interrupt",
+        target_arch = "x86_64"
+    ))]
+    // there seems to be a bug in LLVM that causes rustc to crash on windows when compiling this test:
+    // https://github.com/rust-osdev/x86_64/pull/285#issuecomment-962642984
+    #[cfg(not(windows))]
+    #[test]
+    fn typed_default_handlers() {
+        fn general_handler(_stack_frame: InterruptStackFrame, _index: u8, _error: ExceptionError) {}
+
+        let mut idt = InterruptDescriptorTable::new();
+        set_typed_general_handler!(&mut idt, general_handler, 13);
+        set_typed_general_handler!(&mut idt, general_handler, 32..64);
+        for i in 0..=255 {
+            assert_eq!(entry_present(&idt, i), i == 13 || (32..64).contains(&i));
+        }
+    }
+
+    #[test]
+    fn selector_error_code() {
+        // External event, IDT entry 13
+        let error_code = SelectorErrorCode::new(0x6b).unwrap();
+        assert!(error_code.external());
+        assert_eq!(error_code.table(), DescriptorTable::Idt);
+        assert_eq!(error_code.index(), 13);
+        assert_eq!(error_code.bits(), 0x6b);
+        assert!(!error_code.is_null());
+
+        // LDT entry 5
+        let error_code = SelectorErrorCode::new_truncate(0x1_002c);
+        assert!(!error_code.external());
+        assert_eq!(error_code.table(), DescriptorTable::Ldt);
+        assert_eq!(error_code.index(), 5);
+        assert_eq!(SelectorErrorCode::new(0x1_002c), None);
+    }
+
+    #[test]
+    fn exception_error() {
+        assert_eq!(ExceptionError::new(0, None), ExceptionError::None);
+        assert_eq!(
+            ExceptionError::new(14, Some(0b110)),
+            ExceptionError::PageFault(
+                PageFaultErrorCode::CAUSED_BY_WRITE | PageFaultErrorCode::USER_MODE
+            )
+        );
+        assert_eq!(
+            ExceptionError::new(13, Some(0x2b)),
+            ExceptionError::Selector(SelectorErrorCode::new_truncate(0x2b))
+        );
+        assert_eq!(ExceptionError::new(8, Some(0)), ExceptionError::Other(0));
+    }
+
     #[test]
     fn idt_fmt_debug() {
         dbg!(InterruptDescriptorTable::new());
@@ -1739,6 +2136,68 @@ mod test {
         })
     }
 
+    #[cfg(all(feature = "instructions", target_arch = "x86_64"))]
+    #[test]
+    fn set_handler_addr() {
+        let mut idt = InterruptDescriptorTable::new();
+        let addr = VirtAddr::new(0xffff_8000_1234_5678);
+        unsafe {
+            idt[0x80]
+                .set_handler_addr(addr)
+                .set_privilege_level(PrivilegeLevel::Ring3);
+        }
+        assert_eq!(idt[0x80].handler_addr(), addr);
+        assert!(entry_present(&idt, 0x80));
+        assert_eq!(idt[0x80].options.privilege_level(), PrivilegeLevel::Ring3);
+    }
+
+    #[test]
+    fn saved_registers_layout() {
+        use core::mem::{size_of, transmute};
+
+        assert_eq!(size_of::<SavedRegisters>(), 15 * 8);
+        let registers = SavedRegisters {
+            r15: 1,
+            rax: 2,
+            ..Default::default()
+        };
+        let pushed: [u64; 15] = unsafe { transmute(registers) };
+        assert_eq!(pushed[0], 1);
+        assert_eq!(pushed[14], 2);
+    }
+
+    #[test]
+    fn page_fault_error_code() {
+        let code = PageFaultErrorCode::from_bits_truncate(0x8025);
+        assert_eq!(
+            code,
+            PageFaultErrorCode::PROTECTION_VIOLATION
+                | PageFaultErrorCode::USER_MODE
+                | PageFaultErrorCode::PROTECTION_KEY
+                | PageFaultErrorCode::SGX
+        );
+        assert!(!code.contains(PageFaultErrorCode::SHADOW_STACK));
+    }
+
+    #[test]
+    fn entry_options_trap_gate() {
+        let mut options = EntryOptions::minimal();
+        unsafe {
+            options
+                .set_present(true)
+                .set_privilege_level(PrivilegeLevel::Ring3)
+                .disable_interrupts(false)
+                .set_ist_index(IstIndex::new(2));
+        }
+        // present | DPL 3 | 64-bit trap gate | IST 2
+        assert_eq!(options.bits, 0b1110_1111_0000_0010);
+        assert_eq!(options.stack_index(), Some(1));
+        assert_eq!(options.privilege_level(), PrivilegeLevel::Ring3);
+
+        unsafe { options.set_stack_index(1) };
+        assert_eq!(options.bits, 0b1110_1111_0000_0010);
+    }
+
     #[test]
     fn isr_frame_manipulation() {
         let mut frame = InterruptStackFrame(InterruptStackFrameValue {
@@ -1754,5 +2213,6 @@ mod test {
         unsafe {
             frame.as_mut().update(|f| f.instruction_pointer += 2u64);
         }
+        assert_eq!(frame.instruction_pointer, VirtAddr::new(0x1002));
     }
 }
@@ -4,6 +4,8 @@ use super::page::AddressNotAligned;
 use crate::structures::paging::page::{PageSize, Size4KiB};
 use crate::PhysAddr;
 use core::fmt;
+#[cfg(feature = "step_trait")]
+use core::iter::Step;
 use core::marker::PhantomData;
 use core::ops::{Add, AddAssign, Sub, SubAssign};
 
@@ -17,9 +19,11 @@ pub struct PhysFrame<S: PageSize = Size4KiB> {
 }
 
 impl<S: PageSize> PhysFrame<S> {
-    /// Returns the frame that starts at the given virtual address.
+    /// Returns the frame that starts at the given physical address.
     ///
     /// Returns an error if the address is not correctly aligned (i.e. is not a valid frame start).
+    /// Use [`containing_address`](Self::containing_address) to get the frame that contains an
+    /// unaligned address instead.
     #[inline]
     #[rustversion::attr(since(1.61), const)]
     pub fn from_start_address(address: PhysAddr) -> Result<Self, AddressNotAligned> {
@@ -31,7 +35,7 @@ impl<S: PageSize> PhysFrame<S> {
         Ok(unsafe { PhysFrame::from_start_address_unchecked(address) })
     }
 
-    /// Returns the frame that starts at the given virtual address.
+    /// Returns the frame that starts at the given physical address.
     ///
     /// ## Safety
     ///
@@ -84,6 +88,27 @@ impl<S: PageSize> PhysFrame<S> {
     }
 }
 
+#[cfg(feature = "serde")]
+impl<S: PageSize> serde::Serialize for PhysFrame<S> {
+    fn serialize<Ser: serde::Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        serde::Serialize::serialize(&self.start_address, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, S: PageSize> serde::Deserialize<'de> for PhysFrame<S> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let addr = <PhysAddr as serde::Deserialize>::deserialize(deserializer)?;
+        PhysFrame::from_start_address(addr).map_err(|_| {
+            serde::de::Error::custom(format_args!(
+                "address {:#x} is not aligned to the frame size {}",
+                addr.as_u64(),
+                S::DEBUG_STR
+            ))
+        })
+    }
+}
+
 impl<S: PageSize> fmt::Debug for PhysFrame<S> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.write_fmt(format_args!(
@@ -132,6 +157,47 @@ impl<S: PageSize> Sub<PhysFrame<S>> for PhysFrame<S> {
     }
 }
 
+#[cfg(feature = "step_trait")]
+impl<S: PageSize> Step for PhysFrame<S> {
+    fn steps_between(start: &Self, end: &Self) -> (usize, Option<usize>) {
+        use core::convert::TryFrom;
+
+        if let Some(steps) = end
+            .start_address
+            .as_u64()
+            .checked_sub(start.start_address.as_u64())
+        {
+            let steps = usize::try_from(steps / S::SIZE).ok();
+            (steps.unwrap_or(usize::MAX), steps)
+        } else {
+            (0, None)
+        }
+    }
+
+    fn forward_checked(start: Self, count: usize) -> Option<Self> {
+        use core::convert::TryFrom;
+
+        let count = u64::try_from(count).ok()?.checked_mul(S::SIZE)?;
+        let addr = start.start_address.as_u64().checked_add(count)?;
+        let start_address = PhysAddr::try_new(addr).ok()?;
+        Some(Self {
+            start_address,
+            size: PhantomData,
+        })
+    }
+
+    fn backward_checked(start: Self, count: usize) -> Option<Self> {
+        use core::convert::TryFrom;
+
+        let count = u64::try_from(count).ok()?.checked_mul(S::SIZE)?;
+        let addr = start.start_address.as_u64().checked_sub(count)?;
+        Some(Self {
+            start_address: PhysAddr::new(addr),
+            size: PhantomData,
+        })
+    }
+}
+
 /// An range of physical memory frames, exclusive the upper bound.
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 #[repr(C)]
@@ -164,6 +230,12 @@ impl<S: PageSize> PhysFrameRange<S> {
     pub fn size(&self) -> u64 {
         S::SIZE * self.len()
     }
+
+    /// Returns whether the given frame is part of this range.
+    #[inline]
+    pub fn contains(&self, frame: PhysFrame<S>) -> bool {
+        self.start <= frame && frame < self.end
+    }
 }
 
 impl<S: PageSize> Iterator for PhysFrameRange<S> {
@@ -222,6 +294,12 @@ impl<S: PageSize> PhysFrameRangeInclusive<S> {
     pub fn size(&self) -> u64 {
         S::SIZE * self.len()
     }
+
+    /// Returns whether the given frame is part of this range.
+    #[inline]
+    pub fn contains(&self, frame: PhysFrame<S>) -> bool {
+        self.start <= frame && frame <= self.end
+    }
 }
 
 impl<S: PageSize> Iterator for PhysFrameRangeInclusive<S> {
@@ -251,6 +329,8 @@ impl<S: PageSize> fmt::Debug for PhysFrameRangeInclusive<S> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::structures::paging::page::{Size1GiB, Size2MiB};
+
     #[test]
     pub fn test_frame_range_len() {
         let start_addr = PhysAddr::new(0xdead_beaf);
@@ -263,4 +343,71 @@ mod tests {
         let range_inclusive = PhysFrameRangeInclusive { start, end };
         assert_eq!(range_inclusive.len(), 51);
     }
+
+    #[test]
+    pub fn huge_frame_alignment() {
+        let addr = PhysAddr::new(0x4020_1000);
+        assert_eq!(
+            PhysFrame::<Size2MiB>::from_start_address(addr),
+            Err(AddressNotAligned)
+        );
+        assert_eq!(
+            PhysFrame::<Size1GiB>::from_start_address(addr),
+            Err(AddressNotAligned)
+        );
+
+        let frame = PhysFrame::<Size2MiB>::containing_address(addr);
+        assert_eq!(frame.start_address(), PhysAddr::new(0x4020_0000));
+        assert_eq!(
+            PhysFrame::from_start_address(frame.start_address()),
+            Ok(frame)
+        );
+
+        let frame = PhysFrame::<Size1GiB>::containing_address(addr);
+        assert_eq!(frame.start_address(), PhysAddr::new(0x4000_0000));
+        assert_eq!(
+            PhysFrame::from_start_address(frame.start_address()),
+            Ok(frame)
+        );
+    }
+
+    #[test]
+    pub fn test_frame_range_contains() {
+        let start = PhysFrame::<Size4KiB>::containing_address(PhysAddr::new(0x1000));
+        let end = start + 3;
+
+        let range = PhysFrame::range(start, end);
+        assert!(range.contains(start));
+        assert!(range.contains(end - 1));
+        assert!(!range.contains(end));
+        assert!(!range.contains(start - 1));
+
+        let range_inclusive = PhysFrame::range_inclusive(start, end);
+        assert!(range_inclusive.contains(start));
+        assert!(range_inclusive.contains(end));
+        assert!(!range_inclusive.contains(end + 1));
+        assert!(start < end);
+    }
+
+    #[test]
+    #[cfg(feature = "step_trait")]
+    fn frame_step() {
+        let start = PhysFrame::<Size4KiB>::containing_address(PhysAddr::new(0x1000));
+        let end = start + 3;
+        let mut frames = start..end;
+        assert_eq!(
+            frames.nth(1).unwrap().start_address(),
+            PhysAddr::new(0x2000)
+        );
+        assert_eq!((start..end).count(), 3);
+        assert_eq!((start..end).step_by(2).last(), Some(start + 2));
+
+        let last = PhysFrame::<Size4KiB>::containing_address(PhysAddr::new(0x000f_ffff_ffff_f000));
+        assert_eq!(Step::forward_checked(last, 1), None);
+        assert_eq!(Step::backward_checked(start, 2), None);
+        assert_eq!(
+            Step::steps_between(&start, &last),
+            (0xff_ffff_fffe, Some(0xff_ffff_fffe))
+        );
+    }
 }
@@ -1,6 +1,7 @@
 //! Traits for abstracting away frame allocation and deallocation.
 
-use crate::structures::paging::{PageSize, PhysFrame};
+use crate::structures::paging::frame::PhysFrameRange;
+use crate::structures::paging::{PageSize, PhysFrame, Size4KiB};
 
 /// A trait for types that can allocate a frame of memory.
 ///
@@ -23,3 +24,85 @@ pub trait FrameDeallocator<S: PageSize> {
     /// The caller must ensure that the passed frame is unused.
     unsafe fn deallocate_frame(&mut self, frame: PhysFrame<S>);
 }
+
+/// A frame allocator that hands out the frames of a [`PhysFrameRange`] in order.
+///
+/// Allocated frames can't be freed individually; the allocator returns `None` once all
+/// frames of the range were handed out.
+#[derive(Debug, Clone)]
+pub struct BumpFrameAllocator {
+    range: PhysFrameRange,
+    next: PhysFrame,
+}
+
+impl BumpFrameAllocator {
+    /// Creates a new allocator that hands out the frames of the given range.
+    ///
+    /// ## Safety
+    ///
+    /// The caller must guarantee that all frames in the range are unused and that they are not
+    /// handed out by any other frame allocator.
+    #[inline]
+    pub unsafe fn new(range: PhysFrameRange) -> Self {
+        BumpFrameAllocator {
+            range,
+            next: range.start,
+        }
+    }
+
+    /// Returns the number of frames that can still be allocated.
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        PhysFrameRange {
+            start: self.next,
+            end: self.range.end,
+        }
+        .len() as usize
+    }
+
+    /// Makes all frames of the range available for allocation again.
+    ///
+    /// ## Safety
+    ///
+    /// The caller must guarantee that none of the frames that were allocated so far are
+    /// still in use.
+    #[inline]
+    pub unsafe fn reset(&mut self) {
+        self.next = self.range.start;
+    }
+}
+
+unsafe impl FrameAllocator<Size4KiB> for BumpFrameAllocator {
+    #[inline]
+    fn allocate_frame(&mut self) -> Option<PhysFrame> {
+        if self.next < self.range.end {
+            let frame = self.next;
+            self.next += 1;
+            Some(frame)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PhysAddr;
+
+    #[test]
+    fn bump_frame_allocator() {
+        let start = PhysFrame::containing_address(PhysAddr::new(0x10_0000));
+        let mut allocator = unsafe { BumpFrameAllocator::new(PhysFrame::range(start, start + 3)) };
+        assert_eq!(allocator.remaining(), 3);
+        assert_eq!(allocator.allocate_frame(), Some(start));
+        assert_eq!(allocator.allocate_frame(), Some(start + 1));
+        assert_eq!(allocator.allocate_frame(), Some(start + 2));
+        assert_eq!(allocator.remaining(), 0);
+        assert_eq!(allocator.allocate_frame(), None);
+
+        unsafe { allocator.reset() };
+        assert_eq!(allocator.remaining(), 3);
+        assert_eq!(allocator.allocate_frame(), Some(start));
+    }
+}
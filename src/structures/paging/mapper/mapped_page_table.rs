@@ -1,8 +1,9 @@
 use crate::structures::paging::{
     mapper::*,
     page::AddressNotAligned,
-    page_table::{FrameError, PageTable, PageTableEntry, PageTableLevel},
+    page_table::{FrameError, PageTable, PageTableEntry, PageTableIndex, PageTableLevel},
 };
+use core::fmt;
 
 /// A Mapper implementation that relies on a PhysAddr to VirtAddr conversion function.
 ///
@@ -14,7 +15,9 @@ use crate::structures::paging::{
 #[derive(Debug)]
 pub struct MappedPageTable<'a, P: PageTableFrameMapping> {
     page_table_walker: PageTableWalker<P>,
-    level_4_table: &'a mut PageTable,
+    top_level_table: &'a mut PageTable,
+    #[cfg(feature = "la57")]
+    five_level: bool,
 }
 
 impl<'a, P: PageTableFrameMapping> MappedPageTable<'a, P> {
@@ -30,19 +33,76 @@ impl<'a, P: PageTableFrameMapping> MappedPageTable<'a, P> {
     #[inline]
     pub unsafe fn new(level_4_table: &'a mut PageTable, page_table_frame_mapping: P) -> Self {
         Self {
-            level_4_table,
+            top_level_table: level_4_table,
             page_table_walker: unsafe { PageTableWalker::new(page_table_frame_mapping) },
+            #[cfg(feature = "la57")]
+            five_level: false,
+        }
+    }
+
+    /// Creates a new `MappedPageTable` for 5-level paging that uses the passed
+    /// `PageTableFrameMapping` for converting virtual to physical addresses.
+    ///
+    /// All methods of the mapper (including the `set_flags_p4_entry` family) first look up
+    /// the level 4 table in the given level 5 table and then proceed like for 4-level paging.
+    ///
+    /// ## Safety
+    ///
+    /// This function is unsafe because the caller must guarantee that the passed `page_table_frame_mapping`
+    /// `PageTableFrameMapping` is correct. Also, the passed `level_5_table` must point to the level 5 page table
+    /// of a valid page table hierarchy. Otherwise this function might break memory safety, e.g.
+    /// by writing to an illegal memory location.
+    #[cfg(feature = "la57")]
+    #[inline]
+    pub unsafe fn new_level_5(
+        level_5_table: &'a mut PageTable,
+        page_table_frame_mapping: P,
+    ) -> Self {
+        Self {
+            top_level_table: level_5_table,
+            page_table_walker: unsafe { PageTableWalker::new(page_table_frame_mapping) },
+            five_level: true,
         }
     }
 
     /// Returns an immutable reference to the wrapped level 4 `PageTable` instance.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the mapper was created with `new_level_5`. Use
+    /// [`top_level_table`](Self::top_level_table) instead.
     pub fn level_4_table(&self) -> &PageTable {
-        self.level_4_table
+        #[cfg(feature = "la57")]
+        assert!(!self.five_level, "mapper uses 5-level paging");
+        self.top_level_table
     }
 
     /// Returns a mutable reference to the wrapped level 4 `PageTable` instance.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the mapper was created with `new_level_5`. Use
+    /// [`top_level_table_mut`](Self::top_level_table_mut) instead.
     pub fn level_4_table_mut(&mut self) -> &mut PageTable {
-        self.level_4_table
+        #[cfg(feature = "la57")]
+        assert!(!self.five_level, "mapper uses 5-level paging");
+        self.top_level_table
+    }
+
+    /// Returns an immutable reference to the wrapped top level `PageTable` instance.
+    ///
+    /// This is the level 5 table if the mapper was created with `new_level_5` and the
+    /// level 4 table otherwise.
+    pub fn top_level_table(&self) -> &PageTable {
+        self.top_level_table
+    }
+
+    /// Returns a mutable reference to the wrapped top level `PageTable` instance.
+    ///
+    /// This is the level 5 table if the mapper was created with `new_level_5` and the
+    /// level 4 table otherwise.
+    pub fn top_level_table_mut(&mut self) -> &mut PageTable {
+        self.top_level_table
     }
 
     /// Returns the `PageTableFrameMapping` used for converting virtual to physical addresses.
@@ -50,6 +110,92 @@ impl<'a, P: PageTableFrameMapping> MappedPageTable<'a, P> {
         &self.page_table_walker.page_table_frame_mapping
     }
 
+    /// Returns an iterator over all present entries of the page table hierarchy.
+    ///
+    /// The entries are yielded in depth-first order, i.e. each entry that points to a page
+    /// table is directly followed by the entries of that table. Entries that map a huge page
+    /// are yielded like level 1 entries and are not descended into. The iterator does not
+    /// allocate.
+    pub fn walk(&self) -> impl Iterator<Item = PageTableWalkEntry> + '_ {
+        #[cfg(feature = "la57")]
+        let top_level = if self.five_level { 5 } else { 4 };
+        #[cfg(not(feature = "la57"))]
+        let top_level = 4;
+
+        let mut tables = [None; 5];
+        tables[0] = Some(&*self.top_level_table);
+        PageTableWalk {
+            walker: &self.page_table_walker,
+            top_level,
+            depth: 1,
+            tables,
+            next_index: [0; 5],
+            base: [0; 5],
+        }
+    }
+
+    /// Returns the level 4 table that is responsible for the given address, together with
+    /// the walker for the lower levels.
+    ///
+    /// With 5-level paging, the level 4 table is looked up in the level 5 table first.
+    fn p4_table(
+        &self,
+        addr: VirtAddr,
+    ) -> Result<(&PageTable, &PageTableWalker<P>), PageTableWalkError> {
+        let walker = &self.page_table_walker;
+        #[cfg(feature = "la57")]
+        if self.five_level {
+            let p4 = walker.next_table(&self.top_level_table[addr.p5_index()])?;
+            return Ok((p4, walker));
+        }
+        #[cfg(not(feature = "la57"))]
+        let _ = addr;
+        Ok((self.top_level_table, walker))
+    }
+
+    /// Like [`p4_table`](Self::p4_table), but returns a mutable reference.
+    fn p4_table_mut(
+        &mut self,
+        addr: VirtAddr,
+    ) -> Result<(&mut PageTable, &PageTableWalker<P>), PageTableWalkError> {
+        let walker = &self.page_table_walker;
+        let top_level_table = &mut *self.top_level_table;
+        #[cfg(feature = "la57")]
+        if self.five_level {
+            let p4 = walker.next_table_mut(&mut top_level_table[addr.p5_index()])?;
+            return Ok((p4, walker));
+        }
+        #[cfg(not(feature = "la57"))]
+        let _ = addr;
+        Ok((top_level_table, walker))
+    }
+
+    /// Like [`p4_table_mut`](Self::p4_table_mut), but creates the level 4 table if needed.
+    fn p4_table_create<A>(
+        &mut self,
+        addr: VirtAddr,
+        insert_flags: PageTableFlags,
+        allocator: &mut A,
+    ) -> Result<(&mut PageTable, &PageTableWalker<P>), PageTableCreateError>
+    where
+        A: FrameAllocator<Size4KiB> + ?Sized,
+    {
+        let walker = &self.page_table_walker;
+        let top_level_table = &mut *self.top_level_table;
+        #[cfg(feature = "la57")]
+        if self.five_level {
+            let p4 = walker.create_next_table(
+                &mut top_level_table[addr.p5_index()],
+                insert_flags,
+                allocator,
+            )?;
+            return Ok((p4, walker));
+        }
+        #[cfg(not(feature = "la57"))]
+        let _ = (addr, insert_flags, allocator);
+        Ok((top_level_table, walker))
+    }
+
     /// Helper function for implementing Mapper. Safe to limit the scope of unsafe, see
     /// https://github.com/rust-lang/rfcs/pull/2585.
     fn map_to_1gib<A>(
@@ -63,17 +209,19 @@ impl<'a, P: PageTableFrameMapping> MappedPageTable<'a, P> {
     where
         A: FrameAllocator<Size4KiB> + ?Sized,
     {
-        let p4 = &mut self.level_4_table;
-        let p3 = self.page_table_walker.create_next_table(
-            &mut p4[page.p4_index()],
-            parent_table_flags,
-            allocator,
-        )?;
-
-        if !p3[page.p3_index()].is_unused() {
+        let (p4, walker) =
+            self.p4_table_create(page.start_address(), parent_table_flags, allocator)?;
+        let p3 =
+            walker.create_next_table(&mut p4[page.p4_index()], parent_table_flags, allocator)?;
+
+        let entry = &mut p3[page.p3_index()];
+        if !entry.is_unused() {
+            if !entry.flags().contains(PageTableFlags::HUGE_PAGE) {
+                return Err(MapToError::EntryPointsToPageTable);
+            }
             return Err(MapToError::PageAlreadyMapped(frame));
         }
-        p3[page.p3_index()].set_addr(frame.start_address(), flags | PageTableFlags::HUGE_PAGE);
+        entry.set_addr(frame.start_address(), flags | PageTableFlags::HUGE_PAGE);
 
         Ok(MapperFlush::new(page))
     }
@@ -91,22 +239,21 @@ impl<'a, P: PageTableFrameMapping> MappedPageTable<'a, P> {
     where
         A: FrameAllocator<Size4KiB> + ?Sized,
     {
-        let p4 = &mut self.level_4_table;
-        let p3 = self.page_table_walker.create_next_table(
-            &mut p4[page.p4_index()],
-            parent_table_flags,
-            allocator,
-        )?;
-        let p2 = self.page_table_walker.create_next_table(
-            &mut p3[page.p3_index()],
-            parent_table_flags,
-            allocator,
-        )?;
-
-        if !p2[page.p2_index()].is_unused() {
+        let (p4, walker) =
+            self.p4_table_create(page.start_address(), parent_table_flags, allocator)?;
+        let p3 =
+            walker.create_next_table(&mut p4[page.p4_index()], parent_table_flags, allocator)?;
+        let p2 =
+            walker.create_next_table(&mut p3[page.p3_index()], parent_table_flags, allocator)?;
+
+        let entry = &mut p2[page.p2_index()];
+        if !entry.is_unused() {
+            if !entry.flags().contains(PageTableFlags::HUGE_PAGE) {
+                return Err(MapToError::EntryPointsToPageTable);
+            }
             return Err(MapToError::PageAlreadyMapped(frame));
         }
-        p2[page.p2_index()].set_addr(frame.start_address(), flags | PageTableFlags::HUGE_PAGE);
+        entry.set_addr(frame.start_address(), flags | PageTableFlags::HUGE_PAGE);
 
         Ok(MapperFlush::new(page))
     }
@@ -124,22 +271,14 @@ impl<'a, P: PageTableFrameMapping> MappedPageTable<'a, P> {
     where
         A: FrameAllocator<Size4KiB> + ?Sized,
     {
-        let p4 = &mut self.level_4_table;
-        let p3 = self.page_table_walker.create_next_table(
-            &mut p4[page.p4_index()],
-            parent_table_flags,
-            allocator,
-        )?;
-        let p2 = self.page_table_walker.create_next_table(
-            &mut p3[page.p3_index()],
-            parent_table_flags,
-            allocator,
-        )?;
-        let p1 = self.page_table_walker.create_next_table(
-            &mut p2[page.p2_index()],
-            parent_table_flags,
-            allocator,
-        )?;
+        let (p4, walker) =
+            self.p4_table_create(page.start_address(), parent_table_flags, allocator)?;
+        let p3 =
+            walker.create_next_table(&mut p4[page.p4_index()], parent_table_flags, allocator)?;
+        let p2 =
+            walker.create_next_table(&mut p3[page.p3_index()], parent_table_flags, allocator)?;
+        let p1 =
+            walker.create_next_table(&mut p2[page.p2_index()], parent_table_flags, allocator)?;
 
         if !p1[page.p1_index()].is_unused() {
             return Err(MapToError::PageAlreadyMapped(frame));
@@ -166,14 +305,21 @@ impl<P: PageTableFrameMapping> Mapper<Size1GiB> for MappedPageTable<'_, P> {
         self.map_to_1gib(page, frame, flags, parent_table_flags, allocator)
     }
 
+    #[inline]
     fn unmap(
         &mut self,
         page: Page<Size1GiB>,
     ) -> Result<(PhysFrame<Size1GiB>, MapperFlush<Size1GiB>), UnmapError> {
-        let p4 = &mut self.level_4_table;
-        let p3 = self
-            .page_table_walker
-            .next_table_mut(&mut p4[page.p4_index()])?;
+        self.unmap_with_flags(page)
+            .map(|(frame, _, flush)| (frame, flush))
+    }
+
+    fn unmap_with_flags(
+        &mut self,
+        page: Page<Size1GiB>,
+    ) -> Result<(PhysFrame<Size1GiB>, PageTableFlags, MapperFlush<Size1GiB>), UnmapError> {
+        let (p4, walker) = self.p4_table_mut(page.start_address())?;
+        let p3 = walker.next_table_mut(&mut p4[page.p4_index()])?;
 
         let p3_entry = &mut p3[page.p3_index()];
         let flags = p3_entry.flags();
@@ -189,7 +335,7 @@ impl<P: PageTableFrameMapping> Mapper<Size1GiB> for MappedPageTable<'_, P> {
             .map_err(|AddressNotAligned| UnmapError::InvalidFrameAddress(p3_entry.addr()))?;
 
         p3_entry.set_unused();
-        Ok((frame, MapperFlush::new(page)))
+        Ok((frame, flags, MapperFlush::new(page)))
     }
 
     unsafe fn update_flags(
@@ -197,14 +343,18 @@ impl<P: PageTableFrameMapping> Mapper<Size1GiB> for MappedPageTable<'_, P> {
         page: Page<Size1GiB>,
         flags: PageTableFlags,
     ) -> Result<MapperFlush<Size1GiB>, FlagUpdateError> {
-        let p4 = &mut self.level_4_table;
-        let p3 = self
-            .page_table_walker
-            .next_table_mut(&mut p4[page.p4_index()])?;
+        let (p4, walker) = self.p4_table_mut(page.start_address())?;
+        let p3 = walker.next_table_mut(&mut p4[page.p4_index()])?;
 
         if p3[page.p3_index()].is_unused() {
             return Err(FlagUpdateError::PageNotMapped);
         }
+        if !p3[page.p3_index()]
+            .flags()
+            .contains(PageTableFlags::HUGE_PAGE)
+        {
+            return Err(FlagUpdateError::PageSizeMismatch);
+        }
         p3[page.p3_index()].set_flags(flags | PageTableFlags::HUGE_PAGE);
 
         Ok(MapperFlush::new(page))
@@ -215,7 +365,7 @@ impl<P: PageTableFrameMapping> Mapper<Size1GiB> for MappedPageTable<'_, P> {
         page: Page<Size1GiB>,
         flags: PageTableFlags,
     ) -> Result<MapperFlushAll, FlagUpdateError> {
-        let p4 = &mut self.level_4_table;
+        let (p4, _) = self.p4_table_mut(page.start_address())?;
         let p4_entry = &mut p4[page.p4_index()];
 
         if p4_entry.is_unused() {
@@ -244,8 +394,8 @@ impl<P: PageTableFrameMapping> Mapper<Size1GiB> for MappedPageTable<'_, P> {
     }
 
     fn translate_page(&self, page: Page<Size1GiB>) -> Result<PhysFrame<Size1GiB>, TranslateError> {
-        let p4 = &self.level_4_table;
-        let p3 = self.page_table_walker.next_table(&p4[page.p4_index()])?;
+        let (p4, walker) = self.p4_table(page.start_address())?;
+        let p3 = walker.next_table(&p4[page.p4_index()])?;
 
         let p3_entry = &p3[page.p3_index()];
 
@@ -274,17 +424,22 @@ impl<P: PageTableFrameMapping> Mapper<Size2MiB> for MappedPageTable<'_, P> {
         self.map_to_2mib(page, frame, flags, parent_table_flags, allocator)
     }
 
+    #[inline]
     fn unmap(
         &mut self,
         page: Page<Size2MiB>,
     ) -> Result<(PhysFrame<Size2MiB>, MapperFlush<Size2MiB>), UnmapError> {
-        let p4 = &mut self.level_4_table;
-        let p3 = self
-            .page_table_walker
-            .next_table_mut(&mut p4[page.p4_index()])?;
-        let p2 = self
-            .page_table_walker
-            .next_table_mut(&mut p3[page.p3_index()])?;
+        self.unmap_with_flags(page)
+            .map(|(frame, _, flush)| (frame, flush))
+    }
+
+    fn unmap_with_flags(
+        &mut self,
+        page: Page<Size2MiB>,
+    ) -> Result<(PhysFrame<Size2MiB>, PageTableFlags, MapperFlush<Size2MiB>), UnmapError> {
+        let (p4, walker) = self.p4_table_mut(page.start_address())?;
+        let p3 = walker.next_table_mut(&mut p4[page.p4_index()])?;
+        let p2 = walker.next_table_mut(&mut p3[page.p3_index()])?;
 
         let p2_entry = &mut p2[page.p2_index()];
         let flags = p2_entry.flags();
@@ -300,7 +455,7 @@ impl<P: PageTableFrameMapping> Mapper<Size2MiB> for MappedPageTable<'_, P> {
             .map_err(|AddressNotAligned| UnmapError::InvalidFrameAddress(p2_entry.addr()))?;
 
         p2_entry.set_unused();
-        Ok((frame, MapperFlush::new(page)))
+        Ok((frame, flags, MapperFlush::new(page)))
     }
 
     unsafe fn update_flags(
@@ -308,17 +463,19 @@ impl<P: PageTableFrameMapping> Mapper<Size2MiB> for MappedPageTable<'_, P> {
         page: Page<Size2MiB>,
         flags: PageTableFlags,
     ) -> Result<MapperFlush<Size2MiB>, FlagUpdateError> {
-        let p4 = &mut self.level_4_table;
-        let p3 = self
-            .page_table_walker
-            .next_table_mut(&mut p4[page.p4_index()])?;
-        let p2 = self
-            .page_table_walker
-            .next_table_mut(&mut p3[page.p3_index()])?;
+        let (p4, walker) = self.p4_table_mut(page.start_address())?;
+        let p3 = walker.next_table_mut(&mut p4[page.p4_index()])?;
+        let p2 = walker.next_table_mut(&mut p3[page.p3_index()])?;
 
         if p2[page.p2_index()].is_unused() {
             return Err(FlagUpdateError::PageNotMapped);
         }
+        if !p2[page.p2_index()]
+            .flags()
+            .contains(PageTableFlags::HUGE_PAGE)
+        {
+            return Err(FlagUpdateError::PageSizeMismatch);
+        }
 
         p2[page.p2_index()].set_flags(flags | PageTableFlags::HUGE_PAGE);
 
@@ -330,7 +487,7 @@ impl<P: PageTableFrameMapping> Mapper<Size2MiB> for MappedPageTable<'_, P> {
         page: Page<Size2MiB>,
         flags: PageTableFlags,
     ) -> Result<MapperFlushAll, FlagUpdateError> {
-        let p4 = &mut self.level_4_table;
+        let (p4, _) = self.p4_table_mut(page.start_address())?;
         let p4_entry = &mut p4[page.p4_index()];
 
         if p4_entry.is_unused() {
@@ -347,10 +504,8 @@ impl<P: PageTableFrameMapping> Mapper<Size2MiB> for MappedPageTable<'_, P> {
         page: Page<Size2MiB>,
         flags: PageTableFlags,
     ) -> Result<MapperFlushAll, FlagUpdateError> {
-        let p4 = &mut self.level_4_table;
-        let p3 = self
-            .page_table_walker
-            .next_table_mut(&mut p4[page.p4_index()])?;
+        let (p4, walker) = self.p4_table_mut(page.start_address())?;
+        let p3 = walker.next_table_mut(&mut p4[page.p4_index()])?;
         let p3_entry = &mut p3[page.p3_index()];
 
         if p3_entry.is_unused() {
@@ -371,9 +526,9 @@ impl<P: PageTableFrameMapping> Mapper<Size2MiB> for MappedPageTable<'_, P> {
     }
 
     fn translate_page(&self, page: Page<Size2MiB>) -> Result<PhysFrame<Size2MiB>, TranslateError> {
-        let p4 = &self.level_4_table;
-        let p3 = self.page_table_walker.next_table(&p4[page.p4_index()])?;
-        let p2 = self.page_table_walker.next_table(&p3[page.p3_index()])?;
+        let (p4, walker) = self.p4_table(page.start_address())?;
+        let p3 = walker.next_table(&p4[page.p4_index()])?;
+        let p2 = walker.next_table(&p3[page.p3_index()])?;
 
         let p2_entry = &p2[page.p2_index()];
 
@@ -402,20 +557,23 @@ impl<P: PageTableFrameMapping> Mapper<Size4KiB> for MappedPageTable<'_, P> {
         self.map_to_4kib(page, frame, flags, parent_table_flags, allocator)
     }
 
+    #[inline]
     fn unmap(
         &mut self,
         page: Page<Size4KiB>,
     ) -> Result<(PhysFrame<Size4KiB>, MapperFlush<Size4KiB>), UnmapError> {
-        let p4 = &mut self.level_4_table;
-        let p3 = self
-            .page_table_walker
-            .next_table_mut(&mut p4[page.p4_index()])?;
-        let p2 = self
-            .page_table_walker
-            .next_table_mut(&mut p3[page.p3_index()])?;
-        let p1 = self
-            .page_table_walker
-            .next_table_mut(&mut p2[page.p2_index()])?;
+        self.unmap_with_flags(page)
+            .map(|(frame, _, flush)| (frame, flush))
+    }
+
+    fn unmap_with_flags(
+        &mut self,
+        page: Page<Size4KiB>,
+    ) -> Result<(PhysFrame<Size4KiB>, PageTableFlags, MapperFlush<Size4KiB>), UnmapError> {
+        let (p4, walker) = self.p4_table_mut(page.start_address())?;
+        let p3 = walker.next_table_mut(&mut p4[page.p4_index()])?;
+        let p2 = walker.next_table_mut(&mut p3[page.p3_index()])?;
+        let p1 = walker.next_table_mut(&mut p2[page.p2_index()])?;
 
         let p1_entry = &mut p1[page.p1_index()];
 
@@ -425,8 +583,9 @@ impl<P: PageTableFrameMapping> Mapper<Size4KiB> for MappedPageTable<'_, P> {
             FrameError::HugeFrame => unreachable!(),
         })?;
 
+        let flags = p1_entry.flags();
         p1_entry.set_unused();
-        Ok((frame, MapperFlush::new(page)))
+        Ok((frame, flags, MapperFlush::new(page)))
     }
 
     unsafe fn update_flags(
@@ -434,16 +593,10 @@ impl<P: PageTableFrameMapping> Mapper<Size4KiB> for MappedPageTable<'_, P> {
         page: Page<Size4KiB>,
         flags: PageTableFlags,
     ) -> Result<MapperFlush<Size4KiB>, FlagUpdateError> {
-        let p4 = &mut self.level_4_table;
-        let p3 = self
-            .page_table_walker
-            .next_table_mut(&mut p4[page.p4_index()])?;
-        let p2 = self
-            .page_table_walker
-            .next_table_mut(&mut p3[page.p3_index()])?;
-        let p1 = self
-            .page_table_walker
-            .next_table_mut(&mut p2[page.p2_index()])?;
+        let (p4, walker) = self.p4_table_mut(page.start_address())?;
+        let p3 = walker.next_table_mut(&mut p4[page.p4_index()])?;
+        let p2 = walker.next_table_mut(&mut p3[page.p3_index()])?;
+        let p1 = walker.next_table_mut(&mut p2[page.p2_index()])?;
 
         if p1[page.p1_index()].is_unused() {
             return Err(FlagUpdateError::PageNotMapped);
@@ -459,7 +612,7 @@ impl<P: PageTableFrameMapping> Mapper<Size4KiB> for MappedPageTable<'_, P> {
         page: Page<Size4KiB>,
         flags: PageTableFlags,
     ) -> Result<MapperFlushAll, FlagUpdateError> {
-        let p4 = &mut self.level_4_table;
+        let (p4, _) = self.p4_table_mut(page.start_address())?;
         let p4_entry = &mut p4[page.p4_index()];
 
         if p4_entry.is_unused() {
@@ -476,10 +629,8 @@ impl<P: PageTableFrameMapping> Mapper<Size4KiB> for MappedPageTable<'_, P> {
         page: Page<Size4KiB>,
         flags: PageTableFlags,
     ) -> Result<MapperFlushAll, FlagUpdateError> {
-        let p4 = &mut self.level_4_table;
-        let p3 = self
-            .page_table_walker
-            .next_table_mut(&mut p4[page.p4_index()])?;
+        let (p4, walker) = self.p4_table_mut(page.start_address())?;
+        let p3 = walker.next_table_mut(&mut p4[page.p4_index()])?;
         let p3_entry = &mut p3[page.p3_index()];
 
         if p3_entry.is_unused() {
@@ -496,13 +647,9 @@ impl<P: PageTableFrameMapping> Mapper<Size4KiB> for MappedPageTable<'_, P> {
         page: Page<Size4KiB>,
         flags: PageTableFlags,
     ) -> Result<MapperFlushAll, FlagUpdateError> {
-        let p4 = &mut self.level_4_table;
-        let p3 = self
-            .page_table_walker
-            .next_table_mut(&mut p4[page.p4_index()])?;
-        let p2 = self
-            .page_table_walker
-            .next_table_mut(&mut p3[page.p3_index()])?;
+        let (p4, walker) = self.p4_table_mut(page.start_address())?;
+        let p3 = walker.next_table_mut(&mut p4[page.p4_index()])?;
+        let p2 = walker.next_table_mut(&mut p3[page.p3_index()])?;
         let p2_entry = &mut p2[page.p2_index()];
 
         if p2_entry.is_unused() {
@@ -515,10 +662,10 @@ impl<P: PageTableFrameMapping> Mapper<Size4KiB> for MappedPageTable<'_, P> {
     }
 
     fn translate_page(&self, page: Page<Size4KiB>) -> Result<PhysFrame<Size4KiB>, TranslateError> {
-        let p4 = &self.level_4_table;
-        let p3 = self.page_table_walker.next_table(&p4[page.p4_index()])?;
-        let p2 = self.page_table_walker.next_table(&p3[page.p3_index()])?;
-        let p1 = self.page_table_walker.next_table(&p2[page.p2_index()])?;
+        let (p4, walker) = self.p4_table(page.start_address())?;
+        let p3 = walker.next_table(&p4[page.p4_index()])?;
+        let p2 = walker.next_table(&p3[page.p3_index()])?;
+        let p1 = walker.next_table(&p2[page.p2_index()])?;
 
         let p1_entry = &p1[page.p1_index()];
 
@@ -534,15 +681,21 @@ impl<P: PageTableFrameMapping> Mapper<Size4KiB> for MappedPageTable<'_, P> {
 impl<P: PageTableFrameMapping> Translate for MappedPageTable<'_, P> {
     #[allow(clippy::inconsistent_digit_grouping)]
     fn translate(&self, addr: VirtAddr) -> TranslateResult {
-        let p4 = &self.level_4_table;
-        let p3 = match self.page_table_walker.next_table(&p4[addr.p4_index()]) {
+        let (p4, walker) = match self.p4_table(addr) {
+            Ok(tables) => tables,
+            Err(PageTableWalkError::NotMapped) => return TranslateResult::NotMapped,
+            Err(PageTableWalkError::MappedToHugePage) => {
+                panic!("level 5 entry has huge page bit set")
+            }
+        };
+        let p3 = match walker.next_table(&p4[addr.p4_index()]) {
             Ok(page_table) => page_table,
             Err(PageTableWalkError::NotMapped) => return TranslateResult::NotMapped,
             Err(PageTableWalkError::MappedToHugePage) => {
                 panic!("level 4 entry has huge page bit set")
             }
         };
-        let p2 = match self.page_table_walker.next_table(&p3[addr.p3_index()]) {
+        let p2 = match walker.next_table(&p3[addr.p3_index()]) {
             Ok(page_table) => page_table,
             Err(PageTableWalkError::NotMapped) => return TranslateResult::NotMapped,
             Err(PageTableWalkError::MappedToHugePage) => {
@@ -558,7 +711,7 @@ impl<P: PageTableFrameMapping> Translate for MappedPageTable<'_, P> {
                 };
             }
         };
-        let p1 = match self.page_table_walker.next_table(&p2[addr.p2_index()]) {
+        let p1 = match walker.next_table(&p2[addr.p2_index()]) {
             Ok(page_table) => page_table,
             Err(PageTableWalkError::NotMapped) => return TranslateResult::NotMapped,
             Err(PageTableWalkError::MappedToHugePage) => {
@@ -677,9 +830,55 @@ impl<P: PageTableFrameMapping> CleanUp for MappedPageTable<'_, P> {
             page_table.iter().all(PageTableEntry::is_unused)
         }
 
+        #[cfg(feature = "la57")]
+        if self.five_level {
+            if range.is_empty() {
+                return;
+            }
+
+            let offset_per_entry = PageTableLevel::Four.table_address_space_alignment();
+            let start = usize::from(range.start.p5_index());
+            let end = usize::from(range.end.p5_index());
+            for (i, entry) in self
+                .top_level_table
+                .iter_mut()
+                .enumerate()
+                .take(end + 1)
+                .skip(start)
+            {
+                if let Ok(page_table) = self.page_table_walker.next_table_mut(entry) {
+                    let entry_start = VirtAddr::forward_checked_impl(
+                        VirtAddr::zero(),
+                        (offset_per_entry as usize) * i,
+                    )
+                    .unwrap();
+                    let entry_end = entry_start + (offset_per_entry - 1);
+                    let entry_start = Page::<Size4KiB>::containing_address(entry_start);
+                    let entry_end = Page::<Size4KiB>::containing_address(entry_end);
+                    unsafe {
+                        if clean_up(
+                            page_table,
+                            &self.page_table_walker,
+                            PageTableLevel::Four,
+                            Page::range_inclusive(
+                                entry_start.max(range.start),
+                                entry_end.min(range.end),
+                            ),
+                            frame_deallocator,
+                        ) {
+                            let frame = entry.frame().unwrap();
+                            entry.set_unused();
+                            frame_deallocator.deallocate_frame(frame);
+                        }
+                    }
+                }
+            }
+            return;
+        }
+
         unsafe {
             clean_up(
-                self.level_4_table,
+                self.top_level_table,
                 &self.page_table_walker,
                 PageTableLevel::Four,
                 range,
@@ -689,6 +888,113 @@ impl<P: PageTableFrameMapping> CleanUp for MappedPageTable<'_, P> {
     }
 }
 
+/// A present page table entry, as returned by [`MappedPageTable::walk`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageTableWalkEntry {
+    /// The level of the page table that contains the entry (5 for the level 5 table, 1 for a
+    /// level 1 table).
+    pub level: u8,
+    /// The index of the entry in its page table.
+    pub index: PageTableIndex,
+    /// The start address of the virtual memory region covered by the entry.
+    pub addr: VirtAddr,
+    /// The frame of the next level page table, or the (first) mapped frame for leaf entries.
+    pub frame: PhysFrame,
+    /// The flags of the entry.
+    pub flags: PageTableFlags,
+}
+
+impl PageTableWalkEntry {
+    /// Returns whether the entry maps a page instead of pointing to the next level page table.
+    #[inline]
+    pub fn is_leaf(&self) -> bool {
+        self.level == 1
+            || ((self.level == 2 || self.level == 3)
+                && self.flags.contains(PageTableFlags::HUGE_PAGE))
+    }
+}
+
+/// The iterator returned by [`MappedPageTable::walk`].
+///
+/// Keeps one table and the next index to look at for each level that is currently being
+/// visited, starting with the top level table at position 0.
+struct PageTableWalk<'a, P: PageTableFrameMapping> {
+    walker: &'a PageTableWalker<P>,
+    top_level: u8,
+    depth: usize,
+    tables: [Option<&'a PageTable>; 5],
+    next_index: [u16; 5],
+    base: [u64; 5],
+}
+
+impl<P: PageTableFrameMapping> Iterator for PageTableWalk<'_, P> {
+    type Item = PageTableWalkEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.depth > 0 {
+            let pos = self.depth - 1;
+            let index = self.next_index[pos];
+            if index == 512 {
+                self.depth -= 1;
+                continue;
+            }
+            self.next_index[pos] += 1;
+
+            let entry = &self.tables[pos].unwrap()[usize::from(index)];
+            let flags = entry.flags();
+            if !flags.contains(PageTableFlags::PRESENT) {
+                continue;
+            }
+
+            let level = self.top_level - pos as u8;
+            let shift = 12 + 9 * (u32::from(level) - 1);
+            let addr = self.base[pos] | (u64::from(index) << shift);
+            let entry_info = PageTableWalkEntry {
+                level,
+                index: PageTableIndex::new(index),
+                addr: VirtAddr::new_truncate(sign_extend(addr, self.top_level)),
+                frame: PhysFrame::containing_address(entry.addr()),
+                flags,
+            };
+
+            if entry_info.is_leaf() {
+                // Bit 12 is the PAT bit for huge pages, so it is not part of the address.
+                let frame_addr = entry.addr().align_down(1u64 << shift);
+                return Some(PageTableWalkEntry {
+                    frame: PhysFrame::containing_address(frame_addr),
+                    ..entry_info
+                });
+            }
+
+            if let Ok(table) = self.walker.next_table(entry) {
+                self.tables[self.depth] = Some(table);
+                self.next_index[self.depth] = 0;
+                self.base[self.depth] = addr;
+                self.depth += 1;
+            }
+            return Some(entry_info);
+        }
+        None
+    }
+}
+
+impl<P: PageTableFrameMapping> fmt::Debug for PageTableWalk<'_, P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PageTableWalk")
+            .field("top_level", &self.top_level)
+            .field("depth", &self.depth)
+            .field("next_index", &self.next_index)
+            .finish()
+    }
+}
+
+/// Sign extends the given address from the highest bit that is translated by a page table
+/// hierarchy with the given number of levels.
+fn sign_extend(addr: u64, levels: u8) -> u64 {
+    let unused_bits = 64 - (12 + 9 * u32::from(levels));
+    ((addr << unused_bits) as i64 >> unused_bits) as u64
+}
+
 #[derive(Debug)]
 struct PageTableWalker<P: PageTableFrameMapping> {
     page_table_frame_mapping: P,
@@ -1,6 +1,6 @@
 //! Abstractions for reading and modifying the mapping of pages.
 
-pub use self::mapped_page_table::{MappedPageTable, PageTableFrameMapping};
+pub use self::mapped_page_table::{MappedPageTable, PageTableFrameMapping, PageTableWalkEntry};
 #[cfg(target_pointer_width = "64")]
 pub use self::offset_page_table::OffsetPageTable;
 #[cfg(all(feature = "instructions", target_arch = "x86_64"))]
@@ -13,6 +13,7 @@ use crate::structures::paging::{
     Page, PageSize, PhysFrame, Size1GiB, Size2MiB, Size4KiB,
 };
 use crate::{PhysAddr, VirtAddr};
+use core::cmp;
 
 mod mapped_page_table;
 mod offset_page_table;
@@ -52,9 +53,10 @@ pub trait Translate {
 
 /// The return value of the [`Translate::translate`] function.
 ///
-/// If the given address has a valid mapping, a `Frame4KiB`, `Frame2MiB`, or `Frame1GiB` variant
-/// is returned, depending on the size of the mapped page. The remaining variants indicate errors.
-#[derive(Debug)]
+/// If the given address has a valid mapping, the `Mapped` variant is returned. It contains the
+/// mapped frame, whose [`MappedFrame`] variant depends on the size of the mapped page. The
+/// remaining variants indicate errors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TranslateResult {
     /// The virtual address is mapped to a physical frame.
     Mapped {
@@ -76,7 +78,7 @@ pub enum TranslateResult {
 }
 
 /// Represents a physical frame mapped in a page table.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MappedFrame {
     /// The virtual address is mapped to a 4KiB frame.
     Size4KiB(PhysFrame<Size4KiB>),
@@ -284,6 +286,36 @@ pub trait Mapper<S: PageSize> {
     /// Note that no page tables or pages are deallocated.
     fn unmap(&mut self, page: Page<S>) -> Result<(PhysFrame<S>, MapperFlush<S>), UnmapError>;
 
+    /// Removes a mapping from the page table and returns the frame that used to be mapped,
+    /// together with the flags of the removed page table entry.
+    ///
+    /// This is useful when the old flags decide what happens next with the frame, e.g. for
+    /// copy-on-write or swapping.
+    ///
+    /// The default implementation looks up the flags with [`Translate::translate`] before
+    /// calling [`unmap`](Self::unmap). All mappers of this crate override it to read the
+    /// flags from the entry that is removed.
+    ///
+    /// Note that no page tables or pages are deallocated.
+    #[inline]
+    fn unmap_with_flags(
+        &mut self,
+        page: Page<S>,
+    ) -> Result<(PhysFrame<S>, PageTableFlags, MapperFlush<S>), UnmapError>
+    where
+        Self: Translate,
+    {
+        let flags = match self.translate(page.start_address()) {
+            TranslateResult::Mapped { flags, .. } => flags,
+            // `unmap` returns the appropriate error in this case.
+            TranslateResult::NotMapped | TranslateResult::InvalidFrameAddress(_) => {
+                PageTableFlags::empty()
+            }
+        };
+        let (frame, flush) = self.unmap(page)?;
+        Ok((frame, flags, flush))
+    }
+
     /// Updates the flags of an existing mapping.
     ///
     /// To read the current flags of a mapped page, use the [`Translate::translate`] method.
@@ -303,6 +335,15 @@ pub trait Mapper<S: PageSize> {
 
     /// Set the flags of an existing page level 4 table entry
     ///
+    /// The flags of a parent entry restrict the access to all pages mapped through it (up to
+    /// 512 GiB), so this can be used to e.g. set `USER_ACCESSIBLE` for a complete subtree. The
+    /// given flags replace the existing flags of the entry, so they should usually include
+    /// `PRESENT` and the existing flags (see e.g. [`MappedPageTable::walk`]).
+    ///
+    /// Clearing `PRESENT` makes all pages mapped through the entry inaccessible without
+    /// freeing the lower level page tables, and they can't be used by this mapper anymore until
+    /// `PRESENT` is set again.
+    ///
     /// ## Safety
     ///
     /// This method is unsafe because changing the flags of a mapping
@@ -318,6 +359,9 @@ pub trait Mapper<S: PageSize> {
 
     /// Set the flags of an existing page table level 3 entry
     ///
+    /// This affects all pages mapped through the entry (up to 1 GiB), see
+    /// [`set_flags_p4_entry`](Mapper::set_flags_p4_entry) for details.
+    ///
     /// ## Safety
     ///
     /// This method is unsafe because changing the flags of a mapping
@@ -333,6 +377,9 @@ pub trait Mapper<S: PageSize> {
 
     /// Set the flags of an existing page table level 2 entry
     ///
+    /// This affects all pages mapped through the entry (up to 2 MiB), see
+    /// [`set_flags_p4_entry`](Mapper::set_flags_p4_entry) for details.
+    ///
     /// ## Safety
     ///
     /// This method is unsafe because changing the flags of a mapping
@@ -358,6 +405,9 @@ pub trait Mapper<S: PageSize> {
     ///
     /// This is a convencience function that invokes [`Mapper::map_to`] internally, so
     /// all safety requirements of it also apply for this function.
+    ///
+    /// Returns [`MapToError::NonCanonicalAddress`] if the start address of the frame is not a
+    /// canonical virtual address.
     #[inline]
     unsafe fn identity_map<A>(
         &mut self,
@@ -371,7 +421,9 @@ pub trait Mapper<S: PageSize> {
         S: PageSize,
         Self: Mapper<S>,
     {
-        let page = Page::containing_address(VirtAddr::new(frame.start_address().as_u64()));
+        let addr = VirtAddr::try_new(frame.start_address().as_u64())
+            .map_err(|_| MapToError::NonCanonicalAddress)?;
+        let page = Page::containing_address(addr);
         unsafe { self.map_to(page, frame, flags, frame_allocator) }
     }
 }
@@ -448,6 +500,114 @@ impl MapperFlushAll {
     pub fn ignore(self) {}
 }
 
+/// This type combines the flush promises of multiple page table changes.
+///
+/// Flushing every changed page with `MapperFlush::flush` requires one `invlpg` per page. This
+/// type instead collects the [`MapperFlush`] promises into a single range of pages, which is then
+/// flushed either page by page or, if it is larger than a threshold, by flushing the complete
+/// TLB. Like [`MapperFlush`], it must be flushed or ignored, so it is neither `Clone` nor
+/// `Copy`.
+///
+/// ```
+/// # #[cfg(all(feature = "instructions", target_arch = "x86_64"))]
+/// # fn example(
+/// #     mapper: &mut impl x86_64::structures::paging::Mapper<x86_64::structures::paging::Size4KiB>,
+/// #     frame_allocator: &mut impl x86_64::structures::paging::FrameAllocator<x86_64::structures::paging::Size4KiB>,
+/// #     pages: x86_64::structures::paging::page::PageRange,
+/// #     frame: x86_64::structures::paging::PhysFrame,
+/// # ) -> Result<(), x86_64::structures::paging::mapper::MapToError<x86_64::structures::paging::Size4KiB>> {
+/// use x86_64::structures::paging::{mapper::MapperFlushRange, PageTableFlags};
+///
+/// let mut flush = MapperFlushRange::new();
+/// for (i, page) in pages.enumerate() {
+///     let frame = frame + i as u64;
+///     let flags = PageTableFlags::PRESENT | PageTableFlags::WRITABLE;
+///     flush.add(unsafe { mapper.map_to(page, frame, flags, frame_allocator)? });
+/// }
+/// // reload CR3 if more than 32 pages were mapped
+/// flush.flush(32);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+#[must_use = "Page Table changes must be flushed or ignored."]
+pub struct MapperFlushRange<S: PageSize> {
+    range: Option<PageRangeInclusive<S>>,
+}
+
+impl<S: PageSize> MapperFlushRange<S> {
+    /// Create a new flush promise that doesn't contain any pages.
+    #[inline]
+    pub const fn new() -> Self {
+        MapperFlushRange { range: None }
+    }
+
+    /// Add the page of the given flush promise to the pages that need to be flushed.
+    #[inline]
+    pub fn add(&mut self, flush: MapperFlush<S>) {
+        let page = flush.page();
+        self.range = Some(match self.range {
+            Some(range) => {
+                Page::range_inclusive(cmp::min(range.start, page), cmp::max(range.end, page))
+            }
+            None => Page::range_inclusive(page, page),
+        });
+    }
+
+    /// Returns the smallest range containing all pages that need to be flushed, or `None`
+    /// if no pages were added.
+    #[inline]
+    pub fn range(&self) -> Option<PageRangeInclusive<S>> {
+        self.range
+    }
+
+    /// Flush the pages from the TLB to ensure that the newest mappings are used.
+    ///
+    /// If the [`range`](Self::range) contains more than `threshold` pages (including the pages
+    /// between the added ones), or if it spans both halves of the address space, the complete
+    /// TLB is flushed instead (see
+    /// [`flush_range_or_all`](crate::instructions::tlb::flush_range_or_all)).
+    #[cfg(all(feature = "instructions", target_arch = "x86_64"))]
+    #[inline]
+    pub fn flush(self, threshold: usize) {
+        use crate::instructions::tlb;
+
+        if let Some(range) = self.range {
+            let start = range.start.start_address().as_u64();
+            let end = range.end.start_address().as_u64();
+            // Pages in different halves are separated by the non-canonical hole, so they can't
+            // be flushed one by one.
+            let same_half = (start ^ end) >> 63 == 0;
+            let count = (end - start) / S::SIZE + 1;
+            if same_half && count <= threshold as u64 {
+                tlb::flush_range(range.start.start_address(), count as usize, S::SIZE);
+            } else {
+                tlb::flush_all();
+            }
+        }
+    }
+
+    /// Don't flush the TLB and silence the “must be used” warning.
+    #[inline]
+    pub fn ignore(self) {}
+}
+
+impl<S: PageSize> Default for MapperFlushRange<S> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: PageSize> Extend<MapperFlush<S>> for MapperFlushRange<S> {
+    #[inline]
+    fn extend<T: IntoIterator<Item = MapperFlush<S>>>(&mut self, iter: T) {
+        for flush in iter {
+            self.add(flush);
+        }
+    }
+}
+
 /// This error is returned from `map_to` and similar methods.
 #[derive(Debug)]
 pub enum MapToError<S: PageSize> {
@@ -459,6 +619,12 @@ pub enum MapToError<S: PageSize> {
     ParentEntryHugePage,
     /// The given page is already mapped to a physical frame.
     PageAlreadyMapped(PhysFrame<S>),
+    /// The page table entry for the given huge page already points to a lower level page
+    /// table, so mapping the huge page would discard the mappings of that table.
+    EntryPointsToPageTable,
+    /// The physical address passed to [`Mapper::identity_map`] is not a canonical virtual
+    /// address, so it can't be mapped to the page with the same address.
+    NonCanonicalAddress,
 }
 
 /// An error indicating that an `unmap` call failed.
@@ -481,6 +647,9 @@ pub enum FlagUpdateError {
     /// An upper level page table entry has the `HUGE_PAGE` flag set, which means that the
     /// given page is part of a huge page and can't be freed individually.
     ParentEntryHugePage,
+    /// The page table entry for the given huge page points to a lower level page table,
+    /// which means that the page is mapped with a smaller page size.
+    PageSizeMismatch,
 }
 
 /// An error indicating that an `translate` call failed.
@@ -538,3 +707,39 @@ pub trait CleanUp {
     ) where
         D: FrameDeallocator<Size4KiB>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mapper_flush_range() {
+        let page = |addr| Page::<Size4KiB>::containing_address(VirtAddr::new(addr));
+
+        let mut flush = MapperFlushRange::new();
+        assert_eq!(flush.range(), None);
+        flush.add(MapperFlush::new(page(0x5000)));
+        assert_eq!(
+            flush.range(),
+            Some(Page::range_inclusive(page(0x5000), page(0x5000)))
+        );
+        flush.extend([
+            MapperFlush::new(page(0x2000)),
+            MapperFlush::new(page(0x3000)),
+        ]);
+        assert_eq!(
+            flush.range(),
+            Some(Page::range_inclusive(page(0x2000), page(0x5000)))
+        );
+        flush.ignore();
+    }
+
+    #[test]
+    fn mapper_flush_range_last_page() {
+        let last = Page::<Size4KiB>::containing_address(VirtAddr::new(u64::MAX));
+        let mut flush = MapperFlushRange::new();
+        flush.add(MapperFlush::new(last));
+        assert_eq!(flush.range(), Some(Page::range_inclusive(last, last)));
+        flush.ignore();
+    }
+}
@@ -35,20 +35,75 @@ impl<'a> OffsetPageTable<'a> {
         }
     }
 
+    /// Creates a new `OffsetPageTable` for 5-level paging that uses the given offset for
+    /// converting virtual to physical addresses.
+    ///
+    /// The same requirements for the physical memory mapping apply as for [`new`](Self::new).
+    ///
+    /// ## Safety
+    ///
+    /// This function is unsafe because the caller must guarantee that the passed `phys_offset`
+    /// is correct. Also, the passed `level_5_table` must point to the level 5 page table
+    /// of a valid page table hierarchy. Otherwise this function might break memory safety, e.g.
+    /// by writing to an illegal memory location.
+    #[cfg(feature = "la57")]
+    #[inline]
+    pub unsafe fn new_level_5(level_5_table: &'a mut PageTable, phys_offset: VirtAddr) -> Self {
+        let phys_offset = PhysOffset {
+            offset: phys_offset,
+        };
+        Self {
+            inner: unsafe { MappedPageTable::new_level_5(level_5_table, phys_offset) },
+        }
+    }
+
     /// Returns an immutable reference to the wrapped level 4 `PageTable` instance.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the mapper was created with `new_level_5`. Use
+    /// [`top_level_table`](Self::top_level_table) instead.
     pub fn level_4_table(&self) -> &PageTable {
         self.inner.level_4_table()
     }
 
     /// Returns a mutable reference to the wrapped level 4 `PageTable` instance.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the mapper was created with `new_level_5`. Use
+    /// [`top_level_table_mut`](Self::top_level_table_mut) instead.
     pub fn level_4_table_mut(&mut self) -> &mut PageTable {
         self.inner.level_4_table_mut()
     }
 
+    /// Returns an immutable reference to the wrapped top level `PageTable` instance.
+    ///
+    /// This is the level 5 table if the mapper was created with `new_level_5` and the
+    /// level 4 table otherwise.
+    pub fn top_level_table(&self) -> &PageTable {
+        self.inner.top_level_table()
+    }
+
+    /// Returns a mutable reference to the wrapped top level `PageTable` instance.
+    ///
+    /// This is the level 5 table if the mapper was created with `new_level_5` and the
+    /// level 4 table otherwise.
+    pub fn top_level_table_mut(&mut self) -> &mut PageTable {
+        self.inner.top_level_table_mut()
+    }
+
     /// Returns the offset used for converting virtual to physical addresses.
     pub fn phys_offset(&self) -> VirtAddr {
         self.inner.page_table_frame_mapping().offset
     }
+
+    /// Returns an iterator over all present entries of the page table hierarchy.
+    ///
+    /// See [`MappedPageTable::walk`] for details.
+    pub fn walk(&self) -> impl Iterator<Item = PageTableWalkEntry> + '_ {
+        self.inner.walk()
+    }
 }
 
 #[derive(Debug)]
@@ -92,6 +147,14 @@ impl Mapper<Size1GiB> for OffsetPageTable<'_> {
         self.inner.unmap(page)
     }
 
+    #[inline]
+    fn unmap_with_flags(
+        &mut self,
+        page: Page<Size1GiB>,
+    ) -> Result<(PhysFrame<Size1GiB>, PageTableFlags, MapperFlush<Size1GiB>), UnmapError> {
+        self.inner.unmap_with_flags(page)
+    }
+
     #[inline]
     unsafe fn update_flags(
         &mut self,
@@ -161,6 +224,14 @@ impl Mapper<Size2MiB> for OffsetPageTable<'_> {
         self.inner.unmap(page)
     }
 
+    #[inline]
+    fn unmap_with_flags(
+        &mut self,
+        page: Page<Size2MiB>,
+    ) -> Result<(PhysFrame<Size2MiB>, PageTableFlags, MapperFlush<Size2MiB>), UnmapError> {
+        self.inner.unmap_with_flags(page)
+    }
+
     #[inline]
     unsafe fn update_flags(
         &mut self,
@@ -230,6 +301,14 @@ impl Mapper<Size4KiB> for OffsetPageTable<'_> {
         self.inner.unmap(page)
     }
 
+    #[inline]
+    fn unmap_with_flags(
+        &mut self,
+        page: Page<Size4KiB>,
+    ) -> Result<(PhysFrame<Size4KiB>, PageTableFlags, MapperFlush<Size4KiB>), UnmapError> {
+        self.inner.unmap_with_flags(page)
+    }
+
     #[inline]
     unsafe fn update_flags(
         &mut self,
@@ -299,3 +378,421 @@ impl CleanUp for OffsetPageTable<'_> {
         unsafe { self.inner.clean_up_addr_range(range, frame_deallocator) }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structures::paging::page_table::{PageTable, PageTableEntry};
+    use crate::PhysAddr;
+
+    /// Allocates page tables on the heap. Since the page tables are accessed
+    /// with a physical offset of zero, the physical address of a frame is its
+    /// host address.
+    struct LeakingFrameAllocator;
+
+    unsafe impl FrameAllocator<Size4KiB> for LeakingFrameAllocator {
+        fn allocate_frame(&mut self) -> Option<PhysFrame> {
+            let table: *mut PageTable = Box::leak(Box::new(PageTable::new()));
+            Some(PhysFrame::containing_address(PhysAddr::new(table as u64)))
+        }
+    }
+
+    /// Records the frames that are handed back instead of freeing them.
+    #[derive(Default)]
+    struct RecordingFrameDeallocator(Vec<PhysFrame>);
+
+    impl FrameDeallocator<Size4KiB> for RecordingFrameDeallocator {
+        unsafe fn deallocate_frame(&mut self, frame: PhysFrame) {
+            self.0.push(frame);
+        }
+    }
+
+    fn new_page_table() -> OffsetPageTable<'static> {
+        let level_4_table = Box::leak(Box::new(PageTable::new()));
+        unsafe { OffsetPageTable::new(level_4_table, VirtAddr::zero()) }
+    }
+
+    #[test]
+    fn map_1gib_page() {
+        let mut page_table = new_page_table();
+        let page = Page::<Size1GiB>::containing_address(VirtAddr::new(0x40_0000_0000));
+        let frame = PhysFrame::<Size1GiB>::containing_address(PhysAddr::new(0x8000_0000));
+        let flags = PageTableFlags::PRESENT | PageTableFlags::WRITABLE;
+        unsafe { page_table.map_to(page, frame, flags, &mut LeakingFrameAllocator) }
+            .unwrap()
+            .ignore();
+
+        let addr = page.start_address() + 0x3FFF_FFFFu64;
+        assert_eq!(
+            page_table.translate_addr(addr),
+            Some(PhysAddr::new(0x8000_0000 + 0x3FFF_FFFF))
+        );
+        match page_table.translate(addr) {
+            TranslateResult::Mapped {
+                frame: MappedFrame::Size1GiB(mapped),
+                offset,
+                flags: mapped_flags,
+            } => {
+                assert_eq!(mapped, frame);
+                assert_eq!(offset, 0x3FFF_FFFF);
+                assert_eq!(mapped_flags, flags | PageTableFlags::HUGE_PAGE);
+            }
+            result => panic!("unexpected translation result {:?}", result),
+        }
+        assert_eq!(page_table.translate_page(page).unwrap(), frame);
+    }
+
+    #[test]
+    fn map_to_with_table_flags() {
+        let mut page_table = new_page_table();
+        let page = Page::<Size4KiB>::containing_address(VirtAddr::new(0x20_3000));
+        let frame = PhysFrame::containing_address(PhysAddr::new(0x6000));
+        let flags = PageTableFlags::PRESENT
+            | PageTableFlags::WRITABLE
+            | PageTableFlags::USER_ACCESSIBLE
+            | PageTableFlags::NO_EXECUTE;
+        let parent_flags = PageTableFlags::PRESENT | PageTableFlags::USER_ACCESSIBLE;
+        unsafe {
+            page_table.map_to_with_table_flags(
+                page,
+                frame,
+                flags,
+                parent_flags,
+                &mut LeakingFrameAllocator,
+            )
+        }
+        .unwrap()
+        .ignore();
+
+        let entries: Vec<_> = page_table.walk().collect();
+        assert_eq!(entries.len(), 4);
+        for entry in &entries[..3] {
+            assert!(!entry.is_leaf());
+            assert!(entry.flags.contains(parent_flags));
+            assert!(!entry.flags.contains(PageTableFlags::NO_EXECUTE));
+        }
+        assert!(entries[3].is_leaf());
+        assert_eq!(entries[3].flags, flags);
+
+        // Flags for existing parent entries are added to the existing ones.
+        let page = Page::<Size4KiB>::containing_address(VirtAddr::new(0x20_4000));
+        unsafe {
+            page_table.map_to_with_table_flags(
+                page,
+                frame,
+                flags,
+                PageTableFlags::PRESENT | PageTableFlags::WRITABLE,
+                &mut LeakingFrameAllocator,
+            )
+        }
+        .unwrap()
+        .ignore();
+        for entry in page_table.walk().filter(|entry| !entry.is_leaf()) {
+            assert!(entry
+                .flags
+                .contains(parent_flags | PageTableFlags::WRITABLE));
+        }
+    }
+
+    #[test]
+    fn update_flags() {
+        let mut page_table = new_page_table();
+        let flags = PageTableFlags::PRESENT | PageTableFlags::WRITABLE;
+        let page = Page::<Size4KiB>::containing_address(VirtAddr::new(0x20_3000));
+        let frame = PhysFrame::containing_address(PhysAddr::new(0x6000));
+        unsafe { page_table.map_to(page, frame, flags, &mut LeakingFrameAllocator) }
+            .unwrap()
+            .ignore();
+        let huge_page = Page::<Size2MiB>::containing_address(VirtAddr::new(0x40_0000));
+        let huge_frame = PhysFrame::<Size2MiB>::containing_address(PhysAddr::new(0x20_0000));
+        unsafe { page_table.map_to(huge_page, huge_frame, flags, &mut LeakingFrameAllocator) }
+            .unwrap()
+            .ignore();
+
+        let new_flags = PageTableFlags::PRESENT | PageTableFlags::NO_EXECUTE;
+        let flush = unsafe { page_table.update_flags(page, new_flags) }.unwrap();
+        assert_eq!(flush.page(), page);
+        flush.ignore();
+        assert_eq!(
+            page_table.translate(page.start_address()),
+            TranslateResult::Mapped {
+                frame: MappedFrame::Size4KiB(frame),
+                offset: 0,
+                flags: new_flags,
+            }
+        );
+        unsafe { page_table.update_flags(huge_page, new_flags) }
+            .unwrap()
+            .ignore();
+        assert_eq!(page_table.translate_page(huge_page).unwrap(), huge_frame);
+
+        // The page sizes don't match the existing mappings.
+        let containing_huge_page = Page::<Size2MiB>::containing_address(page.start_address());
+        assert!(matches!(
+            unsafe { page_table.update_flags(containing_huge_page, flags) },
+            Err(FlagUpdateError::PageSizeMismatch)
+        ));
+        let contained_page = Page::<Size4KiB>::containing_address(huge_page.start_address());
+        assert!(matches!(
+            unsafe { page_table.update_flags(contained_page, flags) },
+            Err(FlagUpdateError::ParentEntryHugePage)
+        ));
+        let unmapped_page = Page::<Size4KiB>::containing_address(VirtAddr::new(0x20_4000));
+        assert!(matches!(
+            unsafe { page_table.update_flags(unmapped_page, flags) },
+            Err(FlagUpdateError::PageNotMapped)
+        ));
+    }
+
+    #[test]
+    fn translate_page_sizes() {
+        let mut page_table = new_page_table();
+        let flags = PageTableFlags::PRESENT | PageTableFlags::WRITABLE;
+
+        let page = Page::<Size4KiB>::containing_address(VirtAddr::new(0x20_3000));
+        let frame = PhysFrame::containing_address(PhysAddr::new(0x6000));
+        unsafe { page_table.map_to(page, frame, flags, &mut LeakingFrameAllocator) }
+            .unwrap()
+            .ignore();
+        let huge_page = Page::<Size2MiB>::containing_address(VirtAddr::new(0x40_0000));
+        let huge_frame = PhysFrame::<Size2MiB>::containing_address(PhysAddr::new(0x20_0000));
+        let huge_flags = flags | PageTableFlags::NO_EXECUTE;
+        unsafe {
+            page_table.map_to(
+                huge_page,
+                huge_frame,
+                huge_flags,
+                &mut LeakingFrameAllocator,
+            )
+        }
+        .unwrap()
+        .ignore();
+
+        assert_eq!(
+            page_table.translate(VirtAddr::new(0x20_3abc)),
+            TranslateResult::Mapped {
+                frame: MappedFrame::Size4KiB(frame),
+                offset: 0xabc,
+                flags,
+            }
+        );
+        assert_eq!(
+            page_table.translate(VirtAddr::new(0x51_2345)),
+            TranslateResult::Mapped {
+                frame: MappedFrame::Size2MiB(huge_frame),
+                offset: 0x11_2345,
+                flags: huge_flags | PageTableFlags::HUGE_PAGE,
+            }
+        );
+        assert_eq!(
+            page_table.translate(VirtAddr::new(0x20_4000)),
+            TranslateResult::NotMapped
+        );
+        assert_eq!(
+            page_table.translate(VirtAddr::new(0x8000_0000)),
+            TranslateResult::NotMapped
+        );
+    }
+
+    #[test]
+    fn map_1gib_page_over_page_table() {
+        let mut page_table = new_page_table();
+        let flags = PageTableFlags::PRESENT | PageTableFlags::WRITABLE;
+
+        let small_page = Page::<Size4KiB>::containing_address(VirtAddr::new(0x40_0000_0000));
+        let small_frame = PhysFrame::containing_address(PhysAddr::new(0x1000));
+        unsafe { page_table.map_to(small_page, small_frame, flags, &mut LeakingFrameAllocator) }
+            .unwrap()
+            .ignore();
+
+        let page = Page::<Size1GiB>::containing_address(small_page.start_address());
+        let frame = PhysFrame::<Size1GiB>::containing_address(PhysAddr::new(0x8000_0000));
+        let result = unsafe { page_table.map_to(page, frame, flags, &mut LeakingFrameAllocator) };
+        assert!(matches!(result, Err(MapToError::EntryPointsToPageTable)));
+    }
+
+    #[test]
+    fn unmap_with_flags() {
+        let mut page_table = new_page_table();
+        let page = Page::<Size4KiB>::containing_address(VirtAddr::new(0x1000));
+        let frame = PhysFrame::containing_address(PhysAddr::new(0x6000));
+        let flags = PageTableFlags::PRESENT | PageTableFlags::GLOBAL;
+        unsafe { page_table.map_to(page, frame, flags, &mut LeakingFrameAllocator) }
+            .unwrap()
+            .ignore();
+
+        let (unmapped, unmapped_flags, flush) = page_table.unmap_with_flags(page).unwrap();
+        flush.ignore();
+        assert_eq!(unmapped, frame);
+        assert_eq!(unmapped_flags, flags);
+        assert!(matches!(
+            page_table.unmap_with_flags(page),
+            Err(UnmapError::PageNotMapped)
+        ));
+    }
+
+    #[test]
+    fn clean_up_empty_tables() {
+        let mut page_table = new_page_table();
+        let flags = PageTableFlags::PRESENT | PageTableFlags::WRITABLE;
+        let page_a = Page::<Size4KiB>::containing_address(VirtAddr::new(0x1000));
+        let page_b = Page::<Size4KiB>::containing_address(VirtAddr::new(0x20_0000));
+        let frame = PhysFrame::containing_address(PhysAddr::new(0x1000));
+        for page in [page_a, page_b] {
+            unsafe { page_table.map_to(page, frame, flags, &mut LeakingFrameAllocator) }
+                .unwrap()
+                .ignore();
+        }
+
+        // Only the level 1 table of `page_a` is empty.
+        page_table.unmap(page_a).unwrap().1.ignore();
+        let mut deallocator = RecordingFrameDeallocator::default();
+        unsafe { page_table.clean_up(&mut deallocator) };
+        assert_eq!(deallocator.0.len(), 1);
+        assert_eq!(
+            page_table.translate_addr(page_b.start_address()),
+            Some(frame.start_address())
+        );
+
+        // Freeing the last level 1 table empties the level 2 and 3 tables as
+        // well, but the level 4 table must never be freed.
+        page_table.unmap(page_b).unwrap().1.ignore();
+        let mut deallocator = RecordingFrameDeallocator::default();
+        unsafe { page_table.clean_up(&mut deallocator) };
+        assert_eq!(deallocator.0.len(), 3);
+        assert!(page_table
+            .level_4_table()
+            .iter()
+            .all(PageTableEntry::is_unused));
+    }
+
+    #[test]
+    fn clean_up_addr_range() {
+        let mut page_table = new_page_table();
+        let flags = PageTableFlags::PRESENT | PageTableFlags::WRITABLE;
+        let page = Page::<Size4KiB>::containing_address(VirtAddr::new(0x1000));
+        let frame = PhysFrame::containing_address(PhysAddr::new(0x1000));
+        unsafe { page_table.map_to(page, frame, flags, &mut LeakingFrameAllocator) }
+            .unwrap()
+            .ignore();
+        page_table.unmap(page).unwrap().1.ignore();
+
+        // The range doesn't cover the empty tables.
+        let other = Page::containing_address(VirtAddr::new(0x80_0000_0000));
+        let mut deallocator = RecordingFrameDeallocator::default();
+        unsafe {
+            page_table.clean_up_addr_range(Page::range_inclusive(other, other), &mut deallocator)
+        };
+        assert!(deallocator.0.is_empty());
+
+        unsafe {
+            page_table.clean_up_addr_range(Page::range_inclusive(page, page), &mut deallocator)
+        };
+        assert_eq!(deallocator.0.len(), 3);
+    }
+
+    #[test]
+    fn identity_map() {
+        let mut page_table = new_page_table();
+        let frame = PhysFrame::<Size4KiB>::containing_address(PhysAddr::new(0x1234_5000));
+        let flags = PageTableFlags::PRESENT | PageTableFlags::WRITABLE;
+        unsafe { page_table.identity_map(frame, flags, &mut LeakingFrameAllocator) }
+            .unwrap()
+            .ignore();
+        assert_eq!(
+            page_table.translate_addr(VirtAddr::new(0x1234_5678)),
+            Some(PhysAddr::new(0x1234_5678))
+        );
+    }
+
+    #[cfg(not(feature = "la57"))]
+    #[test]
+    fn identity_map_non_canonical() {
+        let mut page_table = new_page_table();
+        let frame = PhysFrame::<Size4KiB>::containing_address(PhysAddr::new(0x8000_0000_0000));
+        let flags = PageTableFlags::PRESENT;
+        assert!(matches!(
+            unsafe { page_table.identity_map(frame, flags, &mut LeakingFrameAllocator) },
+            Err(MapToError::NonCanonicalAddress)
+        ));
+    }
+
+    #[test]
+    fn walk() {
+        let mut page_table = new_page_table();
+        let flags = PageTableFlags::PRESENT | PageTableFlags::WRITABLE;
+        let page = Page::<Size4KiB>::containing_address(VirtAddr::new(0xffff_8000_0020_3000));
+        let frame = PhysFrame::containing_address(PhysAddr::new(0x5000));
+        unsafe { page_table.map_to(page, frame, flags, &mut LeakingFrameAllocator) }
+            .unwrap()
+            .ignore();
+        let huge_page = Page::<Size2MiB>::containing_address(VirtAddr::new(0xffff_8000_0040_0000));
+        let huge_frame = PhysFrame::<Size2MiB>::containing_address(PhysAddr::new(0x20_0000));
+        unsafe { page_table.map_to(huge_page, huge_frame, flags, &mut LeakingFrameAllocator) }
+            .unwrap()
+            .ignore();
+
+        let entries: Vec<_> = page_table.walk().collect();
+        let summary: Vec<_> = entries
+            .iter()
+            .map(|entry| (entry.level, entry.addr.as_u64(), entry.is_leaf()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (4, 0xffff_8000_0000_0000, false),
+                (3, 0xffff_8000_0000_0000, false),
+                (2, 0xffff_8000_0020_0000, false),
+                (1, 0xffff_8000_0020_3000, true),
+                (2, 0xffff_8000_0040_0000, true),
+            ]
+        );
+        assert_eq!(entries[0].index, page.p4_index());
+        assert_eq!(entries[3].index, page.p1_index());
+        assert_eq!(entries[3].frame, frame);
+        assert_eq!(entries[4].frame.start_address(), huge_frame.start_address());
+        assert!(entries[4].flags.contains(PageTableFlags::HUGE_PAGE));
+        let p3_frame = PhysFrame::containing_address(page_table.level_4_table()[256].addr());
+        assert_eq!(entries[0].frame, p3_frame);
+    }
+
+    #[cfg(feature = "la57")]
+    #[test]
+    fn five_level_paging() {
+        let level_5_table = Box::leak(Box::new(PageTable::new()));
+        let mut page_table =
+            unsafe { OffsetPageTable::new_level_5(level_5_table, VirtAddr::zero()) };
+        // Only reachable with 57-bit addresses: level 5 index 1.
+        let page = Page::<Size4KiB>::containing_address(VirtAddr::new(0x0001_0000_0000_1000));
+        let frame = PhysFrame::containing_address(PhysAddr::new(0x6000));
+        let flags = PageTableFlags::PRESENT | PageTableFlags::WRITABLE;
+        unsafe { page_table.map_to(page, frame, flags, &mut LeakingFrameAllocator) }
+            .unwrap()
+            .ignore();
+
+        assert!(!page_table.top_level_table()[1].is_unused());
+        assert_eq!(
+            page_table.translate_addr(page.start_address() + 0x123u64),
+            Some(PhysAddr::new(0x6123))
+        );
+        let levels: Vec<_> = page_table.walk().map(|entry| entry.level).collect();
+        assert_eq!(levels, [5, 4, 3, 2, 1]);
+        let leaf = page_table.walk().last().unwrap();
+        assert_eq!((leaf.addr, leaf.frame), (page.start_address(), frame));
+
+        page_table.unmap(page).unwrap().1.ignore();
+        let mut deallocator = RecordingFrameDeallocator::default();
+        unsafe { page_table.clean_up(&mut deallocator) };
+        assert_eq!(deallocator.0.len(), 4);
+        assert!(page_table.top_level_table()[1].is_unused());
+    }
+
+    #[cfg(feature = "la57")]
+    #[test]
+    #[should_panic = "5-level paging"]
+    fn five_level_paging_level_4_table() {
+        let level_5_table = Box::leak(Box::new(PageTable::new()));
+        let page_table = unsafe { OffsetPageTable::new_level_5(level_5_table, VirtAddr::zero()) };
+        page_table.level_4_table();
+    }
+}
@@ -30,7 +30,7 @@ use crate::structures::paging::{
 #[derive(Debug)]
 pub struct RecursivePageTable<'a> {
     p4: &'a mut PageTable,
-    recursive_index: PageTableIndex,
+    recursion: Recursion,
 }
 
 impl<'a> RecursivePageTable<'a> {
@@ -70,7 +70,50 @@ impl<'a> RecursivePageTable<'a> {
 
         Ok(RecursivePageTable {
             p4: table,
-            recursive_index,
+            recursion: Recursion::four_level(recursive_index),
+        })
+    }
+
+    /// Creates a new RecursivePageTable from the passed level 5 PageTable for 5-level paging.
+    ///
+    /// The page table must be recursively mapped through the entry `recursive_index`, that
+    /// means the table itself must be accessible at the address that uses `recursive_index`
+    /// for all five levels. The page table must also be active, i.e. the CR3 register must
+    /// contain its physical address. Otherwise an error is returned.
+    ///
+    /// All page tables are then accessible by “looping” through the recursive entry one more
+    /// time than with 4-level paging. For example, with recursive index 510 (`0o776`):
+    ///
+    /// - The level 5 table is at `0o776_776_776_776_776_0000` (`0xfffe_ff7f_bfdf_e000`).
+    /// - The level 4 table for the address `0o001_002_003_004_005_0000` is at
+    ///   `0o776_776_776_776_001_0000` (`0xfffe_ff7f_bfc0_1000`), i.e. the level 5 index of
+    ///   the address moves to the position of the level 1 index.
+    /// - The level 3 table for that address is at `0o776_776_776_001_002_0000`, the level 2
+    ///   table at `0o776_776_001_002_003_0000` and the level 1 table at
+    ///   `0o776_001_002_003_004_0000` (`0xfffe_0080_8060_4000`).
+    ///
+    /// The same considerations about recursive index 511 as for [`new`](Self::new) apply.
+    #[cfg(feature = "la57")]
+    #[inline]
+    pub fn new_la57(
+        table: &'a mut PageTable,
+        recursive_index: PageTableIndex,
+    ) -> Result<Self, InvalidPageTable> {
+        let recursion = Recursion {
+            index: recursive_index,
+            five_level: true,
+        };
+        let table_addr = VirtAddr::from_ptr(table as *const PageTable);
+        if recursion.table_page(table_addr, 5).start_address() != table_addr {
+            return Err(InvalidPageTable::NotRecursive);
+        }
+        if Ok(Cr3::read().0) != table[recursive_index].frame() {
+            return Err(InvalidPageTable::NotActive);
+        }
+
+        Ok(RecursivePageTable {
+            p4: table,
+            recursion,
         })
     }
 
@@ -85,20 +128,113 @@ impl<'a> RecursivePageTable<'a> {
     pub unsafe fn new_unchecked(table: &'a mut PageTable, recursive_index: PageTableIndex) -> Self {
         RecursivePageTable {
             p4: table,
-            recursive_index,
+            recursion: Recursion::four_level(recursive_index),
         }
     }
 
     /// Returns an immutable reference to the wrapped level 4 `PageTable` instance.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the page table was created with `new_la57`. Use
+    /// [`top_level_table`](Self::top_level_table) instead.
     pub fn level_4_table(&self) -> &PageTable {
+        #[cfg(feature = "la57")]
+        assert!(!self.recursion.five_level, "page table uses 5-level paging");
         self.p4
     }
 
     /// Returns a mutable reference to the wrapped level 4 `PageTable` instance.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the page table was created with `new_la57`. Use
+    /// [`top_level_table_mut`](Self::top_level_table_mut) instead.
     pub fn level_4_table_mut(&mut self) -> &mut PageTable {
+        #[cfg(feature = "la57")]
+        assert!(!self.recursion.five_level, "page table uses 5-level paging");
         self.p4
     }
 
+    /// Returns an immutable reference to the wrapped top level `PageTable` instance.
+    ///
+    /// This is the level 5 table if the page table was created with `new_la57` and the
+    /// level 4 table otherwise.
+    pub fn top_level_table(&self) -> &PageTable {
+        self.p4
+    }
+
+    /// Returns a mutable reference to the wrapped top level `PageTable` instance.
+    ///
+    /// This is the level 5 table if the page table was created with `new_la57` and the
+    /// level 4 table otherwise.
+    pub fn top_level_table_mut(&mut self) -> &mut PageTable {
+        self.p4
+    }
+
+    /// Returns the level 4 table that is responsible for the given page, together with the
+    /// recursion parameters for accessing the lower level tables.
+    ///
+    /// With 5-level paging, this returns `None` if the level 5 entry is unused.
+    fn p4_table<S: PageSize>(&self, page: Page<S>) -> Option<(&PageTable, Recursion)> {
+        let recursion = self.recursion;
+        #[cfg(feature = "la57")]
+        if recursion.five_level {
+            if self.p4[page.p5_index()].is_unused() {
+                return None;
+            }
+            let p4 = unsafe { &*(p4_ptr(page, recursion)) };
+            return Some((p4, recursion));
+        }
+        #[cfg(not(feature = "la57"))]
+        let _ = page;
+        Some((self.p4, recursion))
+    }
+
+    /// Like [`p4_table`](Self::p4_table), but returns a mutable reference.
+    fn p4_table_mut<S: PageSize>(&mut self, page: Page<S>) -> Option<(&mut PageTable, Recursion)> {
+        let recursion = self.recursion;
+        #[cfg(feature = "la57")]
+        if recursion.five_level {
+            if self.p4[page.p5_index()].is_unused() {
+                return None;
+            }
+            let p4 = unsafe { &mut *(p4_ptr(page, recursion)) };
+            return Some((p4, recursion));
+        }
+        #[cfg(not(feature = "la57"))]
+        let _ = page;
+        Some((self.p4, recursion))
+    }
+
+    /// Like [`p4_table_mut`](Self::p4_table_mut), but creates the level 4 table if needed.
+    fn p4_table_create<A, S: PageSize>(
+        &mut self,
+        page: Page<S>,
+        insert_flags: PageTableFlags,
+        allocator: &mut A,
+    ) -> Result<(&mut PageTable, Recursion), MapToError<S>>
+    where
+        A: FrameAllocator<Size4KiB> + ?Sized,
+    {
+        let recursion = self.recursion;
+        #[cfg(feature = "la57")]
+        if recursion.five_level {
+            let p4 = unsafe {
+                Self::create_next_table(
+                    &mut self.p4[page.p5_index()],
+                    p4_page(page, recursion),
+                    insert_flags,
+                    allocator,
+                )?
+            };
+            return Ok((p4, recursion));
+        }
+        #[cfg(not(feature = "la57"))]
+        let _ = (page, insert_flags, allocator);
+        Ok((self.p4, recursion))
+    }
+
     /// Internal helper function to create the page table of the next level if needed.
     ///
     /// If the passed entry is unused, a new frame is allocated from the given allocator, zeroed,
@@ -181,9 +317,9 @@ impl<'a> RecursivePageTable<'a> {
         A: FrameAllocator<Size4KiB> + ?Sized,
     {
         use crate::structures::paging::PageTableFlags as Flags;
-        let p4 = &mut self.p4;
+        let (p4, recursion) = self.p4_table_create(page, parent_table_flags, allocator)?;
 
-        let p3_page = p3_page(page, self.recursive_index);
+        let p3_page = p3_page(page, recursion);
         let p3 = unsafe {
             Self::create_next_table(
                 &mut p4[page.p4_index()],
@@ -193,10 +329,14 @@ impl<'a> RecursivePageTable<'a> {
             )?
         };
 
-        if !p3[page.p3_index()].is_unused() {
+        let entry = &mut p3[page.p3_index()];
+        if !entry.is_unused() {
+            if !entry.flags().contains(Flags::HUGE_PAGE) {
+                return Err(MapToError::EntryPointsToPageTable);
+            }
             return Err(MapToError::PageAlreadyMapped(frame));
         }
-        p3[page.p3_index()].set_addr(frame.start_address(), flags | Flags::HUGE_PAGE);
+        entry.set_addr(frame.start_address(), flags | Flags::HUGE_PAGE);
 
         Ok(MapperFlush::new(page))
     }
@@ -215,9 +355,9 @@ impl<'a> RecursivePageTable<'a> {
         A: FrameAllocator<Size4KiB> + ?Sized,
     {
         use crate::structures::paging::PageTableFlags as Flags;
-        let p4 = &mut self.p4;
+        let (p4, recursion) = self.p4_table_create(page, parent_table_flags, allocator)?;
 
-        let p3_page = p3_page(page, self.recursive_index);
+        let p3_page = p3_page(page, recursion);
         let p3 = unsafe {
             Self::create_next_table(
                 &mut p4[page.p4_index()],
@@ -227,7 +367,7 @@ impl<'a> RecursivePageTable<'a> {
             )?
         };
 
-        let p2_page = p2_page(page, self.recursive_index);
+        let p2_page = p2_page(page, recursion);
         let p2 = unsafe {
             Self::create_next_table(
                 &mut p3[page.p3_index()],
@@ -237,10 +377,14 @@ impl<'a> RecursivePageTable<'a> {
             )?
         };
 
-        if !p2[page.p2_index()].is_unused() {
+        let entry = &mut p2[page.p2_index()];
+        if !entry.is_unused() {
+            if !entry.flags().contains(Flags::HUGE_PAGE) {
+                return Err(MapToError::EntryPointsToPageTable);
+            }
             return Err(MapToError::PageAlreadyMapped(frame));
         }
-        p2[page.p2_index()].set_addr(frame.start_address(), flags | Flags::HUGE_PAGE);
+        entry.set_addr(frame.start_address(), flags | Flags::HUGE_PAGE);
 
         Ok(MapperFlush::new(page))
     }
@@ -258,9 +402,9 @@ impl<'a> RecursivePageTable<'a> {
     where
         A: FrameAllocator<Size4KiB> + ?Sized,
     {
-        let p4 = &mut self.p4;
+        let (p4, recursion) = self.p4_table_create(page, parent_table_flags, allocator)?;
 
-        let p3_page = p3_page(page, self.recursive_index);
+        let p3_page = p3_page(page, recursion);
         let p3 = unsafe {
             Self::create_next_table(
                 &mut p4[page.p4_index()],
@@ -270,7 +414,7 @@ impl<'a> RecursivePageTable<'a> {
             )?
         };
 
-        let p2_page = p2_page(page, self.recursive_index);
+        let p2_page = p2_page(page, recursion);
         let p2 = unsafe {
             Self::create_next_table(
                 &mut p3[page.p3_index()],
@@ -280,7 +424,7 @@ impl<'a> RecursivePageTable<'a> {
             )?
         };
 
-        let p1_page = p1_page(page, self.recursive_index);
+        let p1_page = p1_page(page, recursion);
         let p1 = unsafe {
             Self::create_next_table(
                 &mut p2[page.p2_index()],
@@ -315,11 +459,20 @@ impl Mapper<Size1GiB> for RecursivePageTable<'_> {
         self.map_to_1gib(page, frame, flags, parent_table_flags, allocator)
     }
 
+    #[inline]
     fn unmap(
         &mut self,
         page: Page<Size1GiB>,
     ) -> Result<(PhysFrame<Size1GiB>, MapperFlush<Size1GiB>), UnmapError> {
-        let p4 = &mut self.p4;
+        self.unmap_with_flags(page)
+            .map(|(frame, _, flush)| (frame, flush))
+    }
+
+    fn unmap_with_flags(
+        &mut self,
+        page: Page<Size1GiB>,
+    ) -> Result<(PhysFrame<Size1GiB>, PageTableFlags, MapperFlush<Size1GiB>), UnmapError> {
+        let (p4, recursion) = self.p4_table_mut(page).ok_or(UnmapError::PageNotMapped)?;
         let p4_entry = &p4[page.p4_index()];
 
         if p4_entry.flags().contains(PageTableFlags::HUGE_PAGE) {
@@ -331,7 +484,7 @@ impl Mapper<Size1GiB> for RecursivePageTable<'_> {
             FrameError::HugeFrame => unreachable!(),
         })?;
 
-        let p3 = unsafe { &mut *(p3_ptr(page, self.recursive_index)) };
+        let p3 = unsafe { &mut *(p3_ptr(page, recursion)) };
         let p3_entry = &mut p3[page.p3_index()];
         let flags = p3_entry.flags();
 
@@ -346,7 +499,7 @@ impl Mapper<Size1GiB> for RecursivePageTable<'_> {
             .map_err(|AddressNotAligned| UnmapError::InvalidFrameAddress(p3_entry.addr()))?;
 
         p3_entry.set_unused();
-        Ok((frame, MapperFlush::new(page)))
+        Ok((frame, flags, MapperFlush::new(page)))
     }
 
     unsafe fn update_flags(
@@ -355,17 +508,22 @@ impl Mapper<Size1GiB> for RecursivePageTable<'_> {
         flags: PageTableFlags,
     ) -> Result<MapperFlush<Size1GiB>, FlagUpdateError> {
         use crate::structures::paging::PageTableFlags as Flags;
-        let p4 = &mut self.p4;
+        let (p4, recursion) = self
+            .p4_table_mut(page)
+            .ok_or(FlagUpdateError::PageNotMapped)?;
 
         if p4[page.p4_index()].is_unused() {
             return Err(FlagUpdateError::PageNotMapped);
         }
 
-        let p3 = unsafe { &mut *(p3_ptr(page, self.recursive_index)) };
+        let p3 = unsafe { &mut *(p3_ptr(page, recursion)) };
 
         if p3[page.p3_index()].is_unused() {
             return Err(FlagUpdateError::PageNotMapped);
         }
+        if !p3[page.p3_index()].flags().contains(Flags::HUGE_PAGE) {
+            return Err(FlagUpdateError::PageSizeMismatch);
+        }
         p3[page.p3_index()].set_flags(flags | Flags::HUGE_PAGE);
 
         Ok(MapperFlush::new(page))
@@ -376,7 +534,9 @@ impl Mapper<Size1GiB> for RecursivePageTable<'_> {
         page: Page<Size1GiB>,
         flags: PageTableFlags,
     ) -> Result<MapperFlushAll, FlagUpdateError> {
-        let p4 = &mut self.p4;
+        let (p4, _) = self
+            .p4_table_mut(page)
+            .ok_or(FlagUpdateError::PageNotMapped)?;
         let p4_entry = &mut p4[page.p4_index()];
 
         if p4_entry.is_unused() {
@@ -405,13 +565,13 @@ impl Mapper<Size1GiB> for RecursivePageTable<'_> {
     }
 
     fn translate_page(&self, page: Page<Size1GiB>) -> Result<PhysFrame<Size1GiB>, TranslateError> {
-        let p4 = &self.p4;
+        let (p4, recursion) = self.p4_table(page).ok_or(TranslateError::PageNotMapped)?;
 
         if p4[page.p4_index()].is_unused() {
             return Err(TranslateError::PageNotMapped);
         }
 
-        let p3 = unsafe { &*(p3_ptr(page, self.recursive_index)) };
+        let p3 = unsafe { &*(p3_ptr(page, recursion)) };
         let p3_entry = &p3[page.p3_index()];
 
         if p3_entry.is_unused() {
@@ -439,11 +599,20 @@ impl Mapper<Size2MiB> for RecursivePageTable<'_> {
         self.map_to_2mib(page, frame, flags, parent_table_flags, allocator)
     }
 
+    #[inline]
     fn unmap(
         &mut self,
         page: Page<Size2MiB>,
     ) -> Result<(PhysFrame<Size2MiB>, MapperFlush<Size2MiB>), UnmapError> {
-        let p4 = &mut self.p4;
+        self.unmap_with_flags(page)
+            .map(|(frame, _, flush)| (frame, flush))
+    }
+
+    fn unmap_with_flags(
+        &mut self,
+        page: Page<Size2MiB>,
+    ) -> Result<(PhysFrame<Size2MiB>, PageTableFlags, MapperFlush<Size2MiB>), UnmapError> {
+        let (p4, recursion) = self.p4_table_mut(page).ok_or(UnmapError::PageNotMapped)?;
         let p4_entry = &p4[page.p4_index()];
         if p4_entry.flags().contains(PageTableFlags::HUGE_PAGE) {
             return Err(UnmapError::ParentEntryHugePage);
@@ -454,7 +623,7 @@ impl Mapper<Size2MiB> for RecursivePageTable<'_> {
             FrameError::HugeFrame => unreachable!(),
         })?;
 
-        let p3 = unsafe { &mut *(p3_ptr(page, self.recursive_index)) };
+        let p3 = unsafe { &mut *(p3_ptr(page, recursion)) };
         let p3_entry = &p3[page.p3_index()];
         if p3_entry.flags().contains(PageTableFlags::HUGE_PAGE) {
             return Err(UnmapError::ParentEntryHugePage);
@@ -465,7 +634,7 @@ impl Mapper<Size2MiB> for RecursivePageTable<'_> {
             FrameError::HugeFrame => unreachable!(),
         })?;
 
-        let p2 = unsafe { &mut *(p2_ptr(page, self.recursive_index)) };
+        let p2 = unsafe { &mut *(p2_ptr(page, recursion)) };
         let p2_entry = &mut p2[page.p2_index()];
         let flags = p2_entry.flags();
 
@@ -480,7 +649,7 @@ impl Mapper<Size2MiB> for RecursivePageTable<'_> {
             .map_err(|AddressNotAligned| UnmapError::InvalidFrameAddress(p2_entry.addr()))?;
 
         p2_entry.set_unused();
-        Ok((frame, MapperFlush::new(page)))
+        Ok((frame, flags, MapperFlush::new(page)))
     }
 
     unsafe fn update_flags(
@@ -489,23 +658,31 @@ impl Mapper<Size2MiB> for RecursivePageTable<'_> {
         flags: PageTableFlags,
     ) -> Result<MapperFlush<Size2MiB>, FlagUpdateError> {
         use crate::structures::paging::PageTableFlags as Flags;
-        let p4 = &mut self.p4;
+        let (p4, recursion) = self
+            .p4_table_mut(page)
+            .ok_or(FlagUpdateError::PageNotMapped)?;
 
         if p4[page.p4_index()].is_unused() {
             return Err(FlagUpdateError::PageNotMapped);
         }
 
-        let p3 = unsafe { &mut *(p3_ptr(page, self.recursive_index)) };
+        let p3 = unsafe { &mut *(p3_ptr(page, recursion)) };
 
         if p3[page.p3_index()].is_unused() {
             return Err(FlagUpdateError::PageNotMapped);
         }
+        if p3[page.p3_index()].flags().contains(Flags::HUGE_PAGE) {
+            return Err(FlagUpdateError::ParentEntryHugePage);
+        }
 
-        let p2 = unsafe { &mut *(p2_ptr(page, self.recursive_index)) };
+        let p2 = unsafe { &mut *(p2_ptr(page, recursion)) };
 
         if p2[page.p2_index()].is_unused() {
             return Err(FlagUpdateError::PageNotMapped);
         }
+        if !p2[page.p2_index()].flags().contains(Flags::HUGE_PAGE) {
+            return Err(FlagUpdateError::PageSizeMismatch);
+        }
 
         p2[page.p2_index()].set_flags(flags | Flags::HUGE_PAGE);
 
@@ -517,7 +694,9 @@ impl Mapper<Size2MiB> for RecursivePageTable<'_> {
         page: Page<Size2MiB>,
         flags: PageTableFlags,
     ) -> Result<MapperFlushAll, FlagUpdateError> {
-        let p4 = &mut self.p4;
+        let (p4, _) = self
+            .p4_table_mut(page)
+            .ok_or(FlagUpdateError::PageNotMapped)?;
         let p4_entry = &mut p4[page.p4_index()];
 
         if p4_entry.is_unused() {
@@ -534,13 +713,15 @@ impl Mapper<Size2MiB> for RecursivePageTable<'_> {
         page: Page<Size2MiB>,
         flags: PageTableFlags,
     ) -> Result<MapperFlushAll, FlagUpdateError> {
-        let p4 = &mut self.p4;
+        let (p4, recursion) = self
+            .p4_table_mut(page)
+            .ok_or(FlagUpdateError::PageNotMapped)?;
 
         if p4[page.p4_index()].is_unused() {
             return Err(FlagUpdateError::PageNotMapped);
         }
 
-        let p3 = unsafe { &mut *(p3_ptr(page, self.recursive_index)) };
+        let p3 = unsafe { &mut *(p3_ptr(page, recursion)) };
         let p3_entry = &mut p3[page.p3_index()];
 
         if p3_entry.is_unused() {
@@ -561,20 +742,20 @@ impl Mapper<Size2MiB> for RecursivePageTable<'_> {
     }
 
     fn translate_page(&self, page: Page<Size2MiB>) -> Result<PhysFrame<Size2MiB>, TranslateError> {
-        let p4 = &self.p4;
+        let (p4, recursion) = self.p4_table(page).ok_or(TranslateError::PageNotMapped)?;
 
         if p4[page.p4_index()].is_unused() {
             return Err(TranslateError::PageNotMapped);
         }
 
-        let p3 = unsafe { &*(p3_ptr(page, self.recursive_index)) };
+        let p3 = unsafe { &*(p3_ptr(page, recursion)) };
         let p3_entry = &p3[page.p3_index()];
 
         if p3_entry.is_unused() {
             return Err(TranslateError::PageNotMapped);
         }
 
-        let p2 = unsafe { &*(p2_ptr(page, self.recursive_index)) };
+        let p2 = unsafe { &*(p2_ptr(page, recursion)) };
         let p2_entry = &p2[page.p2_index()];
 
         if p2_entry.is_unused() {
@@ -602,11 +783,20 @@ impl Mapper<Size4KiB> for RecursivePageTable<'_> {
         self.map_to_4kib(page, frame, flags, parent_table_flags, allocator)
     }
 
+    #[inline]
     fn unmap(
         &mut self,
         page: Page<Size4KiB>,
     ) -> Result<(PhysFrame<Size4KiB>, MapperFlush<Size4KiB>), UnmapError> {
-        let p4 = &mut self.p4;
+        self.unmap_with_flags(page)
+            .map(|(frame, _, flush)| (frame, flush))
+    }
+
+    fn unmap_with_flags(
+        &mut self,
+        page: Page<Size4KiB>,
+    ) -> Result<(PhysFrame<Size4KiB>, PageTableFlags, MapperFlush<Size4KiB>), UnmapError> {
+        let (p4, recursion) = self.p4_table_mut(page).ok_or(UnmapError::PageNotMapped)?;
         let p4_entry = &p4[page.p4_index()];
         if p4_entry.flags().contains(PageTableFlags::HUGE_PAGE) {
             return Err(UnmapError::ParentEntryHugePage);
@@ -617,7 +807,7 @@ impl Mapper<Size4KiB> for RecursivePageTable<'_> {
             FrameError::HugeFrame => unreachable!(),
         })?;
 
-        let p3 = unsafe { &mut *(p3_ptr(page, self.recursive_index)) };
+        let p3 = unsafe { &mut *(p3_ptr(page, recursion)) };
         let p3_entry = &p3[page.p3_index()];
         if p3_entry.flags().contains(PageTableFlags::HUGE_PAGE) {
             return Err(UnmapError::ParentEntryHugePage);
@@ -628,7 +818,7 @@ impl Mapper<Size4KiB> for RecursivePageTable<'_> {
             FrameError::HugeFrame => unreachable!(),
         })?;
 
-        let p2 = unsafe { &mut *(p2_ptr(page, self.recursive_index)) };
+        let p2 = unsafe { &mut *(p2_ptr(page, recursion)) };
         let p2_entry = &p2[page.p2_index()];
         if p2_entry.flags().contains(PageTableFlags::HUGE_PAGE) {
             return Err(UnmapError::ParentEntryHugePage);
@@ -639,7 +829,7 @@ impl Mapper<Size4KiB> for RecursivePageTable<'_> {
             FrameError::HugeFrame => unreachable!(),
         })?;
 
-        let p1 = unsafe { &mut *(p1_ptr(page, self.recursive_index)) };
+        let p1 = unsafe { &mut *(p1_ptr(page, recursion)) };
         let p1_entry = &mut p1[page.p1_index()];
 
         let frame = p1_entry.frame().map_err(|err| match err {
@@ -648,8 +838,9 @@ impl Mapper<Size4KiB> for RecursivePageTable<'_> {
             FrameError::HugeFrame => unreachable!(),
         })?;
 
+        let flags = p1_entry.flags();
         p1_entry.set_unused();
-        Ok((frame, MapperFlush::new(page)))
+        Ok((frame, flags, MapperFlush::new(page)))
     }
 
     unsafe fn update_flags(
@@ -657,25 +848,39 @@ impl Mapper<Size4KiB> for RecursivePageTable<'_> {
         page: Page<Size4KiB>,
         flags: PageTableFlags,
     ) -> Result<MapperFlush<Size4KiB>, FlagUpdateError> {
-        let p4 = &mut self.p4;
+        let (p4, recursion) = self
+            .p4_table_mut(page)
+            .ok_or(FlagUpdateError::PageNotMapped)?;
 
         if p4[page.p4_index()].is_unused() {
             return Err(FlagUpdateError::PageNotMapped);
         }
 
-        let p3 = unsafe { &mut *(p3_ptr(page, self.recursive_index)) };
+        let p3 = unsafe { &mut *(p3_ptr(page, recursion)) };
 
         if p3[page.p3_index()].is_unused() {
             return Err(FlagUpdateError::PageNotMapped);
         }
+        if p3[page.p3_index()]
+            .flags()
+            .contains(PageTableFlags::HUGE_PAGE)
+        {
+            return Err(FlagUpdateError::ParentEntryHugePage);
+        }
 
-        let p2 = unsafe { &mut *(p2_ptr(page, self.recursive_index)) };
+        let p2 = unsafe { &mut *(p2_ptr(page, recursion)) };
 
         if p2[page.p2_index()].is_unused() {
             return Err(FlagUpdateError::PageNotMapped);
         }
+        if p2[page.p2_index()]
+            .flags()
+            .contains(PageTableFlags::HUGE_PAGE)
+        {
+            return Err(FlagUpdateError::ParentEntryHugePage);
+        }
 
-        let p1 = unsafe { &mut *(p1_ptr(page, self.recursive_index)) };
+        let p1 = unsafe { &mut *(p1_ptr(page, recursion)) };
 
         if p1[page.p1_index()].is_unused() {
             return Err(FlagUpdateError::PageNotMapped);
@@ -691,7 +896,9 @@ impl Mapper<Size4KiB> for RecursivePageTable<'_> {
         page: Page<Size4KiB>,
         flags: PageTableFlags,
     ) -> Result<MapperFlushAll, FlagUpdateError> {
-        let p4 = &mut self.p4;
+        let (p4, _) = self
+            .p4_table_mut(page)
+            .ok_or(FlagUpdateError::PageNotMapped)?;
         let p4_entry = &mut p4[page.p4_index()];
 
         if p4_entry.is_unused() {
@@ -708,13 +915,15 @@ impl Mapper<Size4KiB> for RecursivePageTable<'_> {
         page: Page<Size4KiB>,
         flags: PageTableFlags,
     ) -> Result<MapperFlushAll, FlagUpdateError> {
-        let p4 = &mut self.p4;
+        let (p4, recursion) = self
+            .p4_table_mut(page)
+            .ok_or(FlagUpdateError::PageNotMapped)?;
 
         if p4[page.p4_index()].is_unused() {
             return Err(FlagUpdateError::PageNotMapped);
         }
 
-        let p3 = unsafe { &mut *(p3_ptr(page, self.recursive_index)) };
+        let p3 = unsafe { &mut *(p3_ptr(page, recursion)) };
         let p3_entry = &mut p3[page.p3_index()];
 
         if p3_entry.is_unused() {
@@ -731,19 +940,21 @@ impl Mapper<Size4KiB> for RecursivePageTable<'_> {
         page: Page<Size4KiB>,
         flags: PageTableFlags,
     ) -> Result<MapperFlushAll, FlagUpdateError> {
-        let p4 = &mut self.p4;
+        let (p4, recursion) = self
+            .p4_table_mut(page)
+            .ok_or(FlagUpdateError::PageNotMapped)?;
 
         if p4[page.p4_index()].is_unused() {
             return Err(FlagUpdateError::PageNotMapped);
         }
 
-        let p3 = unsafe { &mut *(p3_ptr(page, self.recursive_index)) };
+        let p3 = unsafe { &mut *(p3_ptr(page, recursion)) };
 
         if p3[page.p3_index()].is_unused() {
             return Err(FlagUpdateError::PageNotMapped);
         }
 
-        let p2 = unsafe { &mut *(p2_ptr(page, self.recursive_index)) };
+        let p2 = unsafe { &mut *(p2_ptr(page, recursion)) };
         let p2_entry = &mut p2[page.p2_index()];
 
         if p2_entry.is_unused() {
@@ -756,27 +967,27 @@ impl Mapper<Size4KiB> for RecursivePageTable<'_> {
     }
 
     fn translate_page(&self, page: Page<Size4KiB>) -> Result<PhysFrame<Size4KiB>, TranslateError> {
-        let p4 = &self.p4;
+        let (p4, recursion) = self.p4_table(page).ok_or(TranslateError::PageNotMapped)?;
 
         if p4[page.p4_index()].is_unused() {
             return Err(TranslateError::PageNotMapped);
         }
 
-        let p3 = unsafe { &*(p3_ptr(page, self.recursive_index)) };
+        let p3 = unsafe { &*(p3_ptr(page, recursion)) };
         let p3_entry = &p3[page.p3_index()];
 
         if p3_entry.is_unused() {
             return Err(TranslateError::PageNotMapped);
         }
 
-        let p2 = unsafe { &*(p2_ptr(page, self.recursive_index)) };
+        let p2 = unsafe { &*(p2_ptr(page, recursion)) };
         let p2_entry = &p2[page.p2_index()];
 
         if p2_entry.is_unused() {
             return Err(TranslateError::PageNotMapped);
         }
 
-        let p1 = unsafe { &*(p1_ptr(page, self.recursive_index)) };
+        let p1 = unsafe { &*(p1_ptr(page, recursion)) };
         let p1_entry = &p1[page.p1_index()];
 
         if p1_entry.is_unused() {
@@ -793,7 +1004,10 @@ impl Translate for RecursivePageTable<'_> {
     fn translate(&self, addr: VirtAddr) -> TranslateResult {
         let page = Page::containing_address(addr);
 
-        let p4 = &self.p4;
+        let (p4, recursion) = match self.p4_table(page) {
+            Some(p4) => p4,
+            None => return TranslateResult::NotMapped,
+        };
         let p4_entry = &p4[addr.p4_index()];
         if p4_entry.is_unused() {
             return TranslateResult::NotMapped;
@@ -802,7 +1016,7 @@ impl Translate for RecursivePageTable<'_> {
             panic!("level 4 entry has huge page bit set")
         }
 
-        let p3 = unsafe { &*(p3_ptr(page, self.recursive_index)) };
+        let p3 = unsafe { &*(p3_ptr(page, recursion)) };
         let p3_entry = &p3[addr.p3_index()];
         if p3_entry.is_unused() {
             return TranslateResult::NotMapped;
@@ -820,7 +1034,7 @@ impl Translate for RecursivePageTable<'_> {
             };
         }
 
-        let p2 = unsafe { &*(p2_ptr(page, self.recursive_index)) };
+        let p2 = unsafe { &*(p2_ptr(page, recursion)) };
         let p2_entry = &p2[addr.p2_index()];
         if p2_entry.is_unused() {
             return TranslateResult::NotMapped;
@@ -838,7 +1052,7 @@ impl Translate for RecursivePageTable<'_> {
             };
         }
 
-        let p1 = unsafe { &*(p1_ptr(page, self.recursive_index)) };
+        let p1 = unsafe { &*(p1_ptr(page, recursion)) };
         let p1_entry = &p1[addr.p1_index()];
         if p1_entry.is_unused() {
             return TranslateResult::NotMapped;
@@ -883,7 +1097,7 @@ impl CleanUp for RecursivePageTable<'_> {
         D: FrameDeallocator<Size4KiB>,
     {
         fn clean_up(
-            recursive_index: PageTableIndex,
+            recursion: Recursion,
             page_table: &mut PageTable,
             level: PageTableLevel,
             range: PageRangeInclusive,
@@ -909,7 +1123,9 @@ impl CleanUp for RecursivePageTable<'_> {
                     .take(usize::from(end) + 1)
                     .skip(usize::from(start))
                     .filter(|(i, _)| {
-                        !(level == PageTableLevel::Four && *i == recursive_index.into())
+                        !(level == PageTableLevel::Four
+                            && recursion.levels() == 4
+                            && *i == recursion.index.into())
                     })
                 {
                     if entry.flags().contains(PageTableFlags::HUGE_PAGE) {
@@ -927,10 +1143,10 @@ impl CleanUp for RecursivePageTable<'_> {
                         let end = Page::<Size4KiB>::containing_address(end);
                         let end = end.min(range.end);
                         let page_table =
-                            [p1_ptr, p2_ptr, p3_ptr][level as usize - 2](start, recursive_index);
+                            [p1_ptr, p2_ptr, p3_ptr][level as usize - 2](start, recursion);
                         let page_table = unsafe { &mut *page_table };
                         if clean_up(
-                            recursive_index,
+                            recursion,
                             page_table,
                             next_level,
                             Page::range_inclusive(start, end),
@@ -948,8 +1164,57 @@ impl CleanUp for RecursivePageTable<'_> {
             page_table.iter().all(PageTableEntry::is_unused)
         }
 
+        #[cfg(feature = "la57")]
+        if self.recursion.five_level {
+            if range.is_empty() {
+                return;
+            }
+
+            let recursion = self.recursion;
+            let offset_per_entry = PageTableLevel::Four.table_address_space_alignment();
+            let start = usize::from(range.start.p5_index());
+            let end = usize::from(range.end.p5_index());
+            for (i, entry) in self
+                .p4
+                .iter_mut()
+                .enumerate()
+                .take(end + 1)
+                .skip(start)
+                .filter(|(i, _)| *i != recursion.index.into())
+            {
+                if entry.flags().contains(PageTableFlags::HUGE_PAGE) {
+                    continue;
+                }
+                if let Ok(frame) = entry.frame() {
+                    let entry_start = VirtAddr::forward_checked_impl(
+                        VirtAddr::zero(),
+                        (offset_per_entry as usize) * i,
+                    )
+                    .unwrap();
+                    let entry_end = entry_start + (offset_per_entry - 1);
+                    let entry_start = Page::<Size4KiB>::containing_address(entry_start);
+                    let entry_end = Page::<Size4KiB>::containing_address(entry_end);
+                    let start = entry_start.max(range.start);
+                    let p4 = unsafe { &mut *p4_ptr(start, recursion) };
+                    if clean_up(
+                        recursion,
+                        p4,
+                        PageTableLevel::Four,
+                        Page::range_inclusive(start, entry_end.min(range.end)),
+                        frame_deallocator,
+                    ) {
+                        entry.set_unused();
+                        unsafe {
+                            frame_deallocator.deallocate_frame(frame);
+                        }
+                    }
+                }
+            }
+            return;
+        }
+
         clean_up(
-            self.recursive_index,
+            self.recursion,
             self.level_4_table_mut(),
             PageTableLevel::Four,
             range,
@@ -985,47 +1250,147 @@ impl fmt::Display for InvalidPageTable {
     }
 }
 
+/// The recursive index together with the number of paging levels. These determine the
+/// virtual addresses through which the page tables are accessible.
+#[derive(Debug, Clone, Copy)]
+struct Recursion {
+    index: PageTableIndex,
+    #[cfg(feature = "la57")]
+    five_level: bool,
+}
+
+impl Recursion {
+    #[inline]
+    fn four_level(index: PageTableIndex) -> Self {
+        Recursion {
+            index,
+            #[cfg(feature = "la57")]
+            five_level: false,
+        }
+    }
+
+    /// Returns the number of paging levels.
+    #[inline]
+    fn levels(self) -> u8 {
+        #[cfg(feature = "la57")]
+        if self.five_level {
+            return 5;
+        }
+        4
+    }
+
+    /// Returns the page through which the page table of the given level that is responsible
+    /// for `addr` is accessible.
+    ///
+    /// The address of the table uses the recursive index for the `level` highest levels,
+    /// followed by the indices of `addr`, starting at the highest level.
+    fn table_page(self, addr: VirtAddr, level: u8) -> Page {
+        let levels = self.levels();
+        let mut raw = 0;
+        for position in 0..levels {
+            let index = if position < level {
+                self.index
+            } else {
+                let index_level = levels - (position - level);
+                PageTableIndex::new_truncate((addr.as_u64() >> (12 + 9 * (index_level - 1))) as u16)
+            };
+            raw |= index.into_u64() << (12 + 9 * (levels - 1 - position));
+        }
+        // sign extend from the highest translated bit
+        let unused_bits = 64 - (12 + 9 * u32::from(levels));
+        let raw = ((raw << unused_bits) as i64 >> unused_bits) as u64;
+        Page::containing_address(VirtAddr::new_truncate(raw))
+    }
+}
+
+#[cfg(feature = "la57")]
 #[inline]
-fn p3_ptr<S: PageSize>(page: Page<S>, recursive_index: PageTableIndex) -> *mut PageTable {
-    p3_page(page, recursive_index).start_address().as_mut_ptr()
+fn p4_ptr<S: PageSize>(page: Page<S>, recursion: Recursion) -> *mut PageTable {
+    p4_page(page, recursion).start_address().as_mut_ptr()
 }
 
+#[cfg(feature = "la57")]
 #[inline]
-fn p3_page<S: PageSize>(page: Page<S>, recursive_index: PageTableIndex) -> Page {
-    Page::from_page_table_indices(
-        recursive_index,
-        recursive_index,
-        recursive_index,
-        page.p4_index(),
-    )
+fn p4_page<S: PageSize>(page: Page<S>, recursion: Recursion) -> Page {
+    recursion.table_page(page.start_address(), 4)
 }
 
 #[inline]
-fn p2_ptr<S: NotGiantPageSize>(page: Page<S>, recursive_index: PageTableIndex) -> *mut PageTable {
-    p2_page(page, recursive_index).start_address().as_mut_ptr()
+fn p3_ptr<S: PageSize>(page: Page<S>, recursion: Recursion) -> *mut PageTable {
+    p3_page(page, recursion).start_address().as_mut_ptr()
 }
 
 #[inline]
-fn p2_page<S: NotGiantPageSize>(page: Page<S>, recursive_index: PageTableIndex) -> Page {
-    Page::from_page_table_indices(
-        recursive_index,
-        recursive_index,
-        page.p4_index(),
-        page.p3_index(),
-    )
+fn p3_page<S: PageSize>(page: Page<S>, recursion: Recursion) -> Page {
+    recursion.table_page(page.start_address(), 3)
 }
 
 #[inline]
-fn p1_ptr(page: Page<Size4KiB>, recursive_index: PageTableIndex) -> *mut PageTable {
-    p1_page(page, recursive_index).start_address().as_mut_ptr()
+fn p2_ptr<S: NotGiantPageSize>(page: Page<S>, recursion: Recursion) -> *mut PageTable {
+    p2_page(page, recursion).start_address().as_mut_ptr()
 }
 
 #[inline]
-fn p1_page(page: Page<Size4KiB>, recursive_index: PageTableIndex) -> Page {
-    Page::from_page_table_indices(
-        recursive_index,
-        page.p4_index(),
-        page.p3_index(),
-        page.p2_index(),
-    )
+fn p2_page<S: NotGiantPageSize>(page: Page<S>, recursion: Recursion) -> Page {
+    recursion.table_page(page.start_address(), 2)
+}
+
+#[inline]
+fn p1_ptr(page: Page<Size4KiB>, recursion: Recursion) -> *mut PageTable {
+    p1_page(page, recursion).start_address().as_mut_ptr()
+}
+
+#[inline]
+fn p1_page(page: Page<Size4KiB>, recursion: Recursion) -> Page {
+    recursion.table_page(page.start_address(), 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recursive_table_addresses() {
+        let recursion = Recursion::four_level(PageTableIndex::new(510));
+        let page = Page::from_page_table_indices(
+            PageTableIndex::new(1),
+            PageTableIndex::new(2),
+            PageTableIndex::new(3),
+            PageTableIndex::new(4),
+        );
+        assert_eq!(
+            p3_page(page, recursion).start_address().as_u64(),
+            0xffff_ff7f_bfc0_1000
+        );
+        assert_eq!(
+            p1_page(page, recursion).start_address().as_u64(),
+            0xffff_ff00_4040_3000
+        );
+    }
+
+    #[cfg(feature = "la57")]
+    #[test]
+    fn recursive_table_addresses_la57() {
+        let recursion = Recursion {
+            index: PageTableIndex::new(510),
+            five_level: true,
+        };
+        let page = Page::<Size4KiB>::containing_address(VirtAddr::new(
+            (1 << 48) | (2 << 39) | (3 << 30) | (4 << 21) | (5 << 12),
+        ));
+        let table_addr = |level| {
+            recursion
+                .table_page(page.start_address(), level)
+                .start_address()
+                .as_u64()
+        };
+        assert_eq!(table_addr(5), 0xfffe_ff7f_bfdf_e000);
+        assert_eq!(table_addr(4), 0xfffe_ff7f_bfc0_1000);
+        assert_eq!(table_addr(3), 0xfffe_ff7f_8020_2000);
+        assert_eq!(table_addr(1), 0xfffe_0080_8060_4000);
+        assert_eq!(
+            p4_page(page, recursion),
+            Page::containing_address(VirtAddr::new(table_addr(4)))
+        );
+    }
 }
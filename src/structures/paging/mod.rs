@@ -3,7 +3,7 @@
 //! Page tables translate virtual memory “pages” to physical memory “frames”.
 
 pub use self::frame::PhysFrame;
-pub use self::frame_alloc::{FrameAllocator, FrameDeallocator};
+pub use self::frame_alloc::{BumpFrameAllocator, FrameAllocator, FrameDeallocator};
 #[doc(no_inline)]
 pub use self::mapper::MappedPageTable;
 #[cfg(target_pointer_width = "64")]
@@ -13,7 +13,7 @@ pub use self::mapper::OffsetPageTable;
 #[doc(no_inline)]
 pub use self::mapper::RecursivePageTable;
 pub use self::mapper::{Mapper, Translate};
-pub use self::page::{Page, PageSize, Size1GiB, Size2MiB, Size4KiB};
+pub use self::page::{largest_pages, Page, PageKind, PageSize, Size1GiB, Size2MiB, Size4KiB};
 pub use self::page_table::{PageOffset, PageTable, PageTableFlags, PageTableIndex};
 
 pub mod frame;
@@ -21,3 +21,40 @@ mod frame_alloc;
 pub mod mapper;
 pub mod page;
 pub mod page_table;
+
+/// Returns a mutable reference to the active level 4 table, whose frame is read from
+/// [`Cr3`](crate::registers::control::Cr3) and accessed through the given physical memory
+/// offset.
+///
+/// This is useful for creating an [`OffsetPageTable`] for the active page table:
+///
+/// ```no_run
+/// use x86_64::structures::paging::{active_level_4_table, OffsetPageTable};
+/// use x86_64::VirtAddr;
+///
+/// # let physical_memory_offset = VirtAddr::new(0xffff_8000_0000_0000);
+/// let mapper = unsafe {
+///     let level_4_table = active_level_4_table(physical_memory_offset);
+///     OffsetPageTable::new(level_4_table, physical_memory_offset)
+/// };
+/// ```
+///
+/// If 5-level paging is enabled, CR3 contains the level 5 table instead, which is returned
+/// by this function in that case.
+///
+/// ## Safety
+///
+/// The complete physical memory must be mapped to virtual memory at the given
+/// `physical_memory_offset`. This function must only be called once to avoid aliasing
+/// `&mut` references (which is undefined behavior).
+#[cfg(all(feature = "instructions", target_arch = "x86_64"))]
+#[inline]
+pub unsafe fn active_level_4_table(
+    physical_memory_offset: crate::VirtAddr,
+) -> &'static mut PageTable {
+    use crate::registers::control::Cr3;
+
+    let (frame, _) = Cr3::read();
+    let virt = physical_memory_offset + frame.start_address().as_u64();
+    unsafe { &mut *virt.as_mut_ptr() }
+}
@@ -1,5 +1,6 @@
 //! Abstractions for default-sized and huge virtual memory pages.
 
+use crate::addr::VirtAddrNotValid;
 use crate::sealed::Sealed;
 use crate::structures::paging::page_table::PageTableLevel;
 use crate::structures::paging::PageTableIndex;
@@ -123,6 +124,14 @@ impl<S: PageSize> Page<S> {
         S::SIZE
     }
 
+    /// Returns the level 5 page table index of this page.
+    #[cfg(feature = "la57")]
+    #[inline]
+    #[rustversion::attr(since(1.61), const)]
+    pub fn p5_index(self) -> PageTableIndex {
+        self.start_address().p5_index()
+    }
+
     /// Returns the level 4 page table index of this page.
     #[inline]
     #[rustversion::attr(since(1.61), const)]
@@ -254,6 +263,27 @@ impl Page<Size4KiB> {
     }
 }
 
+#[cfg(feature = "serde")]
+impl<S: PageSize> serde::Serialize for Page<S> {
+    fn serialize<Ser: serde::Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        serde::Serialize::serialize(&self.start_address, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, S: PageSize> serde::Deserialize<'de> for Page<S> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let addr = <VirtAddr as serde::Deserialize>::deserialize(deserializer)?;
+        Page::from_start_address(addr).map_err(|_| {
+            serde::de::Error::custom(format_args!(
+                "address {:#x} is not aligned to the page size {}",
+                addr.as_u64(),
+                S::DEBUG_STR
+            ))
+        })
+    }
+}
+
 impl<S: PageSize> fmt::Debug for Page<S> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.write_fmt(format_args!(
@@ -356,6 +386,12 @@ impl<S: PageSize> PageRange<S> {
     pub fn size(&self) -> u64 {
         S::SIZE * self.len()
     }
+
+    /// Returns whether the given page is part of this range.
+    #[inline]
+    pub fn contains(&self, page: Page<S>) -> bool {
+        self.start <= page && page < self.end
+    }
 }
 
 impl<S: PageSize> Iterator for PageRange<S> {
@@ -425,6 +461,12 @@ impl<S: PageSize> PageRangeInclusive<S> {
     pub fn size(&self) -> u64 {
         S::SIZE * self.len()
     }
+
+    /// Returns whether the given page is part of this range.
+    #[inline]
+    pub fn contains(&self, page: Page<S>) -> bool {
+        self.start <= page && page <= self.end
+    }
 }
 
 impl<S: PageSize> Iterator for PageRangeInclusive<S> {
@@ -460,8 +502,127 @@ impl<S: PageSize> fmt::Debug for PageRangeInclusive<S> {
     }
 }
 
+/// A page of one of the three page sizes, as returned by [`largest_pages`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PageKind {
+    /// A 4KiB page.
+    Size4KiB(Page<Size4KiB>),
+    /// A 2MiB page.
+    Size2MiB(Page<Size2MiB>),
+    /// A 1GiB page.
+    Size1GiB(Page<Size1GiB>),
+}
+
+impl PageKind {
+    /// Returns the start address of the page.
+    #[inline]
+    pub fn start_address(self) -> VirtAddr {
+        match self {
+            PageKind::Size4KiB(page) => page.start_address(),
+            PageKind::Size2MiB(page) => page.start_address(),
+            PageKind::Size1GiB(page) => page.start_address(),
+        }
+    }
+
+    /// Returns the size of the page in bytes.
+    #[inline]
+    pub fn size(self) -> u64 {
+        match self {
+            PageKind::Size4KiB(_) => Size4KiB::SIZE,
+            PageKind::Size2MiB(_) => Size2MiB::SIZE,
+            PageKind::Size1GiB(_) => Size1GiB::SIZE,
+        }
+    }
+}
+
+/// Splits the memory region of `len` bytes starting at `start` into pages, using the largest
+/// page size that the alignment and the remaining length allow.
+///
+/// The region is extended to 4KiB boundaries, so the returned pages cover all bytes of the
+/// region. When mapping a physical memory region, note that a huge page can only be used if
+/// the physical address is aligned to the same page size.
+///
+/// Returns an error if the region doesn't fit into the canonical half of the address space
+/// that contains `start`, i.e. if it runs into the non-canonical hole or beyond the end of the
+/// address space. The error contains the last address of the region (or `u64::MAX` if that
+/// address would overflow).
+///
+/// ```
+/// use x86_64::structures::paging::{largest_pages, PageKind};
+/// use x86_64::VirtAddr;
+///
+/// let mut pages = largest_pages(VirtAddr::new(0x1ff000), 0x201000).unwrap();
+/// assert!(matches!(pages.next(), Some(PageKind::Size4KiB(_))));
+/// assert!(matches!(pages.next(), Some(PageKind::Size2MiB(_))));
+/// assert_eq!(pages.next(), None);
+/// ```
+#[inline]
+pub fn largest_pages(start: VirtAddr, len: u64) -> Result<LargestPages, VirtAddrNotValid> {
+    let aligned_start = start.align_down(Size4KiB::SIZE);
+    if len == 0 {
+        return Ok(LargestPages {
+            addr: aligned_start.as_u64(),
+            remaining: 0,
+        });
+    }
+
+    let last = start
+        .as_u64()
+        .checked_add(len - 1)
+        .ok_or(VirtAddrNotValid(u64::MAX))?;
+    let last = VirtAddr::try_new(last)?;
+    // Canonical addresses in different halves are separated by the non-canonical hole.
+    if (start.as_u64() ^ last.as_u64()) >> 63 != 0 {
+        return Err(VirtAddrNotValid(last.as_u64()));
+    }
+
+    // The last page ends at most at the end of the address space, so this doesn't overflow.
+    let remaining = (last.align_down(Size4KiB::SIZE) - aligned_start) + Size4KiB::SIZE;
+    Ok(LargestPages {
+        addr: aligned_start.as_u64(),
+        remaining,
+    })
+}
+
+/// An iterator over the pages of a memory region, created by [`largest_pages`].
+#[derive(Debug, Clone)]
+pub struct LargestPages {
+    addr: u64,
+    remaining: u64,
+}
+
+impl Iterator for LargestPages {
+    type Item = PageKind;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let fits = |size: u64| self.addr % size == 0 && self.remaining >= size;
+        let addr = VirtAddr::new(self.addr);
+        // SAFETY: The alignment is checked before creating each page.
+        let page = unsafe {
+            if fits(Size1GiB::SIZE) {
+                PageKind::Size1GiB(Page::from_start_address_unchecked(addr))
+            } else if fits(Size2MiB::SIZE) {
+                PageKind::Size2MiB(Page::from_start_address_unchecked(addr))
+            } else {
+                PageKind::Size4KiB(Page::from_start_address_unchecked(addr))
+            }
+        };
+
+        // The address wraps around after the last page of the address space, but then there
+        // are no remaining pages.
+        self.addr = self.addr.wrapping_add(page.size());
+        self.remaining -= page.size();
+        Some(page)
+    }
+}
+
 /// The given address was not sufficiently aligned.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct AddressNotAligned;
 
 impl fmt::Display for AddressNotAligned {
@@ -476,6 +637,60 @@ mod tests {
 
     fn test_is_hash<T: core::hash::Hash>() {}
 
+    #[test]
+    fn largest_pages_mixed() {
+        let start = VirtAddr::new(0x3fdf_f000);
+        let len = Size4KiB::SIZE + Size2MiB::SIZE + Size1GiB::SIZE + Size2MiB::SIZE + 1;
+        let pages: [PageKind; 5] = {
+            let mut iter = largest_pages(start, len).unwrap();
+            let pages = [(); 5].map(|_| iter.next().unwrap());
+            assert_eq!(iter.next(), None);
+            pages
+        };
+        let expected = [
+            (0x3fdf_f000, Size4KiB::SIZE),
+            (0x3fe0_0000, Size2MiB::SIZE),
+            (0x4000_0000, Size1GiB::SIZE),
+            (0x8000_0000, Size2MiB::SIZE),
+            (0x8020_0000, Size4KiB::SIZE),
+        ];
+        for (page, (addr, size)) in pages.iter().zip(expected.iter()) {
+            assert_eq!(page.start_address(), VirtAddr::new(*addr));
+            assert_eq!(page.size(), *size);
+        }
+
+        // A 2MiB-aligned region that is too short for a huge page.
+        let pages = largest_pages(VirtAddr::new(0x20_0000), Size2MiB::SIZE - Size4KiB::SIZE);
+        assert_eq!(pages.unwrap().count(), 511);
+        assert_eq!(
+            largest_pages(VirtAddr::new(0x1234), 0).unwrap().next(),
+            None
+        );
+    }
+
+    #[test]
+    fn largest_pages_non_canonical() {
+        #[cfg(not(feature = "la57"))]
+        let end_of_lower_half = 0x8000_0000_0000;
+        #[cfg(feature = "la57")]
+        let end_of_lower_half = 0x0100_0000_0000_0000;
+
+        let start = VirtAddr::new(end_of_lower_half - 0x1000);
+        assert_eq!(largest_pages(start, 0x1000).unwrap().count(), 1);
+        assert_eq!(
+            largest_pages(start, 0x2000).unwrap_err(),
+            VirtAddrNotValid(end_of_lower_half + 0xfff)
+        );
+
+        let last_page = VirtAddr::new(0xffff_ffff_ffff_f000);
+        let pages = largest_pages(last_page, 0x1000).unwrap();
+        assert_eq!(pages.count(), 1);
+        assert_eq!(
+            largest_pages(last_page, 0x2000).unwrap_err(),
+            VirtAddrNotValid(u64::MAX)
+        );
+    }
+
     #[test]
     pub fn test_page_is_hash() {
         test_is_hash::<Page<Size4KiB>>();
@@ -543,8 +758,61 @@ mod tests {
         assert_eq!(range_inclusive.len(), 51);
     }
 
+    #[test]
+    pub fn test_page_range_contains() {
+        let start = Page::<Size4KiB>::containing_address(VirtAddr::new(0x1000));
+        let end = start + 3;
+
+        let range = Page::range(start, end);
+        assert!(range.contains(start));
+        assert!(range.contains(end - 1));
+        assert!(!range.contains(end));
+        assert!(!range.contains(start - 1));
+
+        let range_inclusive = Page::range_inclusive(start, end);
+        assert!(range_inclusive.contains(start));
+        assert!(range_inclusive.contains(end));
+        assert!(!range_inclusive.contains(end + 1));
+
+        let mut pages = [end, start, start + 1];
+        pages.sort();
+        assert_eq!(pages, [start, start + 1, end]);
+    }
+
     #[test]
     #[cfg(feature = "step_trait")]
+    fn page_range_syntax() {
+        let start = Page::<Size4KiB>::containing_address(VirtAddr::new(0x1000));
+        let end = start + 3;
+        let mut pages = start..end;
+        assert_eq!(pages.nth(1).unwrap().start_address(), VirtAddr::new(0x2000));
+        assert_eq!((start..end).count(), 3);
+        assert_eq!((start..end).step_by(2).last(), Some(start + 2));
+
+        let last = Page::<Size4KiB>::containing_address(VirtAddr::new(0xffff_ffff_ffff_f000));
+        assert_eq!(Step::forward_checked(last, 1), None);
+        assert_eq!((last..=last).count(), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn deserialize_page() {
+        use serde::de::{value::Error, IntoDeserializer};
+        use serde::Deserialize;
+
+        let page = |addr: u64| {
+            Page::<Size2MiB>::deserialize(IntoDeserializer::<Error>::into_deserializer(addr))
+        };
+        assert_eq!(
+            page(0x20_0000).unwrap().start_address(),
+            VirtAddr::new(0x20_0000)
+        );
+        assert!(page(0x1000).is_err());
+        assert!(page(0x1234_0000_0000_0000).is_err());
+    }
+
+    #[test]
+    #[cfg(all(feature = "step_trait", not(feature = "la57")))]
     fn page_step_forward() {
         let test_cases = [
             (0, 0, Some(0)),
@@ -576,7 +844,7 @@ mod tests {
     }
 
     #[test]
-    #[cfg(feature = "step_trait")]
+    #[cfg(all(feature = "step_trait", not(feature = "la57")))]
     fn page_step_backwards() {
         let test_cases = [
             (0, 0, Some(0)),
@@ -605,7 +873,7 @@ mod tests {
     }
 
     #[test]
-    #[cfg(feature = "step_trait")]
+    #[cfg(all(feature = "step_trait", not(feature = "la57")))]
     fn page_steps_between() {
         let test_cases = [
             (0, 0, 0, Some(0)),
@@ -164,12 +164,24 @@ bitflags! {
         /// Available to the OS, can be used to store additional data, e.g. custom flags.
         const BIT_58 =          1 << 58;
         /// Available to the OS, can be used to store additional data, e.g. custom flags.
+        ///
+        /// Part of the protection key if protection keys are enabled in CR4, see
+        /// [`PageTableFlags::protection_key`].
         const BIT_59 =          1 << 59;
         /// Available to the OS, can be used to store additional data, e.g. custom flags.
+        ///
+        /// Part of the protection key if protection keys are enabled in CR4, see
+        /// [`PageTableFlags::protection_key`].
         const BIT_60 =          1 << 60;
         /// Available to the OS, can be used to store additional data, e.g. custom flags.
+        ///
+        /// Part of the protection key if protection keys are enabled in CR4, see
+        /// [`PageTableFlags::protection_key`].
         const BIT_61 =          1 << 61;
         /// Available to the OS, can be used to store additional data, e.g. custom flags.
+        ///
+        /// Part of the protection key if protection keys are enabled in CR4, see
+        /// [`PageTableFlags::protection_key`].
         const BIT_62 =          1 << 62;
         /// Forbid code execution from the mapped frames.
         ///
@@ -179,6 +191,51 @@ bitflags! {
     }
 }
 
+impl PageTableFlags {
+    /// The bits that hold the protection key (bits 59 to 62).
+    const PROTECTION_KEY_MASK: u64 = 0xf << 59;
+
+    /// Returns the protection key stored in bits 59 to 62.
+    ///
+    /// The protection key is only used by the processor if protection keys are enabled in CR4
+    /// (`PROTECTION_KEY_USER` for user pages, `PROTECTION_KEY_SUPERVISOR` for supervisor pages).
+    /// Otherwise these bits are available to the OS.
+    #[inline]
+    pub const fn protection_key(&self) -> u8 {
+        ((self.bits() & Self::PROTECTION_KEY_MASK) >> 59) as u8
+    }
+
+    /// Sets the protection key stored in bits 59 to 62, without modifying the other flags.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `key` doesn't fit into 4 bits.
+    #[inline]
+    pub fn set_protection_key(&mut self, key: u8) {
+        assert!(key < 16, "protection keys must be smaller than 16");
+        *self = Self::from_bits_retain(
+            (self.bits() & !Self::PROTECTION_KEY_MASK) | (u64::from(key) << 59),
+        );
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for PageTableFlags {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.bits())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for PageTableFlags {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bits = <u64 as serde::Deserialize>::deserialize(deserializer)?;
+        PageTableFlags::from_bits(bits).ok_or_else(|| {
+            serde::de::Error::custom(format_args!("invalid page table flags {:#x}", bits))
+        })
+    }
+}
+
 /// The number of entries in a page table.
 const ENTRY_COUNT: usize = 512;
 
@@ -466,3 +523,39 @@ impl PageTableLevel {
         1u64 << (((self as u8 - 1) * 9) + 12)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn protection_key() {
+        let mut entry = PageTableEntry::new();
+        let addr = PhysAddr::new(0x1234_6000);
+        let flags = PageTableFlags::PRESENT
+            | PageTableFlags::WRITABLE
+            | PageTableFlags::BIT_58
+            | PageTableFlags::NO_EXECUTE;
+        entry.set_addr(addr, flags);
+        assert_eq!(entry.flags().protection_key(), 0);
+
+        for key in [0xf, 0x5, 0xa, 0x0] {
+            let mut new_flags = entry.flags();
+            new_flags.set_protection_key(key);
+            entry.set_flags(new_flags);
+            assert_eq!(entry.flags().protection_key(), key);
+            assert_eq!(entry.addr(), addr);
+            assert_eq!(
+                entry.flags() - PageTableFlags::from_bits_retain(0xf << 59),
+                flags
+            );
+        }
+        assert_eq!(entry.flags(), flags);
+    }
+
+    #[test]
+    #[should_panic]
+    fn protection_key_too_large() {
+        PageTableFlags::empty().set_protection_key(16);
+    }
+}
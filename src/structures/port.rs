@@ -1,10 +1,20 @@
 //! Traits for accessing I/O ports.
 
+use crate::sealed::Sealed;
+
 /// A helper trait that implements the read port operation.
 ///
 /// On x86, I/O ports operate on either `u8` (via `inb`/`outb`), `u16` (via `inw`/`outw`),
 /// or `u32` (via `inl`/`outl`). Therefore this trait is implemented for exactly these types.
-pub trait PortRead {
+/// It is sealed, so it can't be implemented for other types.
+#[rustversion::attr(
+    since(1.78),
+    diagnostic::on_unimplemented(
+        message = "`{Self}` is not a valid I/O port width",
+        note = "I/O ports can only be read as `u8`, `u16`, or `u32`"
+    )
+)]
+pub trait PortRead: Sealed {
     /// Reads a `Self` value from the given port.
     ///
     /// ## Safety
@@ -18,7 +28,15 @@ pub trait PortRead {
 ///
 /// On x86, I/O ports operate on either `u8` (via `inb`/`outb`), `u16` (via `inw`/`outw`),
 /// or `u32` (via `inl`/`outl`). Therefore this trait is implemented for exactly these types.
-pub trait PortWrite {
+/// It is sealed, so it can't be implemented for other types.
+#[rustversion::attr(
+    since(1.78),
+    diagnostic::on_unimplemented(
+        message = "`{Self}` is not a valid I/O port width",
+        note = "I/O ports can only be written as `u8`, `u16`, or `u32`"
+    )
+)]
+pub trait PortWrite: Sealed {
     /// Writes a `Self` value to the given port.
     ///
     /// ## Safety
@@ -27,3 +45,14 @@ pub trait PortWrite {
     /// safety.
     unsafe fn write_to_port(port: u16, value: Self);
 }
+
+/// A helper trait for types that can be both read from and written to I/O ports.
+///
+/// This trait is implemented for all types that implement both [`PortRead`] and [`PortWrite`].
+pub trait PortReadWrite: PortRead + PortWrite {}
+
+impl<T: PortRead + PortWrite> PortReadWrite for T {}
+
+impl Sealed for u8 {}
+impl Sealed for u16 {}
+impl Sealed for u32 {}
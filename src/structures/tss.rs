@@ -1,14 +1,22 @@
 //! Provides a type for the task state segment structure.
 
-use crate::VirtAddr;
+use crate::{PrivilegeLevel, VirtAddr};
 use core::mem::size_of;
+use core::ops::RangeInclusive;
+use core::ptr::addr_of_mut;
 
 /// In 64-bit mode the TSS holds information that is not
 /// directly related to the task-switch mechanism,
 /// but is used for stack switching when an interrupt or exception occurs.
+///
+/// The `N` const generic parameter is the size of the I/O permission bitmap
+/// in bytes. Each bit of the bitmap corresponds to one I/O port (bit 0 of
+/// byte 0 is port 0), and a set bit denies access to that port when the CPL
+/// is greater than the IOPL. Ports not covered by the bitmap are always
+/// denied, so an `N` of 8192 is needed to cover all 65536 ports.
 #[derive(Debug, Clone, Copy)]
-#[repr(C, packed(4))]
-pub struct TaskStateSegment {
+#[repr(C, packed)]
+pub struct TaskStateSegment<const N: usize = 0> {
     reserved_1: u32,
     /// The full 64-bit canonical forms of the stack pointers (RSP) for privilege levels 0-2.
     /// The stack pointers used when a privilege level change occurs from a lower privilege level to a higher one.
@@ -21,27 +29,271 @@ pub struct TaskStateSegment {
     reserved_4: u16,
     /// The 16-bit offset to the I/O permission bit map from the 64-bit TSS base.
     pub iomap_base: u16,
+    /// The I/O permission bitmap. A set bit denies access to the corresponding port.
+    pub iomap: [u8; N],
+    /// The processor may read two bytes of the bitmap at once, so the SDM
+    /// requires the bitmap to be followed by a byte with all bits set.
+    iomap_last_byte: u8,
 }
 
-impl TaskStateSegment {
+/// A TSS with an I/O permission bitmap that covers all 65536 ports.
+///
+/// This type is more than 8KiB large, see [`TaskStateSegment::new_boxed`] for creating it on
+/// the heap.
+#[cfg_attr(not(feature = "alloc"), allow(rustdoc::broken_intra_doc_links))]
+pub type FullIoTss = TaskStateSegment<8192>;
+
+impl<const N: usize> TaskStateSegment<N> {
+    /// The offset of the I/O permission bitmap from the TSS base, i.e. the size of the fixed
+    /// part of the TSS.
+    pub const IOMAP_BASE: u16 = (size_of::<TaskStateSegment>() - 1) as u16;
+
+    /// Returns the segment limit of the TSS, i.e. `size_of::<TaskStateSegment<N>>() - 1`.
+    ///
+    /// This is the limit used by [`Descriptor::tss_segment`](crate::structures::gdt::Descriptor::tss_segment).
+    /// As a const fn, it can be used to check the layout at compile time:
+    ///
+    /// ```
+    /// use x86_64::structures::tss::TaskStateSegment;
+    ///
+    /// const _: () = assert!(TaskStateSegment::<8192>::limit() == 0x2068);
+    /// ```
+    #[inline]
+    pub const fn limit() -> u16 {
+        (size_of::<Self>() - 1) as u16
+    }
+
     /// Creates a new TSS with zeroed privilege and interrupt stack table and an
-    /// empty I/O-Permission Bitmap.
+    /// `N` byte I/O-Permission Bitmap denying access to all ports.
     ///
-    /// As we always set the TSS segment limit to
-    /// `size_of::<TaskStateSegment>() - 1`, this means that `iomap_base` is
-    /// initialized to `size_of::<TaskStateSegment>()`.
+    /// Use [`TaskStateSegment::new`] for a TSS without a bitmap. As we always set the TSS segment limit to
+    /// `size_of::<TaskStateSegment<N>>() - 1`, the bitmap directly follows the
+    /// fixed part of the TSS, so `iomap_base` is initialized to the offset of
+    /// the `iomap` field.
     #[inline]
-    pub const fn new() -> TaskStateSegment {
+    pub const fn new_with_iomap() -> Self {
+        // TODO: Replace with compiler error when feature(generic_const_exprs) is stable.
+        assert!(
+            N <= 8192,
+            "An I/O permission bitmap can be at most 8192 bytes long"
+        );
+
         TaskStateSegment {
             privilege_stack_table: [VirtAddr::zero(); 3],
             interrupt_stack_table: [VirtAddr::zero(); 7],
-            iomap_base: size_of::<TaskStateSegment>() as u16,
+            // The fixed part of the TSS is everything but the bitmap and its
+            // terminating byte.
+            iomap_base: Self::IOMAP_BASE,
+            iomap: [0xff; N],
+            iomap_last_byte: 0xff,
             reserved_1: 0,
             reserved_2: 0,
             reserved_3: 0,
             reserved_4: 0,
         }
     }
+
+    /// Creates a new TSS like [`new_with_iomap`](Self::new_with_iomap), but allocates it directly on the heap.
+    ///
+    /// This avoids placing large TSSs (e.g. a [`FullIoTss`]) on the stack or in `.bss`. Since
+    /// the CPU accesses the TSS for the rest of the program once it is loaded, the box is
+    /// usually leaked to activate it. This should only be done once per CPU:
+    ///
+    /// ```
+    /// use x86_64::structures::tss::FullIoTss;
+    ///
+    /// let tss = Box::leak(FullIoTss::new_boxed());
+    /// let (tss, iomap) = tss.ready_to_activate();
+    /// iomap[0x60 / 8] &= !(1 << (0x60 % 8));
+    /// ```
+    ///
+    /// This method is only available with the `alloc` feature enabled.
+    #[cfg(feature = "alloc")]
+    pub fn new_boxed() -> alloc::boxed::Box<Self> {
+        use alloc::alloc::{alloc_zeroed, handle_alloc_error, Layout};
+
+        assert!(
+            N <= 8192,
+            "An I/O permission bitmap can be at most 8192 bytes long"
+        );
+
+        let layout = Layout::new::<Self>();
+        // SAFETY: The layout has a non-zero size.
+        let ptr = unsafe { alloc_zeroed(layout) }.cast::<Self>();
+        if ptr.is_null() {
+            handle_alloc_error(layout);
+        }
+        // SAFETY: The allocation is valid for a `Self`. Everything but `iomap_base`, the bitmap
+        // and its terminating byte is zero in a TSS returned by `new_with_iomap`; the latter two are
+        // contiguous because the struct is packed.
+        unsafe {
+            addr_of_mut!((*ptr).iomap_base).write_unaligned(Self::IOMAP_BASE);
+            addr_of_mut!((*ptr).iomap)
+                .cast::<u8>()
+                .write_bytes(0xff, N + 1);
+            alloc::boxed::Box::from_raw(ptr)
+        }
+    }
+
+    /// Allows access to the given ports, usable in const context.
+    ///
+    /// This is intended for building a TSS in a `static` initializer, so that
+    /// the I/O permission policy is baked into the binary:
+    ///
+    /// ```
+    /// use x86_64::structures::tss::TaskStateSegment;
+    ///
+    /// static TSS: TaskStateSegment<16> =
+    ///     TaskStateSegment::new_with_iomap().with_allowed_ports(&[0x60, 0x64]);
+    /// assert!(TSS.is_allowed(0x60));
+    /// assert!(!TSS.is_allowed(0x61));
+    /// ```
+    ///
+    /// Panics (at compile time when used in const context) if any of the ports
+    /// is not covered by the I/O permission bitmap.
+    #[inline]
+    pub const fn with_allowed_ports(mut self, ports: &[u16]) -> Self {
+        let mut i = 0;
+        while i < ports.len() {
+            let port = ports[i];
+            let byte = (port / 8) as usize;
+            assert!(byte < N, "port is outside of the I/O permission bitmap");
+            self.iomap[byte] &= !(1 << (port % 8));
+            i += 1;
+        }
+        self
+    }
+
+    /// Sets the stack that is used for interrupts with the given IST index.
+    ///
+    /// The index is the one that is stored in the IDT entry, so IST index 1
+    /// corresponds to `interrupt_stack_table[0]`.
+    #[inline]
+    pub fn set_ist(&mut self, index: IstIndex, stack_top: VirtAddr) {
+        self.interrupt_stack_table[index.table_index()] = stack_top;
+    }
+
+    /// Sets the stack that is used when a privilege level change to the
+    /// given level occurs.
+    ///
+    /// Panics if `level` is [`PrivilegeLevel::Ring3`], since there is no
+    /// privilege stack for ring 3.
+    #[inline]
+    pub fn set_privilege_stack(&mut self, level: PrivilegeLevel, stack_top: VirtAddr) {
+        assert!(
+            level != PrivilegeLevel::Ring3,
+            "there is no privilege stack for ring 3"
+        );
+        self.privilege_stack_table[level as usize] = stack_top;
+    }
+
+    /// Allows access to the given port from any privilege level.
+    ///
+    /// Panics if the port is not covered by the I/O permission bitmap.
+    #[inline]
+    pub fn allow_port(&mut self, port: u16) {
+        let (byte, bit) = Self::iomap_position(port);
+        self.iomap[byte] &= !(1 << bit);
+    }
+
+    /// Denies access to the given port if the CPL is greater than the IOPL.
+    ///
+    /// Panics if the port is not covered by the I/O permission bitmap.
+    #[inline]
+    pub fn deny_port(&mut self, port: u16) {
+        let (byte, bit) = Self::iomap_position(port);
+        self.iomap[byte] |= 1 << bit;
+    }
+
+    /// Returns whether the I/O permission bitmap allows access to the given
+    /// port.
+    ///
+    /// Ports that are not covered by the bitmap are always denied, so this
+    /// returns `false` for them.
+    #[inline]
+    pub fn is_allowed(&self, port: u16) -> bool {
+        let byte = usize::from(port / 8);
+        let bit = port % 8;
+        byte < N && self.iomap[byte] & (1 << bit) == 0
+    }
+
+    /// Allows access to all ports in the given range.
+    ///
+    /// Panics if any port of the range is not covered by the I/O permission
+    /// bitmap.
+    #[inline]
+    pub fn allow_range(&mut self, ports: RangeInclusive<u16>) {
+        for port in ports {
+            self.allow_port(port);
+        }
+    }
+
+    /// Denies access to all ports in the given range.
+    ///
+    /// Panics if any port of the range is not covered by the I/O permission
+    /// bitmap.
+    #[inline]
+    pub fn deny_range(&mut self, ports: RangeInclusive<u16>) {
+        for port in ports {
+            self.deny_port(port);
+        }
+    }
+
+    /// Freezes this TSS so that a descriptor can be created for it.
+    ///
+    /// Once a TSS is loaded, the processor reads it whenever it switches
+    /// stacks, so its stack tables must not be changed in the meantime. The
+    /// I/O permission bitmap, however, is meant to be updated while the TSS is
+    /// active (e.g. when switching between user processes), so a mutable
+    /// reference to it is returned alongside the [`ReadyTssPointer`].
+    #[inline]
+    pub fn ready_to_activate(&'static mut self) -> (ReadyTssPointer<N>, &'static mut [u8; N]) {
+        let (ptr, stacks) = self.ready_to_activate_full();
+        (ptr, stacks.iomap)
+    }
+
+    /// Like [`ready_to_activate`](Self::ready_to_activate), but additionally
+    /// allows updating the privilege and interrupt stack tables after the TSS
+    /// has been activated (e.g. to lazily allocate an IST stack).
+    #[inline]
+    pub fn ready_to_activate_full(&'static mut self) -> (ReadyTssPointer<N>, TssStacksMut<N>) {
+        let ptr: *mut Self = self;
+        // SAFETY: The pointer is derived from a `&'static mut` reference, so
+        // it is valid for the rest of the program. The returned field pointers
+        // and the `iomap` reference are disjoint, and `ReadyTssPointer` never
+        // creates references to any part of the TSS.
+        let stacks = unsafe {
+            TssStacksMut {
+                privilege_stack_table: addr_of_mut!((*ptr).privilege_stack_table).cast(),
+                interrupt_stack_table: addr_of_mut!((*ptr).interrupt_stack_table).cast(),
+                iomap: &mut *addr_of_mut!((*ptr).iomap),
+            }
+        };
+        (ReadyTssPointer(ptr), stacks)
+    }
+
+    /// Returns the byte index and bit offset of the given port in the bitmap.
+    fn iomap_position(port: u16) -> (usize, u16) {
+        let byte = usize::from(port / 8);
+        assert!(
+            byte < N,
+            "port {:#x} is outside of the I/O permission bitmap, which covers {} ports",
+            port,
+            N * 8
+        );
+        (byte, port % 8)
+    }
+}
+
+impl TaskStateSegment {
+    /// Creates a new TSS with zeroed privilege and interrupt stack table and no I/O-Permission
+    /// Bitmap, so all ports are denied if the CPL is greater than the IOPL.
+    ///
+    /// Use [`new_with_iomap`](Self::new_with_iomap) for a TSS with a bitmap.
+    #[inline]
+    pub const fn new() -> Self {
+        Self::new_with_iomap()
+    }
 }
 
 impl Default for TaskStateSegment {
@@ -51,6 +303,122 @@ impl Default for TaskStateSegment {
     }
 }
 
+/// An index into the interrupt stack table (IST).
+///
+/// IDT entries refer to IST stacks by a value between 1 and 7 (inclusive), as
+/// the value 0 means that no stack switch occurs. This index is stored in the
+/// IDT entry as-is, while the
+/// [`interrupt_stack_table`](TaskStateSegment::interrupt_stack_table) array is
+/// indexed with `index - 1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct IstIndex(u8);
+
+impl IstIndex {
+    /// Creates a new IST index.
+    ///
+    /// Panics if the index is not in the range 1..=7.
+    #[inline]
+    pub const fn new(index: u8) -> Self {
+        match Self::try_new(index) {
+            Some(index) => index,
+            None => panic!("IST index must be in the range 1..=7"),
+        }
+    }
+
+    /// Creates a new IST index, returning `None` if the index is not in the
+    /// range 1..=7.
+    #[inline]
+    pub const fn try_new(index: u8) -> Option<Self> {
+        match index {
+            1..=7 => Some(IstIndex(index)),
+            _ => None,
+        }
+    }
+
+    /// Returns the IST index as stored in an IDT entry.
+    #[inline]
+    pub const fn get(self) -> u8 {
+        self.0
+    }
+
+    /// Returns the index into the `interrupt_stack_table` array.
+    #[inline]
+    const fn table_index(self) -> usize {
+        self.0 as usize - 1
+    }
+}
+
+/// A pointer to a [`TaskStateSegment`] that lives for the rest of the program
+/// and that can no longer be modified through a `TaskStateSegment` reference.
+///
+/// This type can only be obtained through
+/// [`TaskStateSegment::ready_to_activate`] or
+/// [`TaskStateSegment::ready_to_activate_full`] and can be turned into a descriptor
+/// using [`GlobalDescriptorTable::append_tss`](crate::structures::gdt::GlobalDescriptorTable::append_tss).
+#[derive(Debug)]
+pub struct ReadyTssPointer<const N: usize>(*mut TaskStateSegment<N>);
+
+impl<const N: usize> ReadyTssPointer<N> {
+    /// Returns the pointer to the TSS.
+    #[inline]
+    pub const fn as_ptr(&self) -> *const TaskStateSegment<N> {
+        self.0
+    }
+}
+
+/// Mutable access to the parts of an activated [`TaskStateSegment`] that may
+/// still be changed, as returned by [`TaskStateSegment::ready_to_activate_full`].
+///
+/// The stack tables of a TSS are not necessarily aligned (the TSS is a packed
+/// structure), so they can't be handed out as references. Instead, this type
+/// provides setters that perform unaligned writes.
+#[derive(Debug)]
+pub struct TssStacksMut<const N: usize> {
+    privilege_stack_table: *mut VirtAddr,
+    interrupt_stack_table: *mut VirtAddr,
+    /// The I/O permission bitmap of the TSS.
+    pub iomap: &'static mut [u8; N],
+}
+
+impl<const N: usize> TssStacksMut<N> {
+    /// Sets the stack that is used for interrupts with the given IST index.
+    ///
+    /// The processor reads this value whenever an interrupt using this IST
+    /// index occurs, so the caller should make sure that no such interrupt can
+    /// happen while the stack is being changed.
+    #[inline]
+    pub fn set_ist(&mut self, index: IstIndex, stack_top: VirtAddr) {
+        // SAFETY: `table_index` is always smaller than 7 and the pointer is
+        // valid for the rest of the program.
+        unsafe {
+            self.interrupt_stack_table
+                .add(index.table_index())
+                .write_unaligned(stack_top)
+        };
+    }
+
+    /// Sets the stack that is used when a privilege level change to the
+    /// given level occurs.
+    ///
+    /// Panics if `level` is [`PrivilegeLevel::Ring3`], since there is no
+    /// privilege stack for ring 3.
+    #[inline]
+    pub fn set_privilege_stack(&mut self, level: PrivilegeLevel, stack_top: VirtAddr) {
+        assert!(
+            level != PrivilegeLevel::Ring3,
+            "there is no privilege stack for ring 3"
+        );
+        // SAFETY: `level` is smaller than 3 and the pointer is valid for the
+        // rest of the program.
+        unsafe {
+            self.privilege_stack_table
+                .add(level as usize)
+                .write_unaligned(stack_top)
+        };
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -58,7 +426,114 @@ mod tests {
     #[test]
     pub fn check_tss_size() {
         // Per the SDM, the minimum size of a TSS is 0x68 bytes, giving a
-        // minimum limit of 0x67.
-        assert_eq!(size_of::<TaskStateSegment>(), 0x68);
+        // minimum limit of 0x67. The I/O permission bitmap follows directly
+        // after, terminated by an additional byte.
+        assert_eq!({ TaskStateSegment::<0>::new().iomap_base }, 0x68);
+        assert_eq!(size_of::<TaskStateSegment>(), 0x69);
+        assert_eq!(size_of::<TaskStateSegment<8192>>(), 0x68 + 8192 + 1);
+        assert_eq!(TaskStateSegment::<0>::IOMAP_BASE, 0x68);
+        assert_eq!(TaskStateSegment::<0>::limit(), 0x68);
+        assert_eq!(TaskStateSegment::<16>::limit(), 0x68 + 16);
+    }
+
+    #[test]
+    pub fn new_without_iomap() {
+        // `new` must not require naming `N`.
+        let mut tss = TaskStateSegment::new();
+        tss.privilege_stack_table[0] = VirtAddr::new(0x1000);
+        assert_eq!({ tss.privilege_stack_table }[0], VirtAddr::new(0x1000));
+        assert_eq!({ TaskStateSegment::default().iomap_base }, 0x68);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    pub fn new_boxed() {
+        let boxed = FullIoTss::new_boxed();
+        let tss = FullIoTss::new_with_iomap();
+        assert_eq!({ boxed.iomap_base }, { tss.iomap_base });
+        assert_eq!(boxed.iomap, tss.iomap);
+        assert_eq!(boxed.iomap_last_byte, 0xff);
+        assert_eq!({ boxed.interrupt_stack_table }, [VirtAddr::zero(); 7]);
+    }
+
+    #[test]
+    pub fn set_stacks() {
+        let mut tss = TaskStateSegment::<0>::new();
+        tss.set_ist(IstIndex::new(1), VirtAddr::new(0x1000));
+        tss.set_ist(IstIndex::new(7), VirtAddr::new(0x7000));
+        tss.set_privilege_stack(PrivilegeLevel::Ring0, VirtAddr::new(0x8000));
+        let ist = tss.interrupt_stack_table;
+        assert_eq!(ist[0], VirtAddr::new(0x1000));
+        assert_eq!(ist[6], VirtAddr::new(0x7000));
+        assert_eq!({ tss.privilege_stack_table }[0], VirtAddr::new(0x8000));
+    }
+
+    #[test]
+    pub fn set_stacks_after_activation() {
+        let tss = Box::leak(Box::new(TaskStateSegment::<1>::new_with_iomap()));
+        let (ptr, mut stacks) = tss.ready_to_activate_full();
+        stacks.set_ist(IstIndex::new(2), VirtAddr::new(0x2000));
+        stacks.set_privilege_stack(PrivilegeLevel::Ring2, VirtAddr::new(0x3000));
+        stacks.iomap[0] = 0;
+
+        let tss = unsafe { ptr.as_ptr().read() };
+        assert_eq!({ tss.interrupt_stack_table }[1], VirtAddr::new(0x2000));
+        assert_eq!({ tss.privilege_stack_table }[2], VirtAddr::new(0x3000));
+        assert!(tss.is_allowed(0));
+    }
+
+    #[test]
+    pub fn ist_index_range() {
+        assert_eq!(IstIndex::try_new(0), None);
+        assert_eq!(IstIndex::try_new(1).map(IstIndex::get), Some(1));
+        assert_eq!(IstIndex::try_new(7).map(IstIndex::get), Some(7));
+        assert_eq!(IstIndex::try_new(8), None);
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn ring3_privilege_stack() {
+        let mut tss = TaskStateSegment::<0>::new();
+        tss.set_privilege_stack(PrivilegeLevel::Ring3, VirtAddr::new(0x8000));
+    }
+
+    #[test]
+    pub fn deny_port() {
+        let mut tss = TaskStateSegment::<16>::new_with_iomap();
+        tss.iomap = [0; 16];
+        tss.deny_port(0x60);
+        let iomap = tss.iomap;
+        for (i, &byte) in iomap.iter().enumerate() {
+            assert_eq!(byte, if i == 12 { 0b1 } else { 0 });
+        }
+        assert!(!tss.is_allowed(0x60));
+        assert!(tss.is_allowed(0x61));
+    }
+
+    #[test]
+    pub fn allow_range() {
+        let mut tss = TaskStateSegment::<2>::new_with_iomap();
+        assert!(!tss.is_allowed(3));
+        tss.allow_range(3..=9);
+        assert!((0..3).all(|port| !tss.is_allowed(port)));
+        assert!((3..=9).all(|port| tss.is_allowed(port)));
+        assert!((10..16).all(|port| !tss.is_allowed(port)));
+        assert!(!tss.is_allowed(16));
+        tss.deny_range(3..=9);
+        assert_eq!({ tss.iomap }, [0xff; 2]);
+    }
+
+    #[test]
+    pub fn const_allowed_ports() {
+        const TSS: TaskStateSegment<2> =
+            TaskStateSegment::new_with_iomap().with_allowed_ports(&[0, 9, 15]);
+        assert_eq!({ TSS.iomap }, [0b1111_1110, 0b0111_1101]);
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn allow_port_outside_iomap() {
+        let mut tss = TaskStateSegment::<2>::new_with_iomap();
+        tss.allow_port(16);
     }
 }